@@ -84,6 +84,21 @@ pub trait AppHandler {
         resources: &mut Resources,
     );
 
+    /// Called once when the app is about to exit, put anything that should be persisted here
+    fn shutdown(
+        &mut self,
+        world: &mut World,
+        resources: &mut Resources,
+    );
+
+    /// Called when the window is asked to close. Nothing is terminated unless the handler does it,
+    /// which lets it ask the user first
+    fn close_requested(
+        &mut self,
+        world: &mut World,
+        resources: &mut Resources,
+    );
+
     fn fatal_error(
         &mut self,
         error: &AppError,
@@ -199,10 +214,35 @@ impl App {
                 input_captured
             };
 
+            // Closing the window is left to the app handler rather than the input state, which
+            // would terminate immediately
+            let close_requested = match event {
+                winit::event::Event::WindowEvent {
+                    event: winit::event::WindowEvent::CloseRequested,
+                    ..
+                } => true,
+                _ => false,
+            };
+
             // if imgui didn't want the event, hand it off to the game
-            if !input_captured {
+            if !input_captured && !close_requested {
                 let mut input_state = resources.get_mut::<InputResource>().unwrap();
                 let mut app_control = resources.get_mut::<AppControlResource>().unwrap();
+
+                // Track key presses before the input state sees them so that repeats can be
+                // filtered out
+                if let winit::event::Event::WindowEvent {
+                    event: winit::event::WindowEvent::KeyboardInput { input, .. },
+                    ..
+                } = &event
+                {
+                    if let (winit::event::ElementState::Pressed, Some(key)) =
+                        (input.state, input.virtual_keycode)
+                    {
+                        input_state.record_key_pressed(key);
+                    }
+                }
+
                 input_state.input_state_mut().handle_winit_event(
                     &mut app_control,
                     &event,
@@ -212,6 +252,12 @@ impl App {
 
             // Handle general update/redraw events
             match event {
+                winit::event::Event::WindowEvent {
+                    event: winit::event::WindowEvent::CloseRequested,
+                    ..
+                } => {
+                    app_handler.close_requested(&mut world, &mut resources);
+                }
                 winit::event::Event::MainEventsCleared => {
                     {
                         let imgui_manager = resources.get_mut::<ImguiResource>().unwrap();
@@ -257,11 +303,14 @@ impl App {
             }
 
             // Always check if we should terminate the application
-            {
-                let app_control = resources.get::<AppControlResource>().unwrap();
-                if app_control.should_terminate_process() {
-                    *control_flow = winit::event_loop::ControlFlow::Exit
-                }
+            let should_terminate_process = resources
+                .get::<AppControlResource>()
+                .unwrap()
+                .should_terminate_process();
+
+            if should_terminate_process && *control_flow != winit::event_loop::ControlFlow::Exit {
+                app_handler.shutdown(&mut world, &mut resources);
+                *control_flow = winit::event_loop::ControlFlow::Exit
             }
         });
     }
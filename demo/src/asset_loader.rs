@@ -0,0 +1,117 @@
+use std::error::Error;
+
+use atelier_core::AssetUuid;
+use atelier_loader::{
+    crossbeam_channel::Receiver, handle::RefOp, rpc_loader::RpcLoader, LoadHandle, LoadStatus,
+    Loader,
+};
+use legion_prefab::CookedPrefab;
+
+use crate::asset_storage::GenericAssetStorage;
+
+/// The interface AssetResource uses to load assets. Assets can either come from the asset daemon
+/// (RpcLoader) or from a packfile built ahead of time (PackfileLoader), and the rest of the app
+/// doesn't need to know which.
+///
+/// This mirrors atelier_loader::Loader rather than using it, because PackfileLoader can't
+/// implement Loader. Loader::process hands each asset to AssetStorage::update_asset, which takes
+/// an AssetLoadOp, and only atelier_loader can create one. PackfileLoader gives assets to
+/// GenericAssetStorage::load_asset instead.
+pub trait AssetLoader: Send + Sync {
+    /// Adds a reference to an asset, requesting it if it isn't already loaded
+    fn add_ref(
+        &self,
+        id: AssetUuid,
+    ) -> LoadHandle;
+
+    fn get_load_status(
+        &self,
+        load_handle: LoadHandle,
+    ) -> LoadStatus;
+
+    /// Returns all loads that have been requested and not yet freed
+    fn get_active_loads(&self) -> Vec<LoadHandle>;
+
+    /// The ID of the asset the load handle was created for
+    fn asset_id(
+        &self,
+        load_handle: LoadHandle,
+    ) -> Option<AssetUuid>;
+
+    /// The number of references keeping the asset loaded
+    fn ref_count(
+        &self,
+        load_handle: LoadHandle,
+    ) -> u32;
+
+    /// Returns the prefab already cooked, if the loader has it. Otherwise the prefab must be
+    /// cooked from the prefab asset (see prefab_cooking)
+    fn load_cooked_prefab(
+        &self,
+        _prefab_id: AssetUuid,
+    ) -> Option<CookedPrefab> {
+        None
+    }
+
+    /// Applies reference count changes from handles that were cloned or dropped
+    fn process_ref_ops(
+        &self,
+        ref_ops: &Receiver<RefOp>,
+    );
+
+    /// Loads requested assets into the storage and frees assets that are no longer referenced
+    fn process(
+        &mut self,
+        storage: &GenericAssetStorage,
+    ) -> Result<(), Box<dyn Error>>;
+}
+
+impl AssetLoader for RpcLoader {
+    fn add_ref(
+        &self,
+        id: AssetUuid,
+    ) -> LoadHandle {
+        Loader::add_ref(self, id)
+    }
+
+    fn get_load_status(
+        &self,
+        load_handle: LoadHandle,
+    ) -> LoadStatus {
+        Loader::get_load_status(self, load_handle)
+    }
+
+    fn get_active_loads(&self) -> Vec<LoadHandle> {
+        Loader::get_active_loads(self)
+    }
+
+    fn asset_id(
+        &self,
+        load_handle: LoadHandle,
+    ) -> Option<AssetUuid> {
+        Loader::get_load_info(self, load_handle).map(|load_info| load_info.asset_id)
+    }
+
+    fn ref_count(
+        &self,
+        load_handle: LoadHandle,
+    ) -> u32 {
+        Loader::get_load_info(self, load_handle)
+            .map(|load_info| load_info.refs)
+            .unwrap_or(0)
+    }
+
+    fn process_ref_ops(
+        &self,
+        ref_ops: &Receiver<RefOp>,
+    ) {
+        atelier_loader::handle::process_ref_ops(self, ref_ops);
+    }
+
+    fn process(
+        &mut self,
+        storage: &GenericAssetStorage,
+    ) -> Result<(), Box<dyn Error>> {
+        Loader::process(self, storage)
+    }
+}
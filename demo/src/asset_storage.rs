@@ -1,154 +1,371 @@
 use atelier_loader::{
-    crossbeam_channel::Sender,
-    handle::{AssetHandle, RefOp, TypedAssetStorage},
+    crossbeam_channel::{self, Receiver, Sender},
+    handle::{AssetHandle, Handle, RefOp},
     AssetLoadOp, AssetStorage, AssetTypeId, LoadHandle, LoaderInfoProvider, TypeUuid,
 };
+use atelier_core::AssetUuid;
 use mopa::{mopafy, Any};
-use std::{sync::Mutex, collections::HashMap, error::Error, sync::Arc};
+use std::{
+    collections::HashMap,
+    error::Error,
+    sync::{Arc, Mutex, RwLock, Weak},
+};
+
+/// Sent to subscribers (see GenericAssetStorage::subscribe) when a new version of an asset is
+/// committed
+#[derive(Clone, Copy, Debug)]
+pub struct AssetVersionChanged {
+    pub load_handle: LoadHandle,
+
+    /// The version that was current before this one, or None if this is the first version of the
+    /// asset to finish loading
+    pub previous_version: Option<u32>,
 
+    pub version: u32,
+}
+
+/// Describes why the most recent load of an asset failed
+#[derive(Clone, Debug)]
+pub struct AssetLoadError {
+    /// The asset's ID, if the loader knows it
+    pub asset_id: Option<AssetUuid>,
+    pub error: String,
+}
+
+/// The serialized data of an asset, as it was received from the loader
+#[derive(Clone, Debug)]
+pub struct RecordedArtifact {
+    pub asset_type: AssetTypeId,
+    pub data: Vec<u8>,
+}
+
+/// Holds a storage for every registered asset type. The set of types is fixed once loading starts
+/// (add_storage requires &mut), so finding a type's storage doesn't need a lock. Each type's
+/// storage has its own locks, so loading one type doesn't block reads of another, and any number
+/// of threads can read the same type at once.
+///
+/// Typed reads return an Arc snapshot of the asset. Holding the snapshot pins that version of the
+/// asset: it stays valid even if the asset is reloaded or freed, and it can still be looked up by
+/// version number with get_pinned_version (for example, the previous_version of an
+/// AssetVersionChanged event). Old versions are dropped when the last snapshot of them is released.
 pub struct GenericAssetStorage {
-    storage: Mutex<HashMap<AssetTypeId, Box<dyn TypedStorage>>>,
+    storage: HashMap<AssetTypeId, Box<dyn TypedStorage>>,
     refop_sender: Arc<Sender<RefOp>>,
+
+    // Assets whose most recent load failed. An entry is removed when a version of the asset is
+    // committed or the asset is freed
+    load_errors: Mutex<HashMap<LoadHandle, AssetLoadError>>,
+
+    // If set, the serialized data of every asset that loads is copied here
+    recorded_artifacts: Mutex<Option<HashMap<LoadHandle, RecordedArtifact>>>,
 }
 
 impl GenericAssetStorage {
     pub fn new(refop_sender: Arc<Sender<RefOp>>) -> Self {
         Self {
-            storage: Mutex::new(HashMap::new()),
+            storage: HashMap::new(),
             refop_sender,
+            load_errors: Mutex::new(HashMap::new()),
+            recorded_artifacts: Mutex::new(None),
         }
     }
 
-    pub fn add_storage<T: TypeUuid + for<'a> serde::Deserialize<'a> + 'static + Send>(&self) {
-        let mut storages = self.storage.lock().unwrap();
-        storages.insert(
+    pub fn add_storage<T: TypeUuid + for<'a> serde::Deserialize<'a> + 'static + Send + Sync>(
+        &mut self
+    ) {
+        self.storage.insert(
             AssetTypeId(T::UUID),
             Box::new(Storage::<T>::new(self.refop_sender.clone())),
         );
     }
+
+    fn typed_storage<A: TypeUuid + 'static + Send + Sync>(&self) -> &Storage<A> {
+        self.storage
+            .get(&AssetTypeId(A::UUID))
+            .expect("unknown asset type")
+            .as_ref()
+            .downcast_ref::<Storage<A>>()
+            .expect("failed to downcast")
+    }
+
+    // Unlike typed_storage, this is reached with asset types that come from the asset daemon, so an
+    // unregistered type is an error rather than a bug
+    fn untyped_storage(
+        &self,
+        asset_type_id: &AssetTypeId,
+    ) -> Option<&dyn TypedStorage> {
+        self.storage
+            .get(asset_type_id)
+            .map(|storage| storage.as_ref())
+    }
+
+    /// Deserializes an asset's data and holds it until it is committed. This is the part of
+    /// AssetStorage::update_asset that doesn't depend on the loader, so loaders other than
+    /// atelier's (see PackfileLoader) can call it directly.
+    pub fn load_asset(
+        &self,
+        loader_info: &dyn LoaderInfoProvider,
+        asset_type_id: &AssetTypeId,
+        data: &[u8],
+        load_handle: LoadHandle,
+        version: u32,
+    ) -> Result<(), Box<dyn Error>> {
+        let result = match self.untyped_storage(asset_type_id) {
+            Some(storage) => storage.update_asset(loader_info, data, load_handle, version),
+            None => Err(format!(
+                "Unknown asset type {}. Add a storage for it with add_storage",
+                uuid::Uuid::from_bytes(asset_type_id.0)
+            )
+            .into()),
+        };
+
+        match &result {
+            Ok(()) => {
+                if let Some(recorded_artifacts) = &mut *self.recorded_artifacts.lock().unwrap() {
+                    recorded_artifacts.insert(
+                        load_handle,
+                        RecordedArtifact {
+                            asset_type: *asset_type_id,
+                            data: data.to_vec(),
+                        },
+                    );
+                }
+            }
+            Err(e) => {
+                // The loader only logs the error, so keep it around to be shown to the user
+                let asset_id = loader_info.get_asset_id(load_handle);
+                log::error!(
+                    "Failed to load asset {:?} ({:?}): {}",
+                    asset_id,
+                    load_handle,
+                    e
+                );
+                self.load_errors.lock().unwrap().insert(
+                    load_handle,
+                    AssetLoadError {
+                        asset_id,
+                        error: e.to_string(),
+                    },
+                );
+            }
+        }
+
+        result
+    }
+
+    /// After this is called, a copy of the serialized data of every asset that loads is kept. This
+    /// is used to pack the assets into a file (see packfile::pack_assets)
+    pub fn start_recording_artifacts(&self) {
+        *self.recorded_artifacts.lock().unwrap() = Some(HashMap::new());
+    }
+
+    /// Returns the data recorded since start_recording_artifacts and stops recording
+    pub fn take_recorded_artifacts(&self) -> HashMap<LoadHandle, RecordedArtifact> {
+        self.recorded_artifacts
+            .lock()
+            .unwrap()
+            .take()
+            .unwrap_or_default()
+    }
+
+    /// Returns why the most recent load of the asset failed, or None if it didn't
+    pub fn load_error(
+        &self,
+        load_handle: LoadHandle,
+    ) -> Option<AssetLoadError> {
+        self.load_errors.lock().unwrap().get(&load_handle).cloned()
+    }
+
+    /// Returns the latest version of the asset, or None if it hasn't finished loading
+    pub fn get<A: TypeUuid + 'static + Send + Sync>(
+        &self,
+        handle: &Handle<A>,
+    ) -> Option<Arc<A>> {
+        self.typed_storage::<A>()
+            .get_latest(handle.load_handle())
+            .map(|(asset, _)| asset)
+    }
+
+    /// Returns the latest version number of the asset, or None if it hasn't finished loading. The
+    /// version changes every time the asset is reloaded
+    pub fn get_version<A: TypeUuid + 'static + Send + Sync>(
+        &self,
+        handle: &Handle<A>,
+    ) -> Option<u32> {
+        self.typed_storage::<A>()
+            .get_latest(handle.load_handle())
+            .map(|(_, version)| version)
+    }
+
+    /// Returns the latest version of the asset and its version number together, so that they are
+    /// guaranteed to match
+    pub fn get_asset_with_version<A: TypeUuid + 'static + Send + Sync>(
+        &self,
+        handle: &Handle<A>,
+    ) -> Option<(Arc<A>, u32)> {
+        self.typed_storage::<A>().get_latest(handle.load_handle())
+    }
+
+    /// Returns a specific version of the asset. This succeeds for the latest version, and for any
+    /// older version that something is still holding a snapshot of. Once the last snapshot of an
+    /// old version is released, it can't be looked up anymore
+    pub fn get_pinned_version<A: TypeUuid + 'static + Send + Sync>(
+        &self,
+        handle: &Handle<A>,
+        version: u32,
+    ) -> Option<Arc<A>> {
+        self.typed_storage::<A>()
+            .get_version(handle.load_handle(), version)
+    }
+
+    /// Returns a channel that receives an event every time a new version of an asset of type A is
+    /// committed. Events are queued until they are read, so systems can check once per frame
+    pub fn subscribe<A: TypeUuid + 'static + Send + Sync>(&self) -> Receiver<AssetVersionChanged> {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        self.typed_storage::<A>()
+            .subscribers
+            .lock()
+            .unwrap()
+            .push(tx);
+        rx
+    }
+}
+
+// All versions of a single asset that are still alive
+struct AssetVersions<A> {
+    // The most recently committed version. Reads return this unless they ask for a specific
+    // version. This is None after the asset is freed
+    latest: Option<(u32, Arc<A>)>,
+
+    // Versions that were replaced or freed. Only weak references are kept so that each one is
+    // dropped when the last snapshot of it is released
+    previous: Vec<(u32, Weak<A>)>,
+}
+
+impl<A> AssetVersions<A> {
+    fn new() -> Self {
+        AssetVersions {
+            latest: None,
+            previous: vec![],
+        }
+    }
+
+    // Moves the latest version into the previous versions list
+    fn retire_latest(&mut self) {
+        if let Some((version, asset)) = self.latest.take() {
+            self.previous.push((version, Arc::downgrade(&asset)));
+        }
+    }
+
+    // Forgets about previous versions that nothing is holding anymore
+    fn remove_released(&mut self) {
+        self.previous.retain(|(_, asset)| asset.strong_count() > 0);
+    }
+
+    fn is_empty(&self) -> bool {
+        self.latest.is_none() && self.previous.is_empty()
+    }
 }
 
-struct AssetState<A> {
-    version: u32,
-    asset: A,
+// Removes released versions of every asset, and the entries of freed assets that no snapshots are
+// left of. Entries of freed assets that are still pinned are kept until a later commit or free
+// finds them released, so they don't stay in the map forever.
+fn remove_released_versions<A>(assets: &mut HashMap<LoadHandle, AssetVersions<A>>) {
+    assets.retain(|_, versions| {
+        versions.remove_released();
+        !versions.is_empty()
+    });
 }
+
 pub struct Storage<A: TypeUuid> {
     refop_sender: Arc<Sender<RefOp>>,
-    assets: HashMap<LoadHandle, AssetState<A>>,
-    uncommitted: HashMap<LoadHandle, AssetState<A>>,
+    assets: RwLock<HashMap<LoadHandle, AssetVersions<A>>>,
+
+    // Versions that have loaded but haven't been committed yet. While hot reloading, more than one
+    // version of the same asset can be waiting here
+    uncommitted: Mutex<HashMap<(LoadHandle, u32), Arc<A>>>,
+
+    subscribers: Mutex<Vec<Sender<AssetVersionChanged>>>,
 }
 impl<A: TypeUuid> Storage<A> {
     fn new(sender: Arc<Sender<RefOp>>) -> Self {
         Self {
             refop_sender: sender,
-            assets: HashMap::new(),
-            uncommitted: HashMap::new(),
+            assets: RwLock::new(HashMap::new()),
+            uncommitted: Mutex::new(HashMap::new()),
+            subscribers: Mutex::new(vec![]),
         }
     }
-    fn get<T: AssetHandle>(
-        &self,
-        handle: &T,
-    ) -> Option<&A> {
-        self.assets.get(&handle.load_handle()).map(|a| &a.asset)
-    }
-    fn get_version<T: AssetHandle>(
-        &self,
-        handle: &T,
-    ) -> Option<u32> {
-        self.assets.get(&handle.load_handle()).map(|a| a.version)
-    }
-    fn get_asset_with_version<T: AssetHandle>(
+    fn get_latest(
         &self,
-        handle: &T,
-    ) -> Option<(&A, u32)> {
+        load_handle: LoadHandle,
+    ) -> Option<(Arc<A>, u32)> {
+        // The lock is only held long enough to clone the Arc
         self.assets
-            .get(&handle.load_handle())
-            .map(|a| (&a.asset, a.version))
+            .read()
+            .unwrap()
+            .get(&load_handle)
+            .and_then(|versions| versions.latest.as_ref())
+            .map(|(version, asset)| (asset.clone(), *version))
     }
-}
-impl<A: TypeUuid + for<'a> serde::Deserialize<'a> + 'static + Send> TypedAssetStorage<A>
-    for GenericAssetStorage
-{
-    fn get<T: AssetHandle>(
-        &self,
-        handle: &T,
-    ) -> Option<&A> {
-        // This transmute can probably be unsound, but I don't have the energy to fix it right now
-        unsafe {
-            std::mem::transmute(
-                self.storage
-                    .lock()
-                    .unwrap()
-                    .get(&AssetTypeId(A::UUID))
-                    .expect("unknown asset type")
-                    .as_ref()
-                    .downcast_ref::<Storage<A>>()
-                    .expect("failed to downcast")
-                    .get(handle),
-            )
+    fn get_version(
+        &self,
+        load_handle: LoadHandle,
+        version: u32,
+    ) -> Option<Arc<A>> {
+        let assets = self.assets.read().unwrap();
+        let versions = assets.get(&load_handle)?;
+
+        if let Some((latest_version, asset)) = &versions.latest {
+            if *latest_version == version {
+                return Some(asset.clone());
+            }
         }
+
+        versions
+            .previous
+            .iter()
+            .find(|(previous_version, _)| *previous_version == version)
+            .and_then(|(_, asset)| asset.upgrade())
     }
-    fn get_version<T: AssetHandle>(
+    fn notify_subscribers(
         &self,
-        handle: &T,
-    ) -> Option<u32> {
-        self.storage
+        event: AssetVersionChanged,
+    ) {
+        // Subscribers that have dropped their receiver are removed
+        self.subscribers
             .lock()
             .unwrap()
-            .get(&AssetTypeId(A::UUID))
-            .expect("unknown asset type")
-            .as_ref()
-            .downcast_ref::<Storage<A>>()
-            .expect("failed to downcast")
-            .get_version(handle)
-    }
-    fn get_asset_with_version<T: AssetHandle>(
-        &self,
-        handle: &T,
-    ) -> Option<(&A, u32)> {
-        // This transmute can probably be unsound, but I don't have the energy to fix it right now
-        unsafe {
-            std::mem::transmute(
-                self.storage
-                    .lock()
-                    .unwrap()
-                    .get(&AssetTypeId(A::UUID))
-                    .expect("unknown asset type")
-                    .as_ref()
-                    .downcast_ref::<Storage<A>>()
-                    .expect("failed to downcast")
-                    .get_asset_with_version(handle),
-            )
-        }
+            .retain(|subscriber| subscriber.send(event).is_ok());
     }
 }
-pub trait TypedStorage: Any + Send {
+pub trait TypedStorage: Any + Send + Sync {
     fn update_asset(
-        &mut self,
+        &self,
         loader_info: &dyn LoaderInfoProvider,
         data: &[u8],
         load_handle: LoadHandle,
-        load_op: AssetLoadOp,
         version: u32,
     ) -> Result<(), Box<dyn Error>>;
     fn commit_asset_version(
-        &mut self,
+        &self,
         handle: LoadHandle,
         version: u32,
-    );
+    ) -> Result<(), Box<dyn Error>>;
     fn free(
-        &mut self,
+        &self,
         handle: LoadHandle,
     );
 }
 mopafy!(TypedStorage);
-impl<A: for<'a> serde::Deserialize<'a> + 'static + TypeUuid + Send> TypedStorage for Storage<A> {
+impl<A: for<'a> serde::Deserialize<'a> + 'static + TypeUuid + Send + Sync> TypedStorage
+    for Storage<A>
+{
     fn update_asset(
-        &mut self,
+        &self,
         loader_info: &dyn LoaderInfoProvider,
         data: &[u8],
         load_handle: LoadHandle,
-        load_op: AssetLoadOp,
         version: u32,
     ) -> Result<(), Box<dyn Error>> {
         // To enable automatic serde of Handle, we need to set up a SerdeContext with a RefOp sender
@@ -158,35 +375,81 @@ impl<A: for<'a> serde::Deserialize<'a> + 'static + TypeUuid + Send> TypedStorage
             || bincode::deserialize::<A>(data),
         )?;
         self.uncommitted
-            .insert(load_handle, AssetState { asset, version });
+            .lock()
+            .unwrap()
+            .insert((load_handle, version), Arc::new(asset));
         log::info!("{} bytes loaded for {:?}", data.len(), load_handle);
-        // The loading process could be async, in which case you can delay
-        // calling `load_op.complete` as it should only be done when the asset is usable.
-        load_op.complete();
         Ok(())
     }
     fn commit_asset_version(
-        &mut self,
+        &self,
         load_handle: LoadHandle,
-        _version: u32,
-    ) {
+        version: u32,
+    ) -> Result<(), Box<dyn Error>> {
         // The commit step is done after an asset load has completed.
         // It exists to avoid frames where an asset that was loaded is unloaded, which
-        // could happen when hot reloading. To support this case, you must support having multiple
-        // versions of an asset loaded at the same time.
-        self.assets.insert(
+        // could happen when hot reloading. The version being replaced stays alive for as long as
+        // any reader holds a snapshot of it.
+        let asset = {
+            let mut uncommitted = self.uncommitted.lock().unwrap();
+            let asset = uncommitted.remove(&(load_handle, version)).ok_or_else(|| {
+                format!(
+                    "Version {} of {:?} was committed but never loaded",
+                    version, load_handle
+                )
+            })?;
+
+            // Any older version that is still waiting has been superseded and will never be
+            // committed
+            uncommitted.retain(|(uncommitted_load_handle, uncommitted_version), _| {
+                *uncommitted_load_handle != load_handle || *uncommitted_version > version
+            });
+
+            asset
+        };
+
+        let previous_version = {
+            let mut assets = self.assets.write().unwrap();
+            let versions = assets.entry(load_handle).or_insert_with(AssetVersions::new);
+            let previous_version = versions.latest.as_ref().map(|(version, _)| *version);
+            versions.retire_latest();
+            versions.latest = Some((version, asset));
+            remove_released_versions(&mut assets);
+            previous_version
+        };
+
+        log::info!(
+            "Commit {:?} version {} (previous version {:?})",
             load_handle,
-            self.uncommitted
-                .remove(&load_handle)
-                .expect("asset not present when committing"),
+            version,
+            previous_version
         );
-        log::info!("Commit {:?}", load_handle);
+
+        self.notify_subscribers(AssetVersionChanged {
+            load_handle,
+            previous_version,
+            version,
+        });
+
+        Ok(())
     }
     fn free(
-        &mut self,
+        &self,
         load_handle: LoadHandle,
     ) {
-        self.assets.remove(&load_handle);
+        self.uncommitted
+            .lock()
+            .unwrap()
+            .retain(|(uncommitted_load_handle, _), _| *uncommitted_load_handle != load_handle);
+
+        // Snapshots that are still held keep their version alive. The entry is removed once none
+        // are left
+        let mut assets = self.assets.write().unwrap();
+        if let Some(versions) = assets.get_mut(&load_handle) {
+            versions.retire_latest();
+        }
+        remove_released_versions(&mut assets);
+
         log::info!("Free {:?}", load_handle);
     }
 }
@@ -202,12 +465,12 @@ impl AssetStorage for GenericAssetStorage {
         load_op: AssetLoadOp,
         version: u32,
     ) -> Result<(), Box<dyn Error>> {
-        self.storage
-            .lock()
-            .unwrap()
-            .get_mut(asset_type_id)
-            .expect("unknown asset type")
-            .update_asset(loader_info, data, load_handle, load_op, version)
+        self.load_asset(loader_info, asset_type_id, data, load_handle, version)?;
+
+        // The loading process could be async, in which case you can delay
+        // calling `load_op.complete` as it should only be done when the asset is usable.
+        load_op.complete();
+        Ok(())
     }
     fn commit_asset_version(
         &self,
@@ -215,23 +478,38 @@ impl AssetStorage for GenericAssetStorage {
         load_handle: LoadHandle,
         version: u32,
     ) {
-        self.storage
-            .lock()
-            .unwrap()
-            .get_mut(asset_type)
-            .expect("unknown asset type")
-            .commit_asset_version(load_handle, version)
+        let result = match self.untyped_storage(asset_type) {
+            Some(storage) => storage.commit_asset_version(load_handle, version),
+            None => Err(format!("Commit of {:?} has an unknown asset type", load_handle).into()),
+        };
+
+        // AssetStorage::commit_asset_version can't return an error, so record it the same way as
+        // a failed load
+        match result {
+            Ok(()) => {
+                self.load_errors.lock().unwrap().remove(&load_handle);
+            }
+            Err(e) => {
+                log::error!("Failed to commit asset {:?}: {}", load_handle, e);
+                self.load_errors.lock().unwrap().insert(
+                    load_handle,
+                    AssetLoadError {
+                        asset_id: None,
+                        error: e.to_string(),
+                    },
+                );
+            }
+        }
     }
     fn free(
         &self,
         asset_type_id: &AssetTypeId,
         load_handle: LoadHandle,
     ) {
-        self.storage
-            .lock()
-            .unwrap()
-            .get_mut(asset_type_id)
-            .expect("unknown asset type")
-            .free(load_handle)
+        self.load_errors.lock().unwrap().remove(&load_handle);
+        match self.untyped_storage(asset_type_id) {
+            Some(storage) => storage.free(load_handle),
+            None => log::warn!("Free of {:?} has an unknown asset type", load_handle),
+        }
     }
 }
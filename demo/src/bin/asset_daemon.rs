@@ -0,0 +1,15 @@
+use structopt::StructOpt;
+
+use atelier_legion_demo::daemon;
+
+// Runs the asset daemon as its own process. Start the demo with --external-daemon to connect to
+// it rather than starting a daemon in the demo's process.
+fn main() {
+    let opt = daemon::AssetDaemonOpt::from_args();
+
+    env_logger::Builder::from_default_env()
+        .filter_level(log::LevelFilter::Info)
+        .init();
+
+    daemon::run(opt.into());
+}
@@ -0,0 +1,71 @@
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+use structopt::StructOpt;
+
+use atelier_legion_demo::daemon;
+use atelier_legion_demo::packfile;
+
+/// Packs every asset into a single file that the demo can load with --packfile, so that it can
+/// ship without the asset daemon or the source assets.
+#[derive(StructOpt)]
+struct AssetPackOpt {
+    /// Socket address of the asset daemon to load assets from
+    #[structopt(
+    long,
+    parse(try_from_str = daemon::parse_socket_addr),
+    default_value = daemon::DEFAULT_DAEMON_ADDRESS
+    )]
+    daemon_address: SocketAddr,
+    /// Connect to an asset daemon that is already running instead of starting one in this process.
+    #[structopt(long)]
+    external_daemon: bool,
+    /// Where to write the packfile
+    #[structopt(short, long, parse(from_os_str), default_value = "assets.pack")]
+    output: PathBuf,
+}
+
+fn main() {
+    let opt = AssetPackOpt::from_args();
+
+    env_logger::Builder::from_default_env()
+        .filter_level(log::LevelFilter::Info)
+        .init();
+
+    let embedded_daemon = if !opt.external_daemon {
+        let daemon_config = daemon::AssetDaemonConfig {
+            address: opt.daemon_address,
+            ..Default::default()
+        };
+
+        Some(daemon::spawn(daemon_config))
+    } else {
+        None
+    };
+
+    let packfile = packfile::pack_assets(opt.daemon_address);
+
+    if let Some(embedded_daemon) = embedded_daemon {
+        embedded_daemon.stop();
+    }
+
+    let packfile = match packfile {
+        Ok(packfile) => packfile,
+        Err(e) => {
+            log::error!("Failed to pack assets: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if let Err(e) = packfile.write(&opt.output) {
+        log::error!("Failed to write {}: {}", opt.output.display(), e);
+        std::process::exit(1);
+    }
+
+    log::info!(
+        "Packed {} assets and {} cooked prefabs into {}",
+        packfile.assets.len(),
+        packfile.cooked_prefabs.len(),
+        opt.output.display()
+    );
+}
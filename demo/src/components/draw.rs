@@ -6,7 +6,7 @@ use legion::entity::Entity;
 use ncollide2d::world::CollisionWorld;
 use legion::world::World;
 use ncollide2d::pipeline::{CollisionGroups, GeometricQueryType};
-use ncollide2d::shape::{Ball, Cuboid};
+use ncollide2d::shape::{Ball, ConvexPolygon, Cuboid};
 use ncollide2d::shape::ShapeHandle;
 use crate::components::{
     Position2DComponent, UniformScale2DComponent, NonUniformScale2DComponent, Rotation2DComponent,
@@ -16,20 +16,196 @@ use crate::math::Vec2;
 use crate::math::Vec4;
 use imgui_inspect_derive::Inspect;
 use legion::prelude::*;
-use crate::resources::OpenedPrefabState;
+use crate::resources::{AssetResource, OpenedPrefabState};
+use crate::pipeline::{ImageAsset, ImageColorSpace};
+use crate::pipeline::{
+    VectorFillRule, VectorLineCap, VectorLineJoin, VectorPaint, VectorPath, VectorPathCommand,
+    VectorShapeAsset, VectorSpreadMethod, VectorStroke, VectorTransform,
+};
+use atelier_loader::handle::Handle;
+use crate::handle_def::HandleDef;
+use legion_transaction::SpawnFrom;
+use crate::keep_changes::DefFromRuntime;
+use legion::storage::ComponentStorage;
+use legion::index::ComponentIndex;
+use std::ops::Range;
+
+// Whether a shape's outline is drawn, its inside, or both
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub enum PaintStyle {
+    Fill,
+    Stroke,
+    FillAndStroke,
+}
+
+// Shapes used to only be drawn as outlines, so that's the default
+impl Default for PaintStyle {
+    fn default() -> Self {
+        PaintStyle::Stroke
+    }
+}
+
+impl crate::inspect::InspectEnum for PaintStyle {
+    fn variants() -> &'static [(Self, &'static str)] {
+        &[
+            (PaintStyle::Fill, "Fill"),
+            (PaintStyle::Stroke, "Stroke"),
+            (PaintStyle::FillAndStroke, "Fill and stroke"),
+        ]
+    }
+}
+
+// How a shape is combined with what has already been drawn under it
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub enum PaintBlendMode {
+    Normal,
+    Multiply,
+    Screen,
+    Overlay,
+    Darken,
+    Lighten,
+    Add,
+}
+
+impl Default for PaintBlendMode {
+    fn default() -> Self {
+        PaintBlendMode::Normal
+    }
+}
+
+impl crate::inspect::InspectEnum for PaintBlendMode {
+    fn variants() -> &'static [(Self, &'static str)] {
+        &[
+            (PaintBlendMode::Normal, "Normal"),
+            (PaintBlendMode::Multiply, "Multiply"),
+            (PaintBlendMode::Screen, "Screen"),
+            (PaintBlendMode::Overlay, "Overlay"),
+            (PaintBlendMode::Darken, "Darken"),
+            (PaintBlendMode::Lighten, "Lighten"),
+            (PaintBlendMode::Add, "Add"),
+        ]
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub enum PaintGradientKind {
+    None,
+    Linear,
+    Radial,
+}
+
+impl Default for PaintGradientKind {
+    fn default() -> Self {
+        PaintGradientKind::None
+    }
+}
+
+impl crate::inspect::InspectEnum for PaintGradientKind {
+    fn variants() -> &'static [(Self, &'static str)] {
+        &[
+            (PaintGradientKind::None, "None"),
+            (PaintGradientKind::Linear, "Linear"),
+            (PaintGradientKind::Radial, "Radial"),
+        ]
+    }
+}
+
+impl crate::inspect::InspectEnum for VectorSpreadMethod {
+    fn variants() -> &'static [(Self, &'static str)] {
+        &[
+            (VectorSpreadMethod::Pad, "Pad"),
+            (VectorSpreadMethod::Reflect, "Reflect"),
+            (VectorSpreadMethod::Repeat, "Repeat"),
+        ]
+    }
+}
+
+impl crate::inspect::InspectEnum for VectorLineCap {
+    fn variants() -> &'static [(Self, &'static str)] {
+        &[
+            (VectorLineCap::Butt, "Butt"),
+            (VectorLineCap::Round, "Round"),
+            (VectorLineCap::Square, "Square"),
+        ]
+    }
+}
+
+impl crate::inspect::InspectEnum for VectorLineJoin {
+    fn variants() -> &'static [(Self, &'static str)] {
+        &[
+            (VectorLineJoin::Miter, "Miter"),
+            (VectorLineJoin::Round, "Round"),
+            (VectorLineJoin::Bevel, "Bevel"),
+        ]
+    }
+}
+
+// A gradient from the paint's color to end_color. The points are relative to the entity's position
+// and are not affected by its scale
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, SerdeDiff, PartialEq, Inspect, Default)]
+#[serde(default)]
+pub struct PaintGradientDef {
+    #[serde_diff(opaque)]
+    #[inspect(proxy_type = "crate::inspect::InspectMixedEnum")]
+    pub kind: PaintGradientKind,
+    // Where the gradient starts. For radial gradients, this is the center
+    #[serde_diff(opaque)]
+    pub start: Vec2,
+    // Where the gradient ends. For radial gradients, the distance from start is the radius
+    #[serde_diff(opaque)]
+    pub end: Vec2,
+    #[serde_diff(opaque)]
+    #[inspect(proxy_type = "crate::inspect::InspectColor")]
+    pub end_color: Vec4,
+    #[serde_diff(opaque)]
+    #[inspect(proxy_type = "crate::inspect::InspectMixedEnum")]
+    pub spread_method: VectorSpreadMethod,
+}
 
-// A utility struct to describe color for a skia shape
+// A utility struct to describe how a skia shape is filled and/or stroked. Fields missing from older
+// prefabs get their default, which matches how shapes were drawn before they existed
 #[derive(Clone, Copy, Debug, Serialize, Deserialize, SerdeDiff, PartialEq, Inspect, Default)]
+#[serde(default)]
 pub struct PaintDef {
     #[serde_diff(opaque)]
+    #[inspect(proxy_type = "crate::inspect::InspectColor")]
     pub color: Vec4,
+    #[serde_diff(opaque)]
+    #[inspect(proxy_type = "crate::inspect::InspectMixedEnum")]
+    pub style: PaintStyle,
+    #[inspect(proxy_type = "crate::inspect::InspectMixedF32")]
     pub stroke_width: f32,
+    #[serde_diff(opaque)]
+    #[inspect(proxy_type = "crate::inspect::InspectMixedEnum")]
+    pub line_cap: VectorLineCap,
+    #[serde_diff(opaque)]
+    #[inspect(proxy_type = "crate::inspect::InspectMixedEnum")]
+    pub line_join: VectorLineJoin,
+    // Length of the dashes along the stroke. 0 draws a solid line
+    #[inspect(proxy_type = "crate::inspect::InspectMixedF32")]
+    pub dash_length: f32,
+    // Length of the gaps between dashes. If 0, the gaps are as long as the dashes
+    #[inspect(proxy_type = "crate::inspect::InspectMixedF32")]
+    pub dash_gap: f32,
+    // How far into the dash pattern the stroke starts
+    #[inspect(proxy_type = "crate::inspect::InspectMixedF32")]
+    pub dash_offset: f32,
+    #[serde_diff(opaque)]
+    #[inspect(proxy_type = "crate::inspect::InspectMixedEnum")]
+    pub blend_mode: PaintBlendMode,
+    pub gradient: PaintGradientDef,
 }
 
 pub struct Paint(pub std::sync::Mutex<skia_safe::Paint>);
 unsafe impl Send for Paint {}
 unsafe impl Sync for Paint {}
 
+impl Clone for Paint {
+    fn clone(&self) -> Self {
+        Paint(std::sync::Mutex::new(self.0.lock().unwrap().clone()))
+    }
+}
+
 impl From<PaintDef> for Paint {
     fn from(from: PaintDef) -> Self {
         let color = skia_safe::Color4f::new(
@@ -41,8 +217,79 @@ impl From<PaintDef> for Paint {
 
         let mut paint = skia_safe::Paint::new(color, None);
         paint.set_anti_alias(true);
-        paint.set_style(skia_safe::paint::Style::Stroke);
+        paint.set_style(match from.style {
+            PaintStyle::Fill => skia_safe::paint::Style::Fill,
+            PaintStyle::Stroke => skia_safe::paint::Style::Stroke,
+            PaintStyle::FillAndStroke => skia_safe::paint::Style::StrokeAndFill,
+        });
         paint.set_stroke_width(from.stroke_width);
+        paint.set_stroke_cap(to_skia_cap(from.line_cap));
+        paint.set_stroke_join(to_skia_join(from.line_join));
+        paint.set_blend_mode(match from.blend_mode {
+            PaintBlendMode::Normal => skia_safe::BlendMode::SrcOver,
+            PaintBlendMode::Multiply => skia_safe::BlendMode::Multiply,
+            PaintBlendMode::Screen => skia_safe::BlendMode::Screen,
+            PaintBlendMode::Overlay => skia_safe::BlendMode::Overlay,
+            PaintBlendMode::Darken => skia_safe::BlendMode::Darken,
+            PaintBlendMode::Lighten => skia_safe::BlendMode::Lighten,
+            PaintBlendMode::Add => skia_safe::BlendMode::Plus,
+        });
+
+        if from.dash_length > 0.0 {
+            let dash_gap = if from.dash_gap > 0.0 {
+                from.dash_gap
+            } else {
+                from.dash_length
+            };
+
+            paint.set_path_effect(skia_safe::PathEffect::dash(
+                &[from.dash_length, dash_gap],
+                from.dash_offset,
+            ));
+        }
+
+        // The gradient starts at the paint's color. Both ends have their own alpha, so the paint's
+        // alpha is reset once the gradient is set
+        let gradient = &from.gradient;
+        let colors = [
+            color.to_color(),
+            skia_safe::Color4f::new(
+                gradient.end_color.x(),
+                gradient.end_color.y(),
+                gradient.end_color.z(),
+                gradient.end_color.w(),
+            )
+            .to_color(),
+        ];
+        let start = skia_safe::Point::new(gradient.start.x(), gradient.start.y());
+        let end = skia_safe::Point::new(gradient.end.x(), gradient.end.y());
+        let tile_mode = to_skia_tile_mode(gradient.spread_method);
+
+        let shader = match gradient.kind {
+            PaintGradientKind::None => None,
+            PaintGradientKind::Linear => skia_safe::gradient_shader::linear(
+                (start, end),
+                &colors[..],
+                None,
+                tile_mode,
+                None,
+                None,
+            ),
+            PaintGradientKind::Radial => skia_safe::gradient_shader::radial(
+                start,
+                (*gradient.end - *gradient.start).length(),
+                &colors[..],
+                None,
+                tile_mode,
+                None,
+                None,
+            ),
+        };
+
+        if shader.is_some() {
+            paint.set_shader(shader);
+            paint.set_alpha_f(1.0);
+        }
 
         Paint(std::sync::Mutex::new(paint))
     }
@@ -62,9 +309,12 @@ pub struct DrawSkiaBoxComponentDef {
 
 legion_prefab::register_component_type!(DrawSkiaBoxComponentDef);
 
+#[derive(Clone)]
 pub struct DrawSkiaBoxComponent {
     pub half_extents: Vec2,
     pub paint: Paint,
+    // What the paint was created from, since it can't be read back out of the skia paint
+    pub paint_def: PaintDef,
 }
 
 impl From<DrawSkiaBoxComponentDef> for DrawSkiaBoxComponent {
@@ -72,10 +322,20 @@ impl From<DrawSkiaBoxComponentDef> for DrawSkiaBoxComponent {
         DrawSkiaBoxComponent {
             half_extents: from.half_extents,
             paint: from.paint.into(),
+            paint_def: from.paint,
         }
     }
 }
 
+impl DefFromRuntime<DrawSkiaBoxComponent> for DrawSkiaBoxComponentDef {
+    fn def_from_runtime(runtime: &DrawSkiaBoxComponent) -> Option<Self> {
+        Some(DrawSkiaBoxComponentDef {
+            half_extents: runtime.half_extents,
+            paint: runtime.paint_def,
+        })
+    }
+}
+
 impl crate::selection::EditorSelectable for DrawSkiaBoxComponent {
     fn create_editor_selection_world(
         &self,
@@ -85,35 +345,45 @@ impl crate::selection::EditorSelectable for DrawSkiaBoxComponent {
         world: &World,
         entity: Entity,
     ) {
-        if let Some(position) = world.get_component::<Position2DComponent>(entity) {
-            let mut half_extents = *self.half_extents;
-
-            if let Some(uniform_scale) = world.get_component::<UniformScale2DComponent>(entity) {
-                half_extents *= uniform_scale.uniform_scale;
-            }
+        add_box_to_selection_world(collision_world, world, entity, self.half_extents);
+    }
+}
 
-            if let Some(non_uniform_scale) =
-                world.get_component::<NonUniformScale2DComponent>(entity)
-            {
-                half_extents *= *non_uniform_scale.non_uniform_scale;
-            }
+// Adds a box with the given half extents to the selection world, placed at the entity's position
+// and affected by its scale and rotation
+fn add_box_to_selection_world(
+    collision_world: &mut CollisionWorld<f32, Entity>,
+    world: &World,
+    entity: Entity,
+    half_extents: Vec2,
+) {
+    if let Some(position) = world.get_component::<Position2DComponent>(entity) {
+        let mut half_extents = *half_extents;
 
-            let mut rotation = 0.0;
-            if let Some(rotation_component) = world.get_component::<Rotation2DComponent>(entity) {
-                rotation = rotation_component.rotation;
-            }
+        if let Some(uniform_scale) = world.get_component::<UniformScale2DComponent>(entity) {
+            half_extents *= uniform_scale.uniform_scale;
+        }
 
-            let shape_handle =
-                ShapeHandle::new(Cuboid::new(crate::math::vec2_glam_to_glm(half_extents)));
+        if let Some(non_uniform_scale) = world.get_component::<NonUniformScale2DComponent>(entity)
+        {
+            half_extents *= *non_uniform_scale.non_uniform_scale;
+        }
 
-            collision_world.add(
-                ncollide2d::math::Isometry::new(position.position.into(), rotation),
-                shape_handle,
-                CollisionGroups::new(),
-                GeometricQueryType::Proximity(0.001),
-                entity,
-            );
+        let mut rotation = 0.0;
+        if let Some(rotation_component) = world.get_component::<Rotation2DComponent>(entity) {
+            rotation = rotation_component.rotation;
         }
+
+        let shape_handle =
+            ShapeHandle::new(Cuboid::new(crate::math::vec2_glam_to_glm(half_extents)));
+
+        collision_world.add(
+            ncollide2d::math::Isometry::new(position.position.into(), rotation),
+            shape_handle,
+            CollisionGroups::new(),
+            GeometricQueryType::Proximity(0.001),
+            entity,
+        );
     }
 }
 
@@ -124,15 +394,18 @@ impl crate::selection::EditorSelectable for DrawSkiaBoxComponent {
 #[derive(TypeUuid, Serialize, Deserialize, SerdeDiff, Debug, PartialEq, Clone, Inspect, Default)]
 #[uuid = "e47f9943-d5bf-4e1b-9601-13e47d7b737c"]
 pub struct DrawSkiaCircleComponentDef {
+    #[inspect(proxy_type = "crate::inspect::InspectMixedF32")]
     pub radius: f32,
     pub paint: PaintDef,
 }
 
 legion_prefab::register_component_type!(DrawSkiaCircleComponentDef);
 
+#[derive(Clone)]
 pub struct DrawSkiaCircleComponent {
     pub radius: f32,
     pub paint: Paint,
+    pub paint_def: PaintDef,
 }
 
 impl From<DrawSkiaCircleComponentDef> for DrawSkiaCircleComponent {
@@ -140,11 +413,21 @@ impl From<DrawSkiaCircleComponentDef> for DrawSkiaCircleComponent {
         let c = DrawSkiaCircleComponent {
             radius: from.radius,
             paint: from.paint.into(),
+            paint_def: from.paint,
         };
         c
     }
 }
 
+impl DefFromRuntime<DrawSkiaCircleComponent> for DrawSkiaCircleComponentDef {
+    fn def_from_runtime(runtime: &DrawSkiaCircleComponent) -> Option<Self> {
+        Some(DrawSkiaCircleComponentDef {
+            radius: runtime.radius,
+            paint: runtime.paint_def,
+        })
+    }
+}
+
 impl crate::selection::EditorSelectable for DrawSkiaCircleComponent {
     fn create_editor_selection_world(
         &self,
@@ -173,3 +456,946 @@ impl crate::selection::EditorSelectable for DrawSkiaCircleComponent {
         }
     }
 }
+
+//
+// Draw a box with rounded corners at the component's current location. Will be affected by scale
+// and rotation, if those components exist
+//
+#[derive(TypeUuid, Serialize, Deserialize, SerdeDiff, Debug, PartialEq, Clone, Inspect, Default)]
+#[uuid = "74883a88-040d-4594-a407-7458d8a7db6e"]
+pub struct DrawSkiaRoundedRectComponentDef {
+    #[serde_diff(opaque)]
+    pub half_extents: Vec2,
+    #[inspect(proxy_type = "crate::inspect::InspectMixedF32")]
+    pub corner_radius: f32,
+    pub paint: PaintDef,
+}
+
+legion_prefab::register_component_type!(DrawSkiaRoundedRectComponentDef);
+
+#[derive(Clone)]
+pub struct DrawSkiaRoundedRectComponent {
+    pub half_extents: Vec2,
+    pub corner_radius: f32,
+    pub paint: Paint,
+    pub paint_def: PaintDef,
+}
+
+impl From<DrawSkiaRoundedRectComponentDef> for DrawSkiaRoundedRectComponent {
+    fn from(from: DrawSkiaRoundedRectComponentDef) -> Self {
+        DrawSkiaRoundedRectComponent {
+            half_extents: from.half_extents,
+            corner_radius: from.corner_radius,
+            paint: from.paint.into(),
+            paint_def: from.paint,
+        }
+    }
+}
+
+impl DefFromRuntime<DrawSkiaRoundedRectComponent> for DrawSkiaRoundedRectComponentDef {
+    fn def_from_runtime(runtime: &DrawSkiaRoundedRectComponent) -> Option<Self> {
+        Some(DrawSkiaRoundedRectComponentDef {
+            half_extents: runtime.half_extents,
+            corner_radius: runtime.corner_radius,
+            paint: runtime.paint_def,
+        })
+    }
+}
+
+impl crate::selection::EditorSelectable for DrawSkiaRoundedRectComponent {
+    fn create_editor_selection_world(
+        &self,
+        collision_world: &mut CollisionWorld<f32, Entity>,
+        resources: &Resources,
+        opened_prefab: &OpenedPrefabState,
+        world: &World,
+        entity: Entity,
+    ) {
+        // The corners are close enough to the box's to not matter for picking
+        add_box_to_selection_world(collision_world, world, entity, self.half_extents);
+    }
+}
+
+//
+// Draw a closed polygon through the given points, which are relative to the component's current
+// location. Will be affected by scale and rotation, if those components exist
+//
+#[derive(TypeUuid, Serialize, Deserialize, SerdeDiff, Debug, PartialEq, Clone, Inspect)]
+#[uuid = "f91a3890-b9ec-44b5-a9cb-9d58300b836a"]
+pub struct DrawSkiaPolygonComponentDef {
+    #[serde_diff(opaque)]
+    #[inspect(proxy_type = "crate::inspect::InspectPoints")]
+    pub points: Vec<Vec2>,
+    pub paint: PaintDef,
+}
+
+impl Default for DrawSkiaPolygonComponentDef {
+    fn default() -> Self {
+        // A polygon with no points can't be seen or selected, so start with a triangle
+        DrawSkiaPolygonComponentDef {
+            points: vec![
+                glam::Vec2::new(-0.5, -0.5).into(),
+                glam::Vec2::new(0.5, -0.5).into(),
+                glam::Vec2::new(0.0, 0.5).into(),
+            ],
+            paint: PaintDef::default(),
+        }
+    }
+}
+
+legion_prefab::register_component_type!(DrawSkiaPolygonComponentDef);
+
+#[derive(Clone)]
+pub struct DrawSkiaPolygonComponent {
+    pub points: Vec<Vec2>,
+    pub paint: Paint,
+    pub paint_def: PaintDef,
+}
+
+impl DrawSkiaPolygonComponent {
+    /// Creates the path to draw. The scale is applied to the points rather than the canvas so that
+    /// it doesn't change the stroke width
+    pub fn create_skia_path(
+        &self,
+        scale: glam::Vec2,
+    ) -> skia_safe::Path {
+        create_skia_polyline(&self.points, scale, true)
+    }
+}
+
+impl From<DrawSkiaPolygonComponentDef> for DrawSkiaPolygonComponent {
+    fn from(from: DrawSkiaPolygonComponentDef) -> Self {
+        DrawSkiaPolygonComponent {
+            points: from.points,
+            paint: from.paint.into(),
+            paint_def: from.paint,
+        }
+    }
+}
+
+impl DefFromRuntime<DrawSkiaPolygonComponent> for DrawSkiaPolygonComponentDef {
+    fn def_from_runtime(runtime: &DrawSkiaPolygonComponent) -> Option<Self> {
+        Some(DrawSkiaPolygonComponentDef {
+            points: runtime.points.clone(),
+            paint: runtime.paint_def,
+        })
+    }
+}
+
+impl crate::selection::EditorSelectable for DrawSkiaPolygonComponent {
+    fn create_editor_selection_world(
+        &self,
+        collision_world: &mut CollisionWorld<f32, Entity>,
+        resources: &Resources,
+        opened_prefab: &OpenedPrefabState,
+        world: &World,
+        entity: Entity,
+    ) {
+        let points: Vec<_> = self.points.iter().map(|point| **point).collect();
+        add_polygon_to_selection_world(collision_world, world, entity, &points);
+    }
+}
+
+//
+// Draw connected lines through the given points, which are relative to the component's current
+// location. Unlike a polygon, the last point isn't connected back to the first. Will be affected
+// by scale and rotation, if those components exist
+//
+#[derive(TypeUuid, Serialize, Deserialize, SerdeDiff, Debug, PartialEq, Clone, Inspect)]
+#[uuid = "6c49fafe-a323-4813-8832-e5b2aba9d650"]
+pub struct DrawSkiaLineStripComponentDef {
+    #[serde_diff(opaque)]
+    #[inspect(proxy_type = "crate::inspect::InspectPoints")]
+    pub points: Vec<Vec2>,
+    pub paint: PaintDef,
+}
+
+impl Default for DrawSkiaLineStripComponentDef {
+    fn default() -> Self {
+        // A line strip needs at least two points to be seen or selected
+        DrawSkiaLineStripComponentDef {
+            points: vec![
+                glam::Vec2::new(-0.5, 0.0).into(),
+                glam::Vec2::new(0.5, 0.0).into(),
+            ],
+            paint: PaintDef::default(),
+        }
+    }
+}
+
+legion_prefab::register_component_type!(DrawSkiaLineStripComponentDef);
+
+#[derive(Clone)]
+pub struct DrawSkiaLineStripComponent {
+    pub points: Vec<Vec2>,
+    pub paint: Paint,
+    pub paint_def: PaintDef,
+}
+
+impl DrawSkiaLineStripComponent {
+    /// Creates the path to draw. The scale is applied to the points rather than the canvas so that
+    /// it doesn't change the stroke width
+    pub fn create_skia_path(
+        &self,
+        scale: glam::Vec2,
+    ) -> skia_safe::Path {
+        create_skia_polyline(&self.points, scale, false)
+    }
+}
+
+impl From<DrawSkiaLineStripComponentDef> for DrawSkiaLineStripComponent {
+    fn from(from: DrawSkiaLineStripComponentDef) -> Self {
+        DrawSkiaLineStripComponent {
+            points: from.points,
+            paint: from.paint.into(),
+            paint_def: from.paint,
+        }
+    }
+}
+
+impl DefFromRuntime<DrawSkiaLineStripComponent> for DrawSkiaLineStripComponentDef {
+    fn def_from_runtime(runtime: &DrawSkiaLineStripComponent) -> Option<Self> {
+        Some(DrawSkiaLineStripComponentDef {
+            points: runtime.points.clone(),
+            paint: runtime.paint_def,
+        })
+    }
+}
+
+impl crate::selection::EditorSelectable for DrawSkiaLineStripComponent {
+    fn create_editor_selection_world(
+        &self,
+        collision_world: &mut CollisionWorld<f32, Entity>,
+        resources: &Resources,
+        opened_prefab: &OpenedPrefabState,
+        world: &World,
+        entity: Entity,
+    ) {
+        let stroke_width = self.paint.0.lock().unwrap().stroke_width();
+        add_line_strip_to_selection_world(
+            collision_world,
+            world,
+            entity,
+            &self.points,
+            stroke_width,
+        );
+    }
+}
+
+// Returns the scale and rotation that the entity's shapes are drawn with
+fn get_scale_and_rotation(
+    world: &World,
+    entity: Entity,
+) -> (glam::Vec2, f32) {
+    let mut scale = glam::Vec2::new(1.0, 1.0);
+
+    if let Some(uniform_scale) = world.get_component::<UniformScale2DComponent>(entity) {
+        scale *= uniform_scale.uniform_scale;
+    }
+
+    if let Some(non_uniform_scale) = world.get_component::<NonUniformScale2DComponent>(entity) {
+        scale *= *non_uniform_scale.non_uniform_scale;
+    }
+
+    let mut rotation = 0.0;
+    if let Some(rotation_component) = world.get_component::<Rotation2DComponent>(entity) {
+        rotation = rotation_component.rotation;
+    }
+
+    (scale, rotation)
+}
+
+// Adds the area inside the given points to the selection world, placed at the entity's position and
+// affected by its scale and rotation. Concave polygons are split into convex pieces, each of which
+// is added separately. Pieces with no area (like a single line) aren't added
+fn add_polygon_to_selection_world(
+    collision_world: &mut CollisionWorld<f32, Entity>,
+    world: &World,
+    entity: Entity,
+    points: &[glam::Vec2],
+) {
+    if let Some(position) = world.get_component::<Position2DComponent>(entity) {
+        let (scale, rotation) = get_scale_and_rotation(world, entity);
+
+        for piece in split_polygon_into_convex_pieces(points) {
+            let piece: Vec<_> = piece
+                .iter()
+                .map(|point| na::Point2::new(point.x() * scale.x(), point.y() * scale.y()))
+                .collect();
+
+            if let Some(polygon) = ConvexPolygon::try_from_points(&piece) {
+                collision_world.add(
+                    ncollide2d::math::Isometry::new(position.position.into(), rotation),
+                    ShapeHandle::new(polygon),
+                    CollisionGroups::new(),
+                    GeometricQueryType::Proximity(0.001),
+                    entity,
+                );
+            }
+        }
+    }
+}
+
+// Positive if a -> b -> c turns counter-clockwise, negative if it turns clockwise and zero if the
+// points are on a line
+fn turn_direction(
+    a: glam::Vec2,
+    b: glam::Vec2,
+    c: glam::Vec2,
+) -> f32 {
+    let ab = b - a;
+    let ac = c - a;
+    ab.x() * ac.y() - ab.y() * ac.x()
+}
+
+// The triangle's points must be in counter-clockwise order. Points on an edge count as inside
+fn is_point_in_triangle(
+    point: glam::Vec2,
+    a: glam::Vec2,
+    b: glam::Vec2,
+    c: glam::Vec2,
+) -> bool {
+    turn_direction(a, b, point) >= 0.0
+        && turn_direction(b, c, point) >= 0.0
+        && turn_direction(c, a, point) >= 0.0
+}
+
+// Splits a polygon into triangles by ear clipping. The points can be in either winding order. A
+// polygon that crosses itself can run out of ears, in which case the points that are left are
+// returned as one last piece, and the caller only gets their convex hull
+fn split_polygon_into_convex_pieces(points: &[glam::Vec2]) -> Vec<Vec<glam::Vec2>> {
+    let mut remaining = points.to_vec();
+
+    // Ear clipping needs the points to be counter-clockwise, which is when the area is positive
+    let twice_area: f32 = (0..remaining.len())
+        .map(|i| {
+            let a = remaining[i];
+            let b = remaining[(i + 1) % remaining.len()];
+            a.x() * b.y() - b.x() * a.y()
+        })
+        .sum();
+
+    if twice_area < 0.0 {
+        remaining.reverse();
+    }
+
+    let mut pieces = vec![];
+    while remaining.len() > 3 {
+        let count = remaining.len();
+        let corner = |i: usize| {
+            (
+                (i + count - 1) % count,
+                remaining[(i + count - 1) % count],
+                remaining[i],
+                remaining[(i + 1) % count],
+            )
+        };
+
+        // A corner is an ear if it's convex and no other point is inside the triangle it forms.
+        // Corners where the points are on a line are clipped too, they just don't have any area
+        let ear = (0..count).find(|&i| {
+            let (previous_index, previous, current, next) = corner(i);
+            let turn = turn_direction(previous, current, next);
+            if turn < 0.0 {
+                return false;
+            }
+
+            turn == 0.0
+                || !remaining.iter().enumerate().any(|(j, point)| {
+                    j != previous_index
+                        && j != i
+                        && j != (i + 1) % count
+                        && is_point_in_triangle(*point, previous, current, next)
+                })
+        });
+
+        match ear {
+            Some(i) => {
+                let (_, previous, current, next) = corner(i);
+                pieces.push(vec![previous, current, next]);
+                remaining.remove(i);
+            }
+            None => break,
+        }
+    }
+
+    pieces.push(remaining);
+    pieces
+}
+
+// Thin lines would be nearly impossible to click on, so lines are at least this thick for selection
+const MIN_LINE_SELECTION_WIDTH: f32 = 0.1;
+
+// Adds a thin box along each line between the given points to the selection world, placed at the
+// entity's position and affected by its scale and rotation
+fn add_line_strip_to_selection_world(
+    collision_world: &mut CollisionWorld<f32, Entity>,
+    world: &World,
+    entity: Entity,
+    points: &[Vec2],
+    stroke_width: f32,
+) {
+    if let Some(position) = world.get_component::<Position2DComponent>(entity) {
+        let (scale, rotation) = get_scale_and_rotation(world, entity);
+        let half_width = stroke_width.max(MIN_LINE_SELECTION_WIDTH) / 2.0;
+
+        for segment in points.windows(2) {
+            let from = *segment[0] * scale;
+            let to = *segment[1] * scale;
+
+            let direction = to - from;
+            let length = direction.length();
+            if length <= std::f32::EPSILON {
+                continue;
+            }
+
+            // Rotate the middle of the segment around the entity's position
+            let center = (from + to) / 2.0;
+            let (sin, cos) = rotation.sin_cos();
+            let center = glam::Vec2::new(
+                center.x() * cos - center.y() * sin,
+                center.x() * sin + center.y() * cos,
+            ) + *position.position;
+
+            let segment_rotation = direction.y().atan2(direction.x()) + rotation;
+            let half_extents = glam::Vec2::new(length / 2.0 + half_width, half_width);
+            let shape_handle =
+                ShapeHandle::new(Cuboid::new(crate::math::vec2_glam_to_glm(half_extents)));
+
+            collision_world.add(
+                ncollide2d::math::Isometry::new(
+                    crate::math::vec2_glam_to_glm(center),
+                    segment_rotation,
+                ),
+                shape_handle,
+                CollisionGroups::new(),
+                GeometricQueryType::Proximity(0.001),
+                entity,
+            );
+        }
+    }
+}
+
+// Builds a skia path through the given points, scaled by the given amount
+fn create_skia_polyline(
+    points: &[Vec2],
+    scale: glam::Vec2,
+    closed: bool,
+) -> skia_safe::Path {
+    let mut path = skia_safe::Path::new();
+    for (i, point) in points.iter().enumerate() {
+        let point = **point * scale;
+        if i == 0 {
+            path.move_to((point.x(), point.y()));
+        } else {
+            path.line_to((point.x(), point.y()));
+        }
+    }
+
+    if closed {
+        path.close();
+    }
+
+    path
+}
+
+//
+// Draw an image at the component's current location, stretched to fill half_extents. Will be
+// affected by scale and rotation, if those components exist
+//
+#[derive(TypeUuid, Serialize, Deserialize, SerdeDiff, Debug, PartialEq, Clone, Inspect)]
+#[uuid = "d5ab89a0-af5f-484a-8b7d-993ac7f3c8ae"]
+pub struct DrawSkiaSpriteComponentDef {
+    #[serde_diff(opaque)]
+    pub image: HandleDef<ImageAsset>,
+    #[serde_diff(opaque)]
+    pub half_extents: Vec2,
+    // Multiplied with the image's color. White draws the image unchanged
+    #[serde_diff(opaque)]
+    #[inspect(proxy_type = "crate::inspect::InspectColor")]
+    pub tint: Vec4,
+}
+
+impl Default for DrawSkiaSpriteComponentDef {
+    fn default() -> Self {
+        DrawSkiaSpriteComponentDef {
+            image: HandleDef::default(),
+            half_extents: glam::Vec2::new(0.5, 0.5).into(),
+            tint: glam::Vec4::new(1.0, 1.0, 1.0, 1.0).into(),
+        }
+    }
+}
+
+legion_prefab::register_component_type!(DrawSkiaSpriteComponentDef);
+
+#[derive(Clone)]
+pub struct SkiaImage(pub skia_safe::Image);
+unsafe impl Send for SkiaImage {}
+unsafe impl Sync for SkiaImage {}
+
+#[derive(Clone)]
+pub struct DrawSkiaSpriteComponent {
+    pub image: Handle<ImageAsset>,
+    pub half_extents: Vec2,
+    pub paint: Paint,
+
+    // What the image handle and paint were created from, so the def can be recreated
+    pub image_def: HandleDef<ImageAsset>,
+    pub tint: Vec4,
+
+    // The skia image created from the image asset, along with the version of the asset it was
+    // created from. This is None until the asset has loaded. When the asset is reloaded, the skia
+    // image is recreated (see update_sprite_images)
+    pub skia_image: Option<(SkiaImage, u32)>,
+}
+
+impl DrawSkiaSpriteComponent {
+    /// Creates the skia image if the image asset has loaded, or recreates it if the asset has been
+    /// reloaded since the skia image was created
+    pub fn update_skia_image(
+        &mut self,
+        asset_resource: &AssetResource,
+    ) {
+        let image_with_version = asset_resource
+            .storage()
+            .get_asset_with_version(&self.image);
+
+        if let Some((image_asset, version)) = image_with_version {
+            let is_current = self
+                .skia_image
+                .as_ref()
+                .map(|(_, skia_image_version)| *skia_image_version == version)
+                .unwrap_or(false);
+
+            if !is_current {
+                self.skia_image = create_skia_image(&image_asset).map(|image| (image, version));
+            }
+        }
+    }
+}
+
+fn create_skia_image(image_asset: &ImageAsset) -> Option<SkiaImage> {
+    let width = image_asset.width();
+    let height = image_asset.height();
+
+    let alpha_type = if image_asset.premultiplied_alpha {
+        skia_safe::AlphaType::Premul
+    } else {
+        skia_safe::AlphaType::Unpremul
+    };
+
+    // Skia treats images without a color space as sRGB
+    let color_space = match image_asset.color_space {
+        ImageColorSpace::Srgb => None,
+        ImageColorSpace::Linear => Some(skia_safe::ColorSpace::new_srgb_linear()),
+    };
+
+    let image_info = skia_safe::ImageInfo::new(
+        (width as i32, height as i32),
+        skia_safe::ColorType::RGBA8888,
+        alpha_type,
+        color_space,
+    );
+
+    let pixels = skia_safe::Data::new_copy(&image_asset.to_rgba8());
+    let image = skia_safe::Image::from_raster_data(&image_info, pixels, width as usize * 4);
+    if image.is_none() {
+        log::warn!("Failed to create a skia image from a {}x{} image", width, height);
+    }
+
+    image.map(SkiaImage)
+}
+
+// Creates a paint that multiplies everything drawn with it by the tint color
+fn create_tint_paint(tint: Vec4) -> Paint {
+    let tint = skia_safe::Color4f::new(tint.x(), tint.y(), tint.z(), tint.w());
+
+    let mut paint = skia_safe::Paint::default();
+    paint.set_anti_alias(true);
+    paint.set_color_filter(skia_safe::color_filters::blend(
+        tint.to_color(),
+        skia_safe::BlendMode::Modulate,
+    ));
+
+    Paint(std::sync::Mutex::new(paint))
+}
+
+impl SpawnFrom<DrawSkiaSpriteComponentDef> for DrawSkiaSpriteComponent {
+    fn spawn_from(
+        _src_world: &World,
+        _src_component_storage: &ComponentStorage,
+        _src_component_storage_indexes: Range<ComponentIndex>,
+        resources: &Resources,
+        _src_entities: &[Entity],
+        _dst_entities: &[Entity],
+        from: &[DrawSkiaSpriteComponentDef],
+        into: &mut [std::mem::MaybeUninit<Self>],
+    ) {
+        let asset_resource = resources.get::<AssetResource>().unwrap();
+
+        for (from, into) in from.iter().zip(into) {
+            let mut sprite = DrawSkiaSpriteComponent {
+                image: from.image.load(&*asset_resource),
+                half_extents: from.half_extents,
+                paint: create_tint_paint(from.tint),
+                image_def: from.image,
+                tint: from.tint,
+                skia_image: None,
+            };
+
+            // The image is usually already loaded if another sprite uses it. If not, the skia
+            // image is created once it finishes loading
+            sprite.update_skia_image(&*asset_resource);
+
+            *into = std::mem::MaybeUninit::new(sprite);
+        }
+    }
+}
+
+impl DefFromRuntime<DrawSkiaSpriteComponent> for DrawSkiaSpriteComponentDef {
+    fn def_from_runtime(runtime: &DrawSkiaSpriteComponent) -> Option<Self> {
+        Some(DrawSkiaSpriteComponentDef {
+            image: runtime.image_def,
+            half_extents: runtime.half_extents,
+            tint: runtime.tint,
+        })
+    }
+}
+
+impl crate::selection::EditorSelectable for DrawSkiaSpriteComponent {
+    fn create_editor_selection_world(
+        &self,
+        collision_world: &mut CollisionWorld<f32, Entity>,
+        resources: &Resources,
+        opened_prefab: &OpenedPrefabState,
+        world: &World,
+        entity: Entity,
+    ) {
+        add_box_to_selection_world(collision_world, world, entity, self.half_extents);
+    }
+}
+
+//
+// Draw a vector shape (see VectorShapeAsset) centered on the component's current location. Will be
+// affected by scale and rotation, if those components exist
+//
+#[derive(TypeUuid, Serialize, Deserialize, SerdeDiff, Debug, PartialEq, Clone, Inspect, Default)]
+#[uuid = "1d6a8f3e-b4c2-4e59-9a70-c83f5e2d1b06"]
+pub struct DrawSkiaVectorShapeComponentDef {
+    #[serde_diff(opaque)]
+    pub shape: HandleDef<VectorShapeAsset>,
+}
+
+legion_prefab::register_component_type!(DrawSkiaVectorShapeComponentDef);
+
+/// The skia paths and paints created from a vector shape asset. Each path is drawn with each of
+/// its paints in order (the fill, then the stroke)
+#[derive(Clone)]
+pub struct SkiaVectorShape {
+    pub paths: Vec<(skia_safe::Path, Vec<Paint>)>,
+}
+
+// SAFETY: The paints are behind Paint's mutex. The paths are only modified while they are being
+// created. After that they're only read (drawn), and skia allows concurrent reads of a path
+unsafe impl Send for SkiaVectorShape {}
+unsafe impl Sync for SkiaVectorShape {}
+
+#[derive(Clone)]
+pub struct DrawSkiaVectorShapeComponent {
+    pub shape: Handle<VectorShapeAsset>,
+    pub shape_def: HandleDef<VectorShapeAsset>,
+
+    // The skia paths created from the asset, along with the version of the asset they were created
+    // from. This is None until the asset has loaded. When the asset is reloaded, the paths are
+    // recreated (see update_vector_shapes)
+    pub skia_shape: Option<(SkiaVectorShape, u32)>,
+}
+
+impl DrawSkiaVectorShapeComponent {
+    /// Creates the skia paths if the shape asset has loaded, or recreates them if the asset has
+    /// been reloaded since they were created
+    pub fn update_skia_shape(
+        &mut self,
+        asset_resource: &AssetResource,
+    ) {
+        let shape_with_version = asset_resource
+            .storage()
+            .get_asset_with_version(&self.shape);
+
+        if let Some((shape_asset, version)) = shape_with_version {
+            let is_current = self
+                .skia_shape
+                .as_ref()
+                .map(|(_, skia_shape_version)| *skia_shape_version == version)
+                .unwrap_or(false);
+
+            if !is_current {
+                self.skia_shape = Some((create_skia_vector_shape(&shape_asset), version));
+            }
+        }
+    }
+}
+
+fn create_skia_vector_shape(shape_asset: &VectorShapeAsset) -> SkiaVectorShape {
+    let paths = shape_asset
+        .paths
+        .iter()
+        .map(|path| {
+            let mut paints = vec![];
+            if let Some(fill) = &path.fill {
+                paints.push(Paint(std::sync::Mutex::new(create_skia_paint(fill, path.opacity))));
+            }
+
+            if let Some(stroke) = &path.stroke {
+                paints.push(Paint(std::sync::Mutex::new(create_skia_stroke_paint(
+                    stroke,
+                    path.opacity,
+                ))));
+            }
+
+            (create_skia_path(path), paints)
+        })
+        .collect();
+
+    SkiaVectorShape { paths }
+}
+
+fn create_skia_path(vector_path: &VectorPath) -> skia_safe::Path {
+    let mut path = skia_safe::Path::new();
+    path.set_fill_type(match vector_path.fill_rule {
+        VectorFillRule::NonZero => skia_safe::path::FillType::Winding,
+        VectorFillRule::EvenOdd => skia_safe::path::FillType::EvenOdd,
+    });
+
+    for command in &vector_path.commands {
+        match *command {
+            VectorPathCommand::MoveTo(p) => {
+                path.move_to((p[0], p[1]));
+            }
+            VectorPathCommand::LineTo(p) => {
+                path.line_to((p[0], p[1]));
+            }
+            VectorPathCommand::QuadTo(p1, p2) => {
+                path.quad_to((p1[0], p1[1]), (p2[0], p2[1]));
+            }
+            VectorPathCommand::CubicTo(p1, p2, p3) => {
+                path.cubic_to((p1[0], p1[1]), (p2[0], p2[1]), (p3[0], p3[1]));
+            }
+            VectorPathCommand::Close => {
+                path.close();
+            }
+        }
+    }
+
+    path
+}
+
+fn to_skia_matrix(transform: &VectorTransform) -> skia_safe::Matrix {
+    let [a, b, c, d, e, f] = transform.0;
+    skia_safe::Matrix::new_all(a, c, e, b, d, f, 0.0, 0.0, 1.0)
+}
+
+fn to_skia_tile_mode(spread_method: VectorSpreadMethod) -> skia_safe::TileMode {
+    match spread_method {
+        VectorSpreadMethod::Pad => skia_safe::TileMode::Clamp,
+        VectorSpreadMethod::Reflect => skia_safe::TileMode::Mirror,
+        VectorSpreadMethod::Repeat => skia_safe::TileMode::Repeat,
+    }
+}
+
+fn to_skia_cap(line_cap: VectorLineCap) -> skia_safe::paint::Cap {
+    match line_cap {
+        VectorLineCap::Butt => skia_safe::paint::Cap::Butt,
+        VectorLineCap::Round => skia_safe::paint::Cap::Round,
+        VectorLineCap::Square => skia_safe::paint::Cap::Square,
+    }
+}
+
+fn to_skia_join(line_join: VectorLineJoin) -> skia_safe::paint::Join {
+    match line_join {
+        VectorLineJoin::Miter => skia_safe::paint::Join::Miter,
+        VectorLineJoin::Round => skia_safe::paint::Join::Round,
+        VectorLineJoin::Bevel => skia_safe::paint::Join::Bevel,
+    }
+}
+
+fn create_gradient_shader(vector_paint: &VectorPaint) -> Option<skia_safe::Shader> {
+    let to_colors_and_positions = |stops: &[crate::pipeline::VectorGradientStop]| {
+        let colors: Vec<_> = stops
+            .iter()
+            .map(|stop| {
+                skia_safe::Color4f::new(stop.color[0], stop.color[1], stop.color[2], stop.color[3])
+                    .to_color()
+            })
+            .collect();
+        let positions: Vec<_> = stops.iter().map(|stop| stop.offset).collect();
+        (colors, positions)
+    };
+
+    match vector_paint {
+        VectorPaint::Color(_) => None,
+        VectorPaint::LinearGradient {
+            start,
+            end,
+            stops,
+            spread_method,
+            transform,
+        } => {
+            let (colors, positions) = to_colors_and_positions(stops);
+            skia_safe::gradient_shader::linear(
+                (
+                    skia_safe::Point::new(start[0], start[1]),
+                    skia_safe::Point::new(end[0], end[1]),
+                ),
+                colors.as_slice(),
+                Some(positions.as_slice()),
+                to_skia_tile_mode(*spread_method),
+                None,
+                Some(&to_skia_matrix(transform)),
+            )
+        }
+        VectorPaint::RadialGradient {
+            center,
+            radius,
+            focal_point,
+            stops,
+            spread_method,
+            transform,
+        } => {
+            let (colors, positions) = to_colors_and_positions(stops);
+            if focal_point == center {
+                skia_safe::gradient_shader::radial(
+                    skia_safe::Point::new(center[0], center[1]),
+                    *radius,
+                    colors.as_slice(),
+                    Some(positions.as_slice()),
+                    to_skia_tile_mode(*spread_method),
+                    None,
+                    Some(&to_skia_matrix(transform)),
+                )
+            } else {
+                // SVG radial gradients with a moved focal point start from a circle of radius 0
+                // at the focal point
+                skia_safe::gradient_shader::two_point_conical(
+                    skia_safe::Point::new(focal_point[0], focal_point[1]),
+                    0.0,
+                    skia_safe::Point::new(center[0], center[1]),
+                    *radius,
+                    colors.as_slice(),
+                    Some(positions.as_slice()),
+                    to_skia_tile_mode(*spread_method),
+                    None,
+                    Some(&to_skia_matrix(transform)),
+                )
+            }
+        }
+    }
+}
+
+// Creates a fill paint. Opacity is multiplied with the paint's alpha
+fn create_skia_paint(
+    vector_paint: &VectorPaint,
+    opacity: f32,
+) -> skia_safe::Paint {
+    let mut paint = match vector_paint {
+        VectorPaint::Color(color) => skia_safe::Paint::new(
+            skia_safe::Color4f::new(color[0], color[1], color[2], color[3] * opacity),
+            None,
+        ),
+        _ => {
+            let mut paint = skia_safe::Paint::default();
+            paint.set_shader(create_gradient_shader(vector_paint));
+            paint.set_alpha_f(opacity);
+            paint
+        }
+    };
+
+    paint.set_anti_alias(true);
+    paint
+}
+
+fn create_skia_stroke_paint(
+    stroke: &VectorStroke,
+    opacity: f32,
+) -> skia_safe::Paint {
+    let mut paint = create_skia_paint(&stroke.paint, opacity);
+    paint.set_style(skia_safe::paint::Style::Stroke);
+    paint.set_stroke_width(stroke.width);
+    paint.set_stroke_miter(stroke.miter_limit);
+    paint.set_stroke_cap(to_skia_cap(stroke.line_cap));
+    paint.set_stroke_join(to_skia_join(stroke.line_join));
+
+    if !stroke.dash_pattern.is_empty() {
+        // Skia needs an even number of lengths. SVG repeats an odd list to make it even
+        let mut dash_pattern = stroke.dash_pattern.clone();
+        if dash_pattern.len() % 2 == 1 {
+            dash_pattern.extend_from_slice(&stroke.dash_pattern);
+        }
+
+        paint.set_path_effect(skia_safe::PathEffect::dash(&dash_pattern, stroke.dash_offset));
+    }
+
+    paint
+}
+
+impl SpawnFrom<DrawSkiaVectorShapeComponentDef> for DrawSkiaVectorShapeComponent {
+    fn spawn_from(
+        _src_world: &World,
+        _src_component_storage: &ComponentStorage,
+        _src_component_storage_indexes: Range<ComponentIndex>,
+        resources: &Resources,
+        _src_entities: &[Entity],
+        _dst_entities: &[Entity],
+        from: &[DrawSkiaVectorShapeComponentDef],
+        into: &mut [std::mem::MaybeUninit<Self>],
+    ) {
+        let asset_resource = resources.get::<AssetResource>().unwrap();
+
+        for (from, into) in from.iter().zip(into) {
+            let mut vector_shape = DrawSkiaVectorShapeComponent {
+                shape: from.shape.load(&*asset_resource),
+                shape_def: from.shape,
+                skia_shape: None,
+            };
+
+            // The shape is created once the asset finishes loading if it hasn't already
+            vector_shape.update_skia_shape(&*asset_resource);
+
+            *into = std::mem::MaybeUninit::new(vector_shape);
+        }
+    }
+}
+
+impl DefFromRuntime<DrawSkiaVectorShapeComponent> for DrawSkiaVectorShapeComponentDef {
+    fn def_from_runtime(runtime: &DrawSkiaVectorShapeComponent) -> Option<Self> {
+        Some(DrawSkiaVectorShapeComponentDef {
+            shape: runtime.shape_def,
+        })
+    }
+}
+
+impl crate::selection::EditorSelectable for DrawSkiaVectorShapeComponent {
+    fn create_editor_selection_world(
+        &self,
+        collision_world: &mut CollisionWorld<f32, Entity>,
+        resources: &Resources,
+        opened_prefab: &OpenedPrefabState,
+        world: &World,
+        entity: Entity,
+    ) {
+        // The shape can't be selected until its asset has loaded
+        let asset_resource = resources.get::<AssetResource>().unwrap();
+        let shape_asset = match asset_resource.storage().get(&self.shape) {
+            Some(shape_asset) => shape_asset,
+            None => return,
+        };
+
+        let points: Vec<_> = shape_asset
+            .collision_polygon
+            .iter()
+            .map(|point| glam::Vec2::new(point[0], point[1]))
+            .collect();
+
+        add_polygon_to_selection_world(collision_world, world, entity, &points);
+    }
+}
@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+use serde_diff::SerdeDiff;
+use type_uuid::TypeUuid;
+use imgui_inspect_derive::Inspect;
+
+/// Groups of drawn entities. Every entity on a layer is drawn on top of every entity on the layers
+/// before it
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DrawLayer {
+    Background,
+    Gameplay,
+    Foreground,
+}
+
+// Entities without a DrawOrderComponent are drawn on this layer
+impl Default for DrawLayer {
+    fn default() -> Self {
+        DrawLayer::Gameplay
+    }
+}
+
+impl crate::inspect::InspectEnum for DrawLayer {
+    fn variants() -> &'static [(Self, &'static str)] {
+        &[
+            (DrawLayer::Background, "Background"),
+            (DrawLayer::Gameplay, "Gameplay"),
+            (DrawLayer::Foreground, "Foreground"),
+        ]
+    }
+}
+
+//
+// Controls when an entity is drawn relative to others. Entities are sorted by layer and then by
+// order, so higher orders are drawn on top within a layer. Entities with the same layer and order
+// are drawn in an unspecified order
+//
+#[derive(
+    TypeUuid, Clone, Copy, Serialize, Deserialize, SerdeDiff, Debug, PartialEq, Inspect, Default,
+)]
+#[uuid = "b1a37e61-2c85-42c9-aed5-3d07a95047e5"]
+pub struct DrawOrderComponent {
+    #[serde_diff(opaque)]
+    #[inspect(proxy_type = "crate::inspect::InspectMixedEnum")]
+    pub layer: DrawLayer,
+    #[inspect(proxy_type = "crate::inspect::InspectMixedI32")]
+    pub order: i32,
+}
+
+legion_prefab::register_component_type!(DrawOrderComponent);
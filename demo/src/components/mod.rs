@@ -15,10 +15,30 @@ pub use transform::UniformScale2DComponent;
 pub use transform::NonUniformScale2DComponent;
 pub use transform::Rotation2DComponent;
 
+mod draw_order;
+pub use draw_order::DrawOrderComponent;
+pub use draw_order::DrawLayer;
+
 mod draw;
 pub use draw::DrawSkiaCircleComponent;
 pub use draw::DrawSkiaCircleComponentDef;
 pub use draw::DrawSkiaBoxComponent;
 pub use draw::DrawSkiaBoxComponentDef;
+pub use draw::DrawSkiaRoundedRectComponent;
+pub use draw::DrawSkiaRoundedRectComponentDef;
+pub use draw::DrawSkiaPolygonComponent;
+pub use draw::DrawSkiaPolygonComponentDef;
+pub use draw::DrawSkiaLineStripComponent;
+pub use draw::DrawSkiaLineStripComponentDef;
+pub use draw::DrawSkiaSpriteComponent;
+pub use draw::DrawSkiaSpriteComponentDef;
+pub use draw::SkiaImage;
+pub use draw::DrawSkiaVectorShapeComponent;
+pub use draw::DrawSkiaVectorShapeComponentDef;
+pub use draw::SkiaVectorShape;
 pub use draw::PaintDef;
+pub use draw::PaintStyle;
+pub use draw::PaintBlendMode;
+pub use draw::PaintGradientKind;
+pub use draw::PaintGradientDef;
 pub use draw::Paint;
@@ -3,6 +3,7 @@ use serde_diff::SerdeDiff;
 use type_uuid::TypeUuid;
 use nphysics2d::object::DefaultBodyHandle;
 use legion_transaction::SpawnFrom;
+use crate::keep_changes::DefFromRuntime;
 use crate::math::Vec2;
 use crate::resources::{PhysicsResource, OpenedPrefabState};
 use legion::prelude::*;
@@ -27,7 +28,9 @@ use ncollide2d::world::CollisionWorld;
 #[derive(TypeUuid, Serialize, Deserialize, SerdeDiff, Debug, PartialEq, Clone, Inspect, Default)]
 #[uuid = "fa518c0a-a65a-44c8-9d35-3f4f336b4de4"]
 pub struct RigidBodyBallComponentDef {
+    #[inspect(proxy_type = "crate::inspect::InspectMixedF32")]
     pub radius: f32,
+    #[inspect(proxy_type = "crate::inspect::InspectMixedBool")]
     pub is_static: bool,
 }
 
@@ -38,16 +41,84 @@ legion_prefab::register_component_type!(RigidBodyBallComponentDef);
 pub struct RigidBodyBoxComponentDef {
     #[serde_diff(opaque)]
     pub half_extents: Vec2,
+    #[inspect(proxy_type = "crate::inspect::InspectMixedBool")]
     pub is_static: bool,
 }
 
 legion_prefab::register_component_type!(RigidBodyBoxComponentDef);
 
+/// The def a rigid body was spawned from. This is kept on the component so that the body can be
+/// spawned again into another physics world, and turned back into its def
+#[derive(Clone, Debug)]
+pub enum RigidBodyDef {
+    Ball(RigidBodyBallComponentDef),
+    Box(RigidBodyBoxComponentDef),
+}
+
+impl RigidBodyDef {
+    // Creates the collider's shape, scaled by the entity's scale
+    fn create_shape_handle(
+        &self,
+        uniform_scale: Option<&UniformScale2DComponent>,
+        non_uniform_scale: Option<&NonUniformScale2DComponent>,
+    ) -> ShapeHandle<f32> {
+        match self {
+            RigidBodyDef::Ball(def) => {
+                let mut radius = def.radius;
+                if let Some(uniform_scale) = uniform_scale {
+                    radius *= uniform_scale.uniform_scale;
+                }
+
+                //TODO: Warn if radius is 0
+                ShapeHandle::new(Ball::new(radius.max(0.01)))
+            }
+            RigidBodyDef::Box(def) => {
+                let mut half_extents = *def.half_extents;
+                if let Some(uniform_scale) = uniform_scale {
+                    half_extents *= glam::Vec2::splat(uniform_scale.uniform_scale);
+                }
+
+                if let Some(non_uniform_scale) = non_uniform_scale {
+                    half_extents *= *non_uniform_scale.non_uniform_scale;
+                }
+
+                ShapeHandle::new(Cuboid::new(crate::math::vec2_glam_to_glm(half_extents)))
+            }
+        }
+    }
+
+    fn is_static(&self) -> bool {
+        match self {
+            RigidBodyDef::Ball(def) => def.is_static,
+            RigidBodyDef::Box(def) => def.is_static,
+        }
+    }
+}
+
 pub struct RigidBodyComponent {
     pub handle: DefaultBodyHandle,
+    pub def: RigidBodyDef,
     delete_body_tx: crossbeam_channel::Sender<DefaultBodyHandle>,
 }
 
+impl DefFromRuntime<RigidBodyComponent> for RigidBodyBallComponentDef {
+    fn def_from_runtime(runtime: &RigidBodyComponent) -> Option<Self> {
+        match &runtime.def {
+            RigidBodyDef::Ball(def) => Some(def.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl DefFromRuntime<RigidBodyComponent> for RigidBodyBoxComponentDef {
+    fn def_from_runtime(runtime: &RigidBodyComponent) -> Option<Self> {
+        match &runtime.def {
+            RigidBodyDef::Box(def) => Some(def.clone()),
+            _ => None,
+        }
+    }
+}
+
 impl Drop for RigidBodyComponent {
     fn drop(&mut self) {
         self.delete_body_tx.send(self.handle);
@@ -58,9 +129,9 @@ fn transform_shape_to_rigid_body(
     physics: &mut PhysicsResource,
     into: &mut std::mem::MaybeUninit<RigidBodyComponent>,
     src_position: Option<&Position2DComponent>,
-    src_rotation: Option<&Rotation2DComponent>,
-    shape_handle: ShapeHandle<f32>,
-    is_static: bool,
+    src_uniform_scale: Option<&UniformScale2DComponent>,
+    src_non_uniform_scale: Option<&NonUniformScale2DComponent>,
+    def: RigidBodyDef,
 ) {
     let position = if let Some(position) = src_position {
         position.position
@@ -71,7 +142,7 @@ fn transform_shape_to_rigid_body(
     let mut collider_offset = Vec2::zero();
 
     // Build the rigid body.
-    let rigid_body_handle = if is_static {
+    let rigid_body_handle = if def.is_static() {
         *collider_offset += *position;
         physics.bodies.insert(nphysics2d::object::Ground::new())
     } else {
@@ -83,7 +154,8 @@ fn transform_shape_to_rigid_body(
     };
 
     // Build the collider.
-    let collider = nphysics2d::object::ColliderDesc::new(shape_handle.clone())
+    let shape_handle = def.create_shape_handle(src_uniform_scale, src_non_uniform_scale);
+    let collider = nphysics2d::object::ColliderDesc::new(shape_handle)
         .density(1.0)
         .translation(collider_offset.into())
         .build(nphysics2d::object::BodyPartHandle(rigid_body_handle, 0));
@@ -93,10 +165,55 @@ fn transform_shape_to_rigid_body(
 
     *into = std::mem::MaybeUninit::new(RigidBodyComponent {
         handle: rigid_body_handle,
+        def,
         delete_body_tx: physics.delete_body_tx().clone(),
     })
 }
 
+// Creates a body in the physics resource for each def, placed and scaled by the source entity's
+// transform components
+fn spawn_rigid_bodies(
+    src_component_storage: &ComponentStorage,
+    src_component_storage_indexes: Range<ComponentIndex>,
+    resources: &Resources,
+    defs: impl Iterator<Item = RigidBodyDef>,
+    into: &mut [std::mem::MaybeUninit<RigidBodyComponent>],
+) {
+    let mut physics = resources.get_mut::<PhysicsResource>().unwrap();
+
+    let position_components = iter_components_in_storage::<Position2DComponent>(
+        src_component_storage,
+        src_component_storage_indexes.clone(),
+    );
+
+    let uniform_scale_components = iter_components_in_storage::<UniformScale2DComponent>(
+        src_component_storage,
+        src_component_storage_indexes.clone(),
+    );
+
+    let non_uniform_scale_components = iter_components_in_storage::<NonUniformScale2DComponent>(
+        src_component_storage,
+        src_component_storage_indexes,
+    );
+
+    for (src_position, src_uniform_scale, src_non_uniform_scale, def, into) in izip!(
+        position_components,
+        uniform_scale_components,
+        non_uniform_scale_components,
+        defs,
+        into
+    ) {
+        transform_shape_to_rigid_body(
+            &mut physics,
+            into,
+            src_position,
+            src_uniform_scale,
+            src_non_uniform_scale,
+            def,
+        );
+    }
+}
+
 impl SpawnFrom<RigidBodyBallComponentDef> for RigidBodyComponent {
     fn spawn_from(
         _src_world: &World,
@@ -108,46 +225,13 @@ impl SpawnFrom<RigidBodyBallComponentDef> for RigidBodyComponent {
         from: &[RigidBodyBallComponentDef],
         into: &mut [std::mem::MaybeUninit<Self>],
     ) {
-        let mut physics = resources.get_mut::<PhysicsResource>().unwrap();
-
-        let position_components = iter_components_in_storage::<Position2DComponent>(
-            src_component_storage,
-            src_component_storage_indexes.clone(),
-        );
-
-        let uniform_scale_components = iter_components_in_storage::<UniformScale2DComponent>(
-            src_component_storage,
-            src_component_storage_indexes.clone(),
-        );
-
-        let rotation_components = iter_components_in_storage::<Rotation2DComponent>(
+        spawn_rigid_bodies(
             src_component_storage,
             src_component_storage_indexes,
+            resources,
+            from.iter().map(|from| RigidBodyDef::Ball(from.clone())),
+            into,
         );
-
-        for (src_position, src_uniform_scale, src_rotation, from, into) in izip!(
-            position_components,
-            uniform_scale_components,
-            rotation_components,
-            from,
-            into
-        ) {
-            let mut radius = from.radius;
-            if let Some(src_uniform_scale) = src_uniform_scale {
-                radius *= src_uniform_scale.uniform_scale;
-            }
-
-            //TODO: Warn if radius is 0
-            let shape_handle = ShapeHandle::new(Ball::new(radius.max(0.01)));
-            transform_shape_to_rigid_body(
-                &mut physics,
-                into,
-                src_position,
-                src_rotation,
-                shape_handle,
-                from.is_static,
-            );
-        }
     }
 }
 
@@ -198,57 +282,36 @@ impl SpawnFrom<RigidBodyBoxComponentDef> for RigidBodyComponent {
         from: &[RigidBodyBoxComponentDef],
         into: &mut [std::mem::MaybeUninit<Self>],
     ) {
-        let mut physics = resources.get_mut::<PhysicsResource>().unwrap();
-
-        let position_components = iter_components_in_storage::<Position2DComponent>(
-            src_component_storage,
-            src_component_storage_indexes.clone(),
-        );
-
-        let uniform_scale_components = iter_components_in_storage::<UniformScale2DComponent>(
+        spawn_rigid_bodies(
             src_component_storage,
-            src_component_storage_indexes.clone(),
-        );
-
-        let non_uniform_scale_components = iter_components_in_storage::<NonUniformScale2DComponent>(
-            src_component_storage,
-            src_component_storage_indexes.clone(),
+            src_component_storage_indexes,
+            resources,
+            from.iter().map(|from| RigidBodyDef::Box(from.clone())),
+            into,
         );
+    }
+}
 
-        let rotation_components = iter_components_in_storage::<Rotation2DComponent>(
+// Spawns a new body for each of the given bodies into the physics resource, i.e. when a world is
+// cloned into one with its own physics. The original bodies are left alone
+impl SpawnFrom<RigidBodyComponent> for RigidBodyComponent {
+    fn spawn_from(
+        _src_world: &World,
+        src_component_storage: &ComponentStorage,
+        src_component_storage_indexes: Range<ComponentIndex>,
+        resources: &Resources,
+        _src_entities: &[Entity],
+        _dst_entities: &[Entity],
+        from: &[RigidBodyComponent],
+        into: &mut [std::mem::MaybeUninit<Self>],
+    ) {
+        spawn_rigid_bodies(
             src_component_storage,
             src_component_storage_indexes,
+            resources,
+            from.iter().map(|from| from.def.clone()),
+            into,
         );
-
-        for (src_position, src_uniform_scale, src_non_uniform_scale, src_rotation, from, into) in izip!(
-            position_components,
-            uniform_scale_components,
-            non_uniform_scale_components,
-            rotation_components,
-            from,
-            into
-        ) {
-            let mut half_extents = *from.half_extents;
-
-            if let Some(src_uniform_scale) = src_uniform_scale {
-                half_extents *= glam::Vec2::splat(src_uniform_scale.uniform_scale);
-            }
-
-            if let Some(src_non_uniform_scale) = src_non_uniform_scale {
-                half_extents *= *src_non_uniform_scale.non_uniform_scale;
-            }
-
-            let shape_handle =
-                ShapeHandle::new(Cuboid::new(crate::math::vec2_glam_to_glm(half_extents)));
-            transform_shape_to_rigid_body(
-                &mut physics,
-                into,
-                src_position,
-                src_rotation,
-                shape_handle,
-                from.is_static,
-            );
-        }
     }
 }
 
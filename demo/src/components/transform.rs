@@ -23,6 +23,7 @@ legion_prefab::register_component_type!(Position2DComponent);
 #[derive(TypeUuid, Clone, Serialize, Deserialize, SerdeDiff, Debug, Inspect)]
 #[uuid = "ea1118ac-ebbe-433b-8532-e8938cd3a2dc"]
 pub struct UniformScale2DComponent {
+    #[inspect(proxy_type = "crate::inspect::InspectMixedF32")]
     pub uniform_scale: f32,
 }
 
@@ -60,6 +61,7 @@ legion_prefab::register_component_type!(NonUniformScale2DComponent);
 #[derive(TypeUuid, Clone, Serialize, Deserialize, SerdeDiff, Debug, Inspect, Default)]
 #[uuid = "6841f13d-fe38-4320-a8f8-1a6133f45e33"]
 pub struct Rotation2DComponent {
+    #[inspect(proxy_type = "crate::inspect::InspectMixedF32")]
     pub rotation: f32,
 }
 
@@ -1,12 +1,13 @@
 use std::{
     net::{AddrParseError, SocketAddr},
     path::PathBuf,
+    thread::JoinHandle,
 };
 
 use atelier_daemon::AssetDaemon;
 use structopt::StructOpt;
 
-/// Parameters to the asset daemon.
+/// Command line parameters to the standalone asset daemon (see src/bin/asset_daemon.rs).
 ///
 /// # Examples
 ///
@@ -23,7 +24,7 @@ pub struct AssetDaemonOpt {
     short,
     long,
     parse(try_from_str = parse_socket_addr),
-    default_value = "127.0.0.1:9999"
+    default_value = DEFAULT_DAEMON_ADDRESS
     )]
     pub address: SocketAddr,
     /// Directories to watch for assets.
@@ -32,10 +33,46 @@ pub struct AssetDaemonOpt {
 }
 
 /// Parses a string as a socket address.
-fn parse_socket_addr(s: &str) -> std::result::Result<SocketAddr, AddrParseError> {
+pub fn parse_socket_addr(s: &str) -> std::result::Result<SocketAddr, AddrParseError> {
     s.parse()
 }
 
+/// The address the daemon listens on and the app connects to if neither is configured
+pub const DEFAULT_DAEMON_ADDRESS: &str = "127.0.0.1:9999";
+
+/// Configuration for running the asset daemon. The standalone binary builds this from its command
+/// line. The app builds one when it runs the daemon embedded on a background thread, so the
+/// daemon never reads the app's command line.
+#[derive(Clone, Debug)]
+pub struct AssetDaemonConfig {
+    /// Path to the asset metadata database directory.
+    pub db_dir: PathBuf,
+    /// Socket address for the daemon to listen for connections.
+    pub address: SocketAddr,
+    /// Directories to watch for assets.
+    pub asset_dirs: Vec<PathBuf>,
+}
+
+impl Default for AssetDaemonConfig {
+    fn default() -> Self {
+        AssetDaemonConfig {
+            db_dir: PathBuf::from(".assets_db"),
+            address: parse_socket_addr(DEFAULT_DAEMON_ADDRESS).unwrap(),
+            asset_dirs: vec![PathBuf::from("assets")],
+        }
+    }
+}
+
+impl From<AssetDaemonOpt> for AssetDaemonConfig {
+    fn from(opt: AssetDaemonOpt) -> Self {
+        AssetDaemonConfig {
+            db_dir: opt.db_dir,
+            address: opt.address,
+            asset_dirs: opt.asset_dirs,
+        }
+    }
+}
+
 // This is required because rustc does not recognize .ctor segments when considering which symbols
 // to include when linking static libraries to avoid having the module eliminated as "dead code".
 // We need to reference a symbol in each module (crate) that registers an importer since atelier_importer uses
@@ -53,7 +90,45 @@ fn init_modules() {
     // }
 }
 
-pub fn run() {
+/// An asset daemon running on a background thread
+pub struct AssetDaemonHandle {
+    thread: JoinHandle<()>,
+
+    // Tells the daemon to stop. This is held until the daemon has stopped
+    shutdown: Box<dyn FnOnce() + Send>,
+}
+
+impl AssetDaemonHandle {
+    /// Waits for the daemon to stop on its own
+    pub fn join(self) {
+        let AssetDaemonHandle { thread, shutdown } = self;
+        if thread.join().is_err() {
+            log::error!("The asset daemon thread panicked");
+        }
+
+        drop(shutdown);
+    }
+
+    /// Tells the daemon to stop and waits for it to finish
+    pub fn stop(self) {
+        let AssetDaemonHandle { thread, shutdown } = self;
+        log::info!("Stopping the asset daemon");
+        shutdown();
+        if thread.join().is_err() {
+            log::error!("The asset daemon thread panicked");
+        }
+    }
+}
+
+/// Runs the asset daemon and blocks until it stops. Used by the standalone asset_daemon binary
+pub fn run(config: AssetDaemonConfig) {
+    spawn(config).join();
+}
+
+/// Runs the asset daemon on a background thread. This is convenient for development since only
+/// one process needs to be launched. A standalone daemon can be run instead with the asset_daemon
+/// binary. Stop the daemon with AssetDaemonHandle::stop before exiting.
+pub fn spawn(config: AssetDaemonConfig) -> AssetDaemonHandle {
     init_modules();
 
     log::info!(
@@ -64,12 +139,24 @@ pub fn run() {
             .join(", ")
     );
 
-    let opt = AssetDaemonOpt::from_args();
+    log::info!(
+        "Asset daemon listening on {}, watching {:?}",
+        config.address,
+        config.asset_dirs
+    );
 
-    AssetDaemon::default()
+    // The daemon runs on its own thread and returns a sender that tells it to shut down
+    let (thread, shutdown_tx) = AssetDaemon::default()
         .with_importers(atelier_importer::get_source_importers())
-        .with_db_path(opt.db_dir)
-        .with_address(opt.address)
-        .with_asset_dirs(opt.asset_dirs)
+        .with_db_path(config.db_dir)
+        .with_address(config.address)
+        .with_asset_dirs(config.asset_dirs)
         .run();
+
+    AssetDaemonHandle {
+        thread,
+        shutdown: Box::new(move || {
+            let _ = shutdown_tx.send(true);
+        }),
+    }
 }
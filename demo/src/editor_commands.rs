@@ -0,0 +1,294 @@
+use legion::prelude::*;
+use std::sync::Arc;
+
+use crate::resources::{
+    EditorAction, EditorSelectionResource, EditorStateResource, UniverseResource, WindowOptions,
+};
+use crate::resources::find_prefab_source_files;
+
+type EditorCommandFn = dyn Fn(&mut World, &mut Resources) + Send + Sync;
+
+type EditorCommandProviderFn = dyn Fn(&World, &Resources) -> Vec<EditorCommand> + Send + Sync;
+
+/// A named operation that can be found and run from the command palette
+#[derive(Clone)]
+pub struct EditorCommand {
+    name: String,
+    action: Option<EditorAction>,
+    run: Arc<EditorCommandFn>,
+}
+
+impl EditorCommand {
+    pub fn new<F>(
+        name: impl Into<String>,
+        run: F,
+    ) -> Self
+    where
+        F: Fn(&mut World, &mut Resources) + Send + Sync + 'static,
+    {
+        EditorCommand {
+            name: name.into(),
+            action: None,
+            run: Arc::new(run),
+        }
+    }
+
+    /// A command that does the same thing as pressing the key bound to the action
+    pub fn from_action(action: EditorAction) -> Self {
+        EditorCommand {
+            name: action.name().to_string(),
+            action: Some(action),
+            run: Arc::new(move |_, resources| {
+                let mut editor_state = resources.get_mut::<EditorStateResource>().unwrap();
+                action.enqueue(&mut *editor_state);
+            }),
+        }
+    }
+
+    /// A command that shows or hides one of the windows in WindowOptions
+    pub fn toggle_window(
+        window_name: &str,
+        window_flag: fn(&mut WindowOptions) -> &mut bool,
+    ) -> Self {
+        Self::new(
+            format!("Toggle Window: {}", window_name),
+            move |_, resources| {
+                let mut editor_state = resources.get_mut::<EditorStateResource>().unwrap();
+                let flag = window_flag(editor_state.window_options_mut());
+                *flag = !*flag;
+            },
+        )
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The action this command runs, if any. This is used to show the key bound to the command
+    pub fn action(&self) -> Option<EditorAction> {
+        self.action
+    }
+
+    pub fn run(
+        &self,
+        world: &mut World,
+        resources: &mut Resources,
+    ) {
+        (self.run)(world, resources);
+    }
+}
+
+/// All commands that are available in the command palette. Most commands are registered once at
+/// startup. Commands that depend on editor state (like which prefabs exist) are produced by
+/// providers each time the palette is opened.
+///
+/// Downstream code can add its own commands by getting this resource and calling register or
+/// register_provider.
+#[derive(Default)]
+pub struct EditorCommandRegistry {
+    commands: Vec<EditorCommand>,
+    providers: Vec<Box<EditorCommandProviderFn>>,
+}
+
+impl EditorCommandRegistry {
+    pub fn register(
+        &mut self,
+        command: EditorCommand,
+    ) {
+        self.commands.push(command);
+    }
+
+    /// Registers a function that returns commands based on the current state of the world and
+    /// resources
+    pub fn register_provider<F>(
+        &mut self,
+        provider: F,
+    ) where
+        F: Fn(&World, &Resources) -> Vec<EditorCommand> + Send + Sync + 'static,
+    {
+        self.providers.push(Box::new(provider));
+    }
+
+    /// Returns every command that is currently available
+    pub fn commands(
+        &self,
+        world: &World,
+        resources: &Resources,
+    ) -> Vec<EditorCommand> {
+        let mut commands = self.commands.clone();
+        for provider in &self.providers {
+            commands.extend(provider(world, resources));
+        }
+
+        commands
+    }
+}
+
+/// Provides a command to open each prefab that can be found on disk
+pub fn open_prefab_commands(
+    _world: &World,
+    _resources: &Resources,
+) -> Vec<EditorCommand> {
+    find_prefab_source_files()
+        .into_iter()
+        .map(|prefab_source_file| {
+            let uuid = prefab_source_file.uuid;
+            EditorCommand::new(
+                format!("Open Prefab: {}", prefab_source_file.name()),
+                move |_, resources| {
+                    let mut editor_state = resources.get_mut::<EditorStateResource>().unwrap();
+                    editor_state.enqueue_open_prefab(uuid);
+                },
+            )
+        })
+        .collect()
+}
+
+/// Provides commands to switch to and close each open document
+pub fn document_commands(
+    _world: &World,
+    resources: &Resources,
+) -> Vec<EditorCommand> {
+    let editor_state = resources.get::<EditorStateResource>().unwrap();
+
+    let mut commands = vec![];
+    for document in editor_state.open_documents() {
+        let uuid = document.uuid();
+        commands.push(EditorCommand::new(
+            format!("Switch To Document: {}", document.name()),
+            move |_, resources| {
+                let mut editor_state = resources.get_mut::<EditorStateResource>().unwrap();
+                editor_state.enqueue_switch_document(uuid);
+            },
+        ));
+
+        commands.push(EditorCommand::new(
+            format!("Close Document: {}", document.name()),
+            move |_, resources| {
+                let mut editor_state = resources.get_mut::<EditorStateResource>().unwrap();
+
+                // Make the document active first so that if the user is asked to save it, they
+                // can see what is being saved
+                if editor_state.document_has_unsaved_changes(uuid) {
+                    editor_state.enqueue_switch_document(uuid);
+                }
+
+                editor_state.enqueue_close_document(uuid);
+            },
+        ));
+    }
+
+    commands
+}
+
+/// Provides a command to jump to each step in the undo history
+pub fn undo_history_commands(
+    _world: &World,
+    resources: &Resources,
+) -> Vec<EditorCommand> {
+    let editor_state = resources.get::<EditorStateResource>().unwrap();
+
+    let mut commands = vec![EditorCommand::new(
+        "Jump To Undo Step: <Opened Prefab>",
+        |_, resources| {
+            let mut editor_state = resources.get_mut::<EditorStateResource>().unwrap();
+            editor_state.enqueue_jump_to_undo_position(0);
+        },
+    )];
+
+    for (index, undo_step) in editor_state.undo_chain().iter().enumerate() {
+        let position = index + 1;
+        commands.push(EditorCommand::new(
+            format!(
+                "Jump To Undo Step: {}: {}",
+                position,
+                undo_step.description()
+            ),
+            move |_, resources| {
+                let mut editor_state = resources.get_mut::<EditorStateResource>().unwrap();
+                editor_state.enqueue_jump_to_undo_position(position);
+            },
+        ));
+    }
+
+    commands
+}
+
+/// Provides a command to add each component type to the selected entities. Types that only exist
+/// as the result of spawning can't be saved in a prefab, so they are skipped
+pub fn add_component_commands(
+    _world: &World,
+    resources: &Resources,
+) -> Vec<EditorCommand> {
+    let selection = resources.get::<EditorSelectionResource>().unwrap();
+    if selection.selected_entities().is_empty() {
+        return vec![];
+    }
+
+    let editor_state = resources.get::<EditorStateResource>().unwrap();
+    let spawn_mappings = editor_state.component_spawn_mappings();
+
+    editor_state
+        .component_registry()
+        .iter()
+        .filter(|(component_type_id, _)| !spawn_mappings.is_runtime_only(**component_type_id))
+        .map(|(_, component_type)| {
+            let component_type = component_type.clone();
+            EditorCommand::new(
+                format!("Add Component: {}", component_type.type_name()),
+                move |_, resources| {
+                    let selection = resources.get::<EditorSelectionResource>().unwrap();
+                    let universe = resources.get::<UniverseResource>().unwrap();
+                    let mut editor_state = resources.get_mut::<EditorStateResource>().unwrap();
+                    editor_state.add_component_to_selected(
+                        &*selection,
+                        &*universe,
+                        &component_type,
+                    );
+                },
+            )
+        })
+        .collect()
+}
+
+/// Scores how well the search text matches the candidate, or returns None if it doesn't match.
+/// Every character of the search must appear in the candidate in order (ignoring case and
+/// whitespace). Matches that are consecutive or start a word score higher, so "tp" ranks
+/// "Toggle Pause" above "Keep Changes From Play".
+pub fn fuzzy_match_score(
+    search_text: &str,
+    candidate: &str,
+) -> Option<i32> {
+    let candidate: Vec<char> = candidate.chars().collect();
+    let mut score = 0;
+    let mut candidate_index = 0;
+    let mut previous_match_index: Option<usize> = None;
+
+    for search_char in search_text.chars().filter(|c| !c.is_whitespace()) {
+        let search_char = search_char.to_ascii_lowercase();
+
+        let match_index = (candidate_index..candidate.len())
+            .find(|i| candidate[*i].to_ascii_lowercase() == search_char)?;
+
+        score += 1;
+
+        // Reward characters that directly follow the previous match
+        if previous_match_index.map(|i| i + 1) == Some(match_index) {
+            score += 5;
+        }
+
+        // Reward matching the first letter of a word
+        let is_word_start = match_index == 0 || !candidate[match_index - 1].is_alphanumeric();
+        if is_word_start {
+            score += 10;
+        }
+
+        // Penalize skipping over characters
+        score -= (match_index - candidate_index).min(10) as i32;
+
+        previous_match_index = Some(match_index);
+        candidate_index = match_index + 1;
+    }
+
+    Some(score)
+}
@@ -0,0 +1,236 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::marker::PhantomData;
+
+use atelier_core::AssetUuid;
+use atelier_loader::handle::Handle;
+use imgui::Ui;
+use imgui_inspect::InspectArgsDefault;
+use imgui_inspect::InspectRenderDefault;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use type_uuid::TypeUuid;
+
+use crate::inspect::is_mixed;
+use crate::resources::{find_asset_source_file, find_asset_source_files, AssetResource};
+
+thread_local! {
+    // If set, every HandleDef that is serialized on this thread adds its asset here. See
+    // collect_asset_references
+    static REFERENCED_ASSETS: RefCell<Option<HashSet<AssetUuid>>> = RefCell::new(None);
+}
+
+/// A reference to an asset, for use as a field in def components. In prefabs it is stored as the
+/// asset's AssetUuid. When the def is spawned, call load() to get a Handle<T> for the runtime
+/// component. Because Handle<T> serializes through the loader's SerdeContext, it can't be used
+/// directly in defs, which are also read and written when there is no loader (i.e. importing and
+/// editing).
+///
+/// The inspector shows this as a picker listing assets of type T, and the prefab importer reports
+/// every referenced asset as a load dependency so it is loaded before the prefab.
+pub struct HandleDef<T> {
+    uuid: AssetUuid,
+    phantom_data: PhantomData<T>,
+}
+
+impl<T> HandleDef<T> {
+    pub fn new(uuid: AssetUuid) -> Self {
+        HandleDef {
+            uuid,
+            phantom_data: Default::default(),
+        }
+    }
+
+    pub fn uuid(&self) -> AssetUuid {
+        self.uuid
+    }
+
+    /// True if no asset has been picked
+    pub fn is_none(&self) -> bool {
+        self.uuid == AssetUuid::default()
+    }
+
+    /// Adds a reference to the asset (starting a load if it isn't loaded yet) and returns a handle
+    /// to it. The asset stays loaded for as long as the handle exists
+    pub fn load(
+        &self,
+        asset_resource: &AssetResource,
+    ) -> Handle<T> {
+        let load_handle = asset_resource.loader().add_ref(self.uuid);
+        Handle::<T>::new(asset_resource.tx().clone(), load_handle)
+    }
+}
+
+// These are implemented manually so that T doesn't need to implement them
+impl<T> Clone for HandleDef<T> {
+    fn clone(&self) -> Self {
+        HandleDef::new(self.uuid)
+    }
+}
+
+impl<T> Copy for HandleDef<T> {}
+
+impl<T> PartialEq for HandleDef<T> {
+    fn eq(
+        &self,
+        other: &Self,
+    ) -> bool {
+        self.uuid == other.uuid
+    }
+}
+
+impl<T> Default for HandleDef<T> {
+    fn default() -> Self {
+        HandleDef::new(AssetUuid::default())
+    }
+}
+
+impl<T> std::fmt::Debug for HandleDef<T> {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        write!(f, "HandleDef({})", uuid::Uuid::from_bytes(self.uuid.0))
+    }
+}
+
+impl<T> Serialize for HandleDef<T> {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        if !self.is_none() {
+            REFERENCED_ASSETS.with(|referenced_assets| {
+                if let Some(referenced_assets) = &mut *referenced_assets.borrow_mut() {
+                    referenced_assets.insert(self.uuid);
+                }
+            });
+        }
+
+        self.uuid.serialize(serializer)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for HandleDef<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        AssetUuid::deserialize(deserializer).map(HandleDef::new)
+    }
+}
+
+// Stops collecting asset references when dropped. If f panics in collect_asset_references, this
+// makes sure that later serialization on the same thread doesn't keep collecting
+struct StopCollectingAssetReferences;
+
+impl Drop for StopCollectingAssetReferences {
+    fn drop(&mut self) {
+        REFERENCED_ASSETS.with(|referenced_assets| {
+            *referenced_assets.borrow_mut() = None;
+        });
+    }
+}
+
+/// Runs f and returns every asset referenced by a HandleDef that was serialized while it ran.
+/// Serializing a prefab inside f finds all the assets it references
+pub fn collect_asset_references<R, F: FnOnce() -> R>(f: F) -> (R, Vec<AssetUuid>) {
+    REFERENCED_ASSETS.with(|referenced_assets| {
+        *referenced_assets.borrow_mut() = Some(HashSet::new());
+    });
+
+    let _stop_collecting = StopCollectingAssetReferences;
+    let result = f();
+
+    let referenced_assets = REFERENCED_ASSETS
+        .with(|referenced_assets| referenced_assets.borrow_mut().take())
+        .unwrap_or_default();
+
+    (result, referenced_assets.into_iter().collect())
+}
+
+// The name shown in the inspector for the asset
+fn asset_display_name(uuid: &AssetUuid) -> String {
+    if *uuid == AssetUuid::default() {
+        return "None".to_string();
+    }
+
+    find_asset_source_file(uuid)
+        .map(|asset_source_file| asset_source_file.name())
+        .unwrap_or_else(|| uuid::Uuid::from_bytes(uuid.0).to_string())
+}
+
+/// Renders a button showing the picked asset. Clicking it opens a list of every asset of type T
+impl<T: TypeUuid> InspectRenderDefault<HandleDef<T>> for HandleDef<T> {
+    fn render(
+        data: &[&HandleDef<T>],
+        label: &'static str,
+        ui: &Ui,
+        _args: &InspectArgsDefault,
+    ) {
+        if data.is_empty() {
+            return;
+        }
+
+        if is_mixed(data) {
+            ui.text(&imgui::im_str!("{}: (mixed)", label));
+        } else {
+            ui.text(&imgui::im_str!(
+                "{}: {}",
+                label,
+                asset_display_name(&data[0].uuid)
+            ));
+        }
+    }
+
+    fn render_mut(
+        data: &mut [&mut HandleDef<T>],
+        label: &'static str,
+        ui: &Ui,
+        _args: &InspectArgsDefault,
+    ) -> bool {
+        if data.is_empty() {
+            return false;
+        }
+
+        let mixed = data.iter().skip(1).any(|x| **x != *data[0]);
+        let current_uuid = data[0].uuid;
+        let popup_id = imgui::im_str!("{}##asset_picker", label);
+
+        let button_text = if mixed {
+            "(mixed)".to_string()
+        } else {
+            asset_display_name(&current_uuid)
+        };
+
+        if ui.button(&imgui::im_str!("{}###{}", button_text, label), [0.0, 0.0]) {
+            ui.open_popup(&popup_id);
+        }
+        ui.same_line(0.0);
+        ui.text(label);
+
+        let mut picked_uuid = None;
+        ui.popup(&popup_id, || {
+            if imgui::Selectable::new(imgui::im_str!("None"))
+                .selected(!mixed && current_uuid == AssetUuid::default())
+                .build(ui)
+            {
+                picked_uuid = Some(AssetUuid::default());
+            }
+
+            for asset_source_file in find_asset_source_files(T::UUID) {
+                if imgui::Selectable::new(&imgui::im_str!("{}", asset_source_file.name()))
+                    .selected(!mixed && current_uuid == asset_source_file.uuid)
+                    .build(ui)
+                {
+                    picked_uuid = Some(asset_source_file.uuid);
+                }
+            }
+        });
+
+        if let Some(picked_uuid) = picked_uuid {
+            for d in data {
+                **d = HandleDef::new(picked_uuid);
+            }
+            return true;
+        }
+
+        false
+    }
+}
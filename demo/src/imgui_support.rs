@@ -6,6 +6,9 @@ use imgui::sys as imgui_sys;
 use std::sync::Arc;
 use std::sync::Mutex;
 
+/// imgui stores window positions and sizes in this file
+const IMGUI_INI_PATH: &str = "editor_imgui.ini";
+
 // Inner state for ImguiManager, which will be protected by a Mutex. Mutex protection required since
 // this object is Send but not Sync
 struct Inner {
@@ -284,7 +287,8 @@ fn init_imgui(window: &winit::window::Window) -> imgui::Context {
         }
     }
 
-    imgui.set_ini_filename(None);
+    // Window positions and sizes are kept between sessions
+    imgui.set_ini_filename(Some(imgui::ImString::new(IMGUI_INI_PATH)));
 
     // In the examples we only use integer DPI factors, because the UI can get very blurry
     // otherwise. This might or might not be what you want in a real application.
@@ -6,6 +6,10 @@ use imgui::sys as imgui_sys;
 use imgui::Ui;
 use imgui_inspect::InspectRenderStruct;
 use imgui_inspect::InspectArgsStruct;
+use imgui_inspect::InspectArgsDefault;
+use imgui_inspect::InspectRenderDefault;
+use imgui::ImString;
+use atelier_core::AssetUuid;
 
 #[derive(PartialEq)]
 enum InspectResult {
@@ -79,7 +83,9 @@ where
             let mut values = world.get_all_components_mut::<T>();
             let mut slice = values.as_mut_slice();
 
-            if !slice.is_empty() {
+            // Only components that exist on every selected entity are shown. Editing a component
+            // that only some of the entities have would be ambiguous.
+            if !slice.is_empty() && slice.len() == entities.len() {
                 let header_text = &imgui::im_str!("{}", core::any::type_name::<T>());
                 let content_region = ui.window_content_region_max();
 
@@ -126,7 +132,7 @@ where
                 id_token.pop(ui);
                 result
             } else {
-                // This component type is not on the selected entities
+                // This component type is not on all of the selected entities
                 InspectResult::Unchanged
             }
         };
@@ -141,6 +147,456 @@ where
     }
 }
 
+/// Produces a label for a field that is being edited on multiple entities at once. If the values
+/// differ, the label is marked as mixed. The imgui ID is kept stable either way so that toggling
+/// between mixed and not mixed doesn't drop keyboard focus.
+pub fn mixed_label(
+    label: &str,
+    is_mixed: bool,
+) -> ImString {
+    if is_mixed {
+        imgui::im_str!("{} (mixed)###{}", label, label)
+    } else {
+        imgui::im_str!("{}###{}", label, label)
+    }
+}
+
+/// Returns true if any of the values differ from the first one
+pub fn is_mixed<T: PartialEq>(data: &[&T]) -> bool {
+    data.iter().skip(1).any(|x| *x != data[0])
+}
+
+/// Same as is_mixed, for the mutable references passed to render_mut
+pub fn is_mixed_mut<T: PartialEq>(data: &[&mut T]) -> bool {
+    data.iter().skip(1).any(|x| **x != *data[0])
+}
+
+/// Renders an f32, marking it as mixed if the selected entities have different values. Use via
+/// #[inspect(proxy_type = "crate::inspect::InspectMixedF32")]
+pub struct InspectMixedF32;
+
+impl InspectRenderDefault<f32> for InspectMixedF32 {
+    fn render(
+        data: &[&f32],
+        label: &'static str,
+        ui: &Ui,
+        _args: &InspectArgsDefault,
+    ) {
+        if data.is_empty() {
+            return;
+        }
+
+        if is_mixed(data) {
+            ui.text(&imgui::im_str!("{}: (mixed)", label));
+        } else {
+            ui.text(&imgui::im_str!("{}: {}", label, data[0]));
+        }
+    }
+
+    fn render_mut(
+        data: &mut [&mut f32],
+        label: &'static str,
+        ui: &Ui,
+        _args: &InspectArgsDefault,
+    ) -> bool {
+        if data.is_empty() {
+            return false;
+        }
+
+        let mixed = is_mixed_mut(data);
+        let mut value = *data[0];
+        if ui
+            .input_float(&mixed_label(label, mixed), &mut value)
+            .build()
+        {
+            // Only the edited field is written, other fields on the entities are left alone
+            for d in data {
+                **d = value;
+            }
+            return true;
+        }
+
+        false
+    }
+}
+
+/// Renders an i32, marking it as mixed if the selected entities have different values. Use via
+/// #[inspect(proxy_type = "crate::inspect::InspectMixedI32")]
+pub struct InspectMixedI32;
+
+impl InspectRenderDefault<i32> for InspectMixedI32 {
+    fn render(
+        data: &[&i32],
+        label: &'static str,
+        ui: &Ui,
+        _args: &InspectArgsDefault,
+    ) {
+        if data.is_empty() {
+            return;
+        }
+
+        if is_mixed(data) {
+            ui.text(&imgui::im_str!("{}: (mixed)", label));
+        } else {
+            ui.text(&imgui::im_str!("{}: {}", label, data[0]));
+        }
+    }
+
+    fn render_mut(
+        data: &mut [&mut i32],
+        label: &'static str,
+        ui: &Ui,
+        _args: &InspectArgsDefault,
+    ) -> bool {
+        if data.is_empty() {
+            return false;
+        }
+
+        let mixed = is_mixed_mut(data);
+        let mut value = *data[0];
+        if ui.input_int(&mixed_label(label, mixed), &mut value).build() {
+            for d in data {
+                **d = value;
+            }
+            return true;
+        }
+
+        false
+    }
+}
+
+/// Renders a bool, marking it as mixed if the selected entities have different values. Use via
+/// #[inspect(proxy_type = "crate::inspect::InspectMixedBool")]
+pub struct InspectMixedBool;
+
+impl InspectRenderDefault<bool> for InspectMixedBool {
+    fn render(
+        data: &[&bool],
+        label: &'static str,
+        ui: &Ui,
+        _args: &InspectArgsDefault,
+    ) {
+        if data.is_empty() {
+            return;
+        }
+
+        if is_mixed(data) {
+            ui.text(&imgui::im_str!("{}: (mixed)", label));
+        } else {
+            ui.text(&imgui::im_str!("{}: {}", label, data[0]));
+        }
+    }
+
+    fn render_mut(
+        data: &mut [&mut bool],
+        label: &'static str,
+        ui: &Ui,
+        _args: &InspectArgsDefault,
+    ) -> bool {
+        if data.is_empty() {
+            return false;
+        }
+
+        let mixed = is_mixed_mut(data);
+        let mut value = *data[0];
+        if ui.checkbox(&mixed_label(label, mixed), &mut value) {
+            for d in data {
+                **d = value;
+            }
+            return true;
+        }
+
+        false
+    }
+}
+
+/// An enum with no data in its variants, which can be picked from a list in the inspector
+pub trait InspectEnum: Copy + PartialEq + 'static {
+    /// Every variant, along with the name shown for it
+    fn variants() -> &'static [(Self, &'static str)];
+
+    fn variant_name(&self) -> &'static str {
+        Self::variants()
+            .iter()
+            .find(|(variant, _)| variant == self)
+            .map(|(_, name)| *name)
+            .unwrap_or("")
+    }
+}
+
+/// Renders an enum as a combo box, marking it as mixed if the selected entities have different
+/// values. The enum must implement InspectEnum. Use via
+/// #[inspect(proxy_type = "crate::inspect::InspectMixedEnum")]
+pub struct InspectMixedEnum;
+
+impl<T: InspectEnum> InspectRenderDefault<T> for InspectMixedEnum {
+    fn render(
+        data: &[&T],
+        label: &'static str,
+        ui: &Ui,
+        _args: &InspectArgsDefault,
+    ) {
+        if data.is_empty() {
+            return;
+        }
+
+        if is_mixed(data) {
+            ui.text(&imgui::im_str!("{}: (mixed)", label));
+        } else {
+            ui.text(&imgui::im_str!("{}: {}", label, data[0].variant_name()));
+        }
+    }
+
+    fn render_mut(
+        data: &mut [&mut T],
+        label: &'static str,
+        ui: &Ui,
+        _args: &InspectArgsDefault,
+    ) -> bool {
+        if data.is_empty() {
+            return false;
+        }
+
+        let mixed = is_mixed_mut(data);
+        let variants = T::variants();
+        let names: Vec<ImString> = variants
+            .iter()
+            .map(|(_, name)| ImString::new(*name))
+            .collect();
+        let name_refs: Vec<&ImString> = names.iter().collect();
+
+        let original = variants
+            .iter()
+            .position(|(variant, _)| variant == &*data[0])
+            .unwrap_or(0);
+        let mut selected = original;
+        if imgui::ComboBox::new(&mixed_label(label, mixed)).build_simple_string(
+            ui,
+            &mut selected,
+            &name_refs,
+        ) && selected != original
+        {
+            for d in data {
+                **d = variants[selected].0;
+            }
+            return true;
+        }
+
+        false
+    }
+}
+
+/// Renders a color with a color picker, marking it as mixed if the selected entities have
+/// different values. Use via #[inspect(proxy_type = "crate::inspect::InspectColor")]
+pub struct InspectColor;
+
+impl InspectRenderDefault<crate::math::Vec4> for InspectColor {
+    fn render(
+        data: &[&crate::math::Vec4],
+        label: &'static str,
+        ui: &Ui,
+        _args: &InspectArgsDefault,
+    ) {
+        if data.is_empty() {
+            return;
+        }
+
+        if is_mixed(data) {
+            ui.text(&imgui::im_str!("{}: (mixed)", label));
+        } else {
+            let value = [data[0].x(), data[0].y(), data[0].z(), data[0].w()];
+            ui.text(&imgui::im_str!("{}:", label));
+            ui.same_line(0.0);
+            imgui::ColorButton::new(&imgui::im_str!("{}", label), value).build(ui);
+        }
+    }
+
+    fn render_mut(
+        data: &mut [&mut crate::math::Vec4],
+        label: &'static str,
+        ui: &Ui,
+        _args: &InspectArgsDefault,
+    ) -> bool {
+        if data.is_empty() {
+            return false;
+        }
+
+        let mixed = is_mixed_mut(data);
+        let mut value = [data[0].x(), data[0].y(), data[0].z(), data[0].w()];
+        if imgui::ColorEdit::new(&mixed_label(label, mixed), &mut value)
+            .alpha_bar(true)
+            .build(ui)
+        {
+            // The picker edits all four channels together, so the whole color is written
+            for d in data {
+                **d = glam::Vec4::new(value[0], value[1], value[2], value[3]).into();
+            }
+            return true;
+        }
+
+        false
+    }
+}
+
+/// Renders a list of points that can be edited, added to and removed from. Lists that differ
+/// between the selected entities can only be replaced as a whole. Use via
+/// #[inspect(proxy_type = "crate::inspect::InspectPoints")]
+pub struct InspectPoints;
+
+impl InspectRenderDefault<Vec<crate::math::Vec2>> for InspectPoints {
+    fn render(
+        data: &[&Vec<crate::math::Vec2>],
+        label: &'static str,
+        ui: &Ui,
+        _args: &InspectArgsDefault,
+    ) {
+        if data.is_empty() {
+            return;
+        }
+
+        if is_mixed(data) {
+            ui.text(&imgui::im_str!("{}: (mixed)", label));
+            return;
+        }
+
+        ui.text(&imgui::im_str!("{}:", label));
+        for point in data[0] {
+            ui.text(&imgui::im_str!("  {} {}", point.x(), point.y()));
+        }
+    }
+
+    fn render_mut(
+        data: &mut [&mut Vec<crate::math::Vec2>],
+        label: &'static str,
+        ui: &Ui,
+        _args: &InspectArgsDefault,
+    ) -> bool {
+        if data.is_empty() {
+            return false;
+        }
+
+        let mixed = is_mixed_mut(data);
+        if mixed {
+            ui.text(&imgui::im_str!("{}: (mixed)", label));
+            ui.same_line(0.0);
+            if ui.small_button(&imgui::im_str!("Use first###{}", label)) {
+                let first = data[0].clone();
+                for d in data {
+                    **d = first.clone();
+                }
+                return true;
+            }
+
+            return false;
+        }
+
+        let mut points = data[0].clone();
+        let mut changed = false;
+        let mut removed = None;
+
+        ui.text(&imgui::im_str!("{}:", label));
+        for (i, point) in points.iter_mut().enumerate() {
+            let mut value = [point.x(), point.y()];
+            if ui
+                .input_float2(&imgui::im_str!("###{}{}", label, i), &mut value)
+                .build()
+            {
+                *point = glam::Vec2::new(value[0], value[1]).into();
+                changed = true;
+            }
+
+            ui.same_line(0.0);
+            if ui.small_button(&imgui::im_str!("X###{}{}remove", label, i)) {
+                removed = Some(i);
+            }
+        }
+
+        if let Some(removed) = removed {
+            points.remove(removed);
+            changed = true;
+        }
+
+        // New points continue on from the last one so they don't all stack up at the origin
+        if ui.small_button(&imgui::im_str!("Add point###{}add", label)) {
+            let new_point = points
+                .last()
+                .map(|last| **last + glam::Vec2::new(1.0, 0.0))
+                .unwrap_or_else(glam::Vec2::zero);
+            points.push(new_point.into());
+            changed = true;
+        }
+
+        if changed {
+            for d in data {
+                **d = points.clone();
+            }
+        }
+
+        changed
+    }
+}
+
+/// Renders an asset UUID as text that can be edited. Use via
+/// #[inspect(proxy_type = "crate::inspect::InspectAssetUuid")]
+pub struct InspectAssetUuid;
+
+impl InspectRenderDefault<AssetUuid> for InspectAssetUuid {
+    fn render(
+        data: &[&AssetUuid],
+        label: &'static str,
+        ui: &Ui,
+        _args: &InspectArgsDefault,
+    ) {
+        if data.is_empty() {
+            return;
+        }
+
+        if is_mixed(data) {
+            ui.text(&imgui::im_str!("{}: (mixed)", label));
+        } else {
+            ui.text(&imgui::im_str!(
+                "{}: {}",
+                label,
+                uuid::Uuid::from_bytes(data[0].0)
+            ));
+        }
+    }
+
+    fn render_mut(
+        data: &mut [&mut AssetUuid],
+        label: &'static str,
+        ui: &Ui,
+        _args: &InspectArgsDefault,
+    ) -> bool {
+        if data.is_empty() {
+            return false;
+        }
+
+        let mixed = is_mixed_mut(data);
+        let mut value = ImString::new(uuid::Uuid::from_bytes(data[0].0).to_string());
+        value.reserve(64);
+
+        // Only apply the text once enter is pressed, most partially-typed UUIDs aren't valid
+        if ui
+            .input_text(&mixed_label(label, mixed), &mut value)
+            .enter_returns_true(true)
+            .build()
+        {
+            match uuid::Uuid::parse_str(value.to_str()) {
+                Ok(parsed) => {
+                    for d in data {
+                        **d = AssetUuid(*parsed.as_bytes());
+                    }
+                    return true;
+                }
+                Err(e) => log::warn!("Invalid asset UUID {}: {}", value.to_str(), e),
+            }
+        }
+
+        false
+    }
+}
+
 #[derive(Default)]
 pub struct EditorInspectRegistry {
     registered: Vec<Box<dyn RegisteredEditorInspectorT>>,
@@ -0,0 +1,140 @@
+use legion::prelude::*;
+use legion::storage::{Component, ComponentTypeId};
+use std::marker::PhantomData;
+
+use crate::spawn_mappings::ComponentSpawnMappings;
+
+/// Turns a component in the play world back into the def it was spawned from, so that its values
+/// at runtime can be kept. Returns None if the component wasn't spawned from this def (i.e. a rigid
+/// body spawned from a different shape)
+pub trait DefFromRuntime<RuntimeT>: Sized {
+    fn def_from_runtime(runtime: &RuntimeT) -> Option<Self>;
+}
+
+// Components that are spawned as themselves are kept as they are
+impl<T: Clone> DefFromRuntime<T> for T {
+    fn def_from_runtime(runtime: &T) -> Option<Self> {
+        Some(runtime.clone())
+    }
+}
+
+/// A trait object which allows dynamic dispatch into copying a component between worlds
+trait RegisteredKeepChangesT: Send + Sync {
+    fn copy_component(
+        &self,
+        src_world: &World,
+        src_entity: Entity,
+        dst_world: &mut World,
+        dst_entity: Entity,
+    );
+}
+
+/// Implements the RegisteredKeepChangesT trait object with code that can turn RuntimeT in one world
+/// into DefT in another
+#[derive(Default)]
+struct RegisteredKeepChanges<RuntimeT, DefT> {
+    phantom_data: PhantomData<(RuntimeT, DefT)>,
+}
+
+impl<RuntimeT, DefT> RegisteredKeepChanges<RuntimeT, DefT>
+where
+    RuntimeT: Component,
+    DefT: Component + DefFromRuntime<RuntimeT>,
+{
+    fn new() -> Self {
+        RegisteredKeepChanges {
+            phantom_data: Default::default(),
+        }
+    }
+}
+
+impl<RuntimeT, DefT> RegisteredKeepChangesT for RegisteredKeepChanges<RuntimeT, DefT>
+where
+    RuntimeT: Component,
+    DefT: Component + DefFromRuntime<RuntimeT>,
+{
+    fn copy_component(
+        &self,
+        src_world: &World,
+        src_entity: Entity,
+        dst_world: &mut World,
+        dst_entity: Entity,
+    ) {
+        let value = src_world
+            .get_component::<RuntimeT>(src_entity)
+            .and_then(|component| DefT::def_from_runtime(&*component));
+
+        // Only overwrite components that already exist on the def. Adding or removing components
+        // during play isn't something we try to keep
+        if let Some(value) = value {
+            if let Some(mut component) = dst_world.get_component_mut::<DefT>(dst_entity) {
+                *component = value;
+            }
+        }
+    }
+}
+
+/// Tracks which components can be copied from the play world back into the prefab being edited.
+/// Components that exist unchanged in both the prefab and the spawned world are copied as they are.
+/// Components that are spawned from a def (i.e. rigid bodies) are turned back into the def.
+pub struct KeepChangesRegistry {
+    spawn_mappings: ComponentSpawnMappings,
+    registered: Vec<Box<dyn RegisteredKeepChangesT>>,
+}
+
+impl KeepChangesRegistry {
+    pub fn new(spawn_mappings: ComponentSpawnMappings) -> Self {
+        KeepChangesRegistry {
+            spawn_mappings,
+            registered: Default::default(),
+        }
+    }
+
+    /// Adds a type to the registry, which allows its values at runtime to be kept when play stops.
+    /// Panics if the type is spawned from (or into) a different type. Use register_converted for
+    /// those
+    pub fn register<T: Component + Clone>(&mut self) {
+        let component_type = ComponentTypeId::of::<T>();
+        assert!(
+            !self.spawn_mappings.is_runtime_only(component_type),
+            "{} is changed by spawning, so it can't be kept from play as it is",
+            std::any::type_name::<T>()
+        );
+
+        self.register_converted::<T, T>();
+    }
+
+    /// Adds a def to the registry, which allows the values of the component it was spawned as to
+    /// be kept when play stops. Panics if DefT isn't spawned as RuntimeT
+    pub fn register_converted<RuntimeT, DefT>(&mut self)
+    where
+        RuntimeT: Component,
+        DefT: Component + DefFromRuntime<RuntimeT>,
+    {
+        assert!(
+            self.spawn_mappings
+                .spawned_type(ComponentTypeId::of::<DefT>())
+                == ComponentTypeId::of::<RuntimeT>(),
+            "{} isn't spawned as {}, so it can't be kept from it",
+            std::any::type_name::<DefT>(),
+            std::any::type_name::<RuntimeT>()
+        );
+
+        self.registered
+            .push(Box::new(RegisteredKeepChanges::<RuntimeT, DefT>::new()));
+    }
+
+    /// Copies all registered components from the entity in the play world to the entity in the
+    /// prefab world
+    pub fn copy_components(
+        &self,
+        play_world: &World,
+        play_entity: Entity,
+        prefab_world: &mut World,
+        prefab_entity: Entity,
+    ) {
+        for r in &self.registered {
+            r.copy_component(play_world, play_entity, prefab_world, prefab_entity);
+        }
+    }
+}
@@ -7,6 +7,8 @@ extern crate nalgebra_glm as glm;
 use legion::prelude::*;
 
 use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::path::PathBuf;
 use legion::storage::ComponentTypeId;
 use legion_prefab::ComponentRegistration;
 use prefab_format::ComponentTypeUuid;
@@ -14,6 +16,12 @@ use atelier_core::asset_uuid;
 
 mod asset_storage;
 
+mod asset_loader;
+
+pub mod packfile;
+
+pub mod handle_def;
+
 mod components;
 use components::*;
 
@@ -29,6 +37,15 @@ use selection::EditorSelectableRegistry;
 mod inspect;
 use inspect::EditorInspectRegistry;
 
+mod keep_changes;
+use keep_changes::KeepChangesRegistry;
+
+pub mod editor_commands;
+use editor_commands::{EditorCommand, EditorCommandRegistry};
+
+mod spawn_mappings;
+use spawn_mappings::{ComponentSpawnMappings, SpawnMappingsBuilder};
+
 pub mod math;
 
 mod pipeline;
@@ -52,10 +69,29 @@ use legion_transaction::SpawnCloneImplHandlerSet;
 pub const GROUND_HALF_EXTENTS_WIDTH: f32 = 3.0;
 pub const GRAVITY: f32 = -9.81;
 
+/// Where assets are loaded from
+#[derive(Clone, Debug)]
+pub enum AssetSource {
+    /// The asset daemon listening on this address. Assets are reloaded when their source changes
+    Daemon(SocketAddr),
+
+    /// A packfile created by the asset_pack binary. Neither the daemon nor the source assets are
+    /// needed
+    Packfile(PathBuf),
+}
+
 /// Create the asset manager that has all the required types registered
-pub fn create_asset_manager() -> AssetResource {
-    let mut asset_manager = AssetResource::default();
+pub fn create_asset_manager(asset_source: &AssetSource) -> AssetResource {
+    let mut asset_manager = match asset_source {
+        AssetSource::Daemon(daemon_address) => {
+            AssetResource::new(*daemon_address).expect("failed to create asset loader")
+        }
+        AssetSource::Packfile(path) => AssetResource::from_packfile(path)
+            .unwrap_or_else(|e| panic!("failed to open packfile {}: {}", path.display(), e)),
+    };
     asset_manager.add_storage::<PrefabAsset>();
+    asset_manager.add_storage::<ImageAsset>();
+    asset_manager.add_storage::<VectorShapeAsset>();
     asset_manager
 }
 
@@ -78,16 +114,48 @@ pub fn create_component_registry_by_uuid() -> HashMap<ComponentTypeUuid, Compone
     component_types
 }
 
+// Every def component that is transformed into a different component when it's spawned
+fn create_spawn_mappings_builder() -> SpawnMappingsBuilder {
+    let mut builder = SpawnMappingsBuilder::new();
+    builder.add_mapping_into::<DrawSkiaCircleComponentDef, DrawSkiaCircleComponent>();
+    builder.add_mapping_into::<DrawSkiaBoxComponentDef, DrawSkiaBoxComponent>();
+    builder.add_mapping_into::<DrawSkiaRoundedRectComponentDef, DrawSkiaRoundedRectComponent>();
+    builder.add_mapping_into::<DrawSkiaPolygonComponentDef, DrawSkiaPolygonComponent>();
+    builder.add_mapping_into::<DrawSkiaLineStripComponentDef, DrawSkiaLineStripComponent>();
+    builder.add_mapping::<DrawSkiaSpriteComponentDef, DrawSkiaSpriteComponent>();
+    builder.add_mapping::<DrawSkiaVectorShapeComponentDef, DrawSkiaVectorShapeComponent>();
+    builder.add_mapping::<RigidBodyBallComponentDef, RigidBodyComponent>();
+    builder.add_mapping::<RigidBodyBoxComponentDef, RigidBodyComponent>();
+    builder
+}
+
 pub fn create_spawn_clone_impl_handler_set(
 ) -> SpawnCloneImplHandlerSet {
+    let (handler_set, _) = create_spawn_mappings_builder().build();
+    handler_set
+}
+
+// Every runtime component, so that the edit world can be cloned into the play world. Components
+// that are stored in prefabs are cloned through their registration instead. Rigid bodies are
+// spawned again into the play world's physics, since their bodies belong to the edit world's
+pub fn create_play_clone_impl_handler_set() -> SpawnCloneImplHandlerSet {
     let mut handler_set = SpawnCloneImplHandlerSet::new();
-    handler_set.add_mapping_into::<DrawSkiaCircleComponentDef, DrawSkiaCircleComponent>();
-    handler_set.add_mapping_into::<DrawSkiaBoxComponentDef, DrawSkiaBoxComponent>();
-    handler_set.add_mapping::<RigidBodyBallComponentDef, RigidBodyComponent>();
-    handler_set.add_mapping::<RigidBodyBoxComponentDef, RigidBodyComponent>();
+    handler_set.add_mapping_into::<DrawSkiaCircleComponent, DrawSkiaCircleComponent>();
+    handler_set.add_mapping_into::<DrawSkiaBoxComponent, DrawSkiaBoxComponent>();
+    handler_set.add_mapping_into::<DrawSkiaRoundedRectComponent, DrawSkiaRoundedRectComponent>();
+    handler_set.add_mapping_into::<DrawSkiaPolygonComponent, DrawSkiaPolygonComponent>();
+    handler_set.add_mapping_into::<DrawSkiaLineStripComponent, DrawSkiaLineStripComponent>();
+    handler_set.add_mapping_into::<DrawSkiaSpriteComponent, DrawSkiaSpriteComponent>();
+    handler_set.add_mapping_into::<DrawSkiaVectorShapeComponent, DrawSkiaVectorShapeComponent>();
+    handler_set.add_mapping::<RigidBodyComponent, RigidBodyComponent>();
     handler_set
 }
 
+pub fn create_component_spawn_mappings() -> ComponentSpawnMappings {
+    let (_, mappings) = create_spawn_mappings_builder().build();
+    mappings
+}
+
 pub fn create_spawn_clone_impl<'a, 'b, 'c>(
     handler_set: &'a SpawnCloneImplHandlerSet,
     component_registry: &'b HashMap<ComponentTypeId, ComponentRegistration>,
@@ -100,6 +168,11 @@ pub fn create_editor_selection_registry() -> EditorSelectableRegistry {
     let mut registry = EditorSelectableRegistry::default();
     registry.register::<DrawSkiaBoxComponent>();
     registry.register::<DrawSkiaCircleComponent>();
+    registry.register::<DrawSkiaRoundedRectComponent>();
+    registry.register::<DrawSkiaPolygonComponent>();
+    registry.register::<DrawSkiaLineStripComponent>();
+    registry.register::<DrawSkiaSpriteComponent>();
+    registry.register::<DrawSkiaVectorShapeComponent>();
     registry.register_transformed::<RigidBodyBoxComponentDef, RigidBodyComponent>();
     registry.register_transformed::<RigidBodyBallComponentDef, RigidBodyComponent>();
     registry
@@ -109,23 +182,106 @@ pub fn create_editor_inspector_registry() -> EditorInspectRegistry {
     let mut registry = EditorInspectRegistry::default();
     registry.register::<DrawSkiaCircleComponentDef>();
     registry.register::<DrawSkiaBoxComponentDef>();
+    registry.register::<DrawSkiaRoundedRectComponentDef>();
+    registry.register::<DrawSkiaPolygonComponentDef>();
+    registry.register::<DrawSkiaLineStripComponentDef>();
+    registry.register::<DrawSkiaSpriteComponentDef>();
+    registry.register::<DrawSkiaVectorShapeComponentDef>();
+    registry.register::<Position2DComponent>();
+    registry.register::<UniformScale2DComponent>();
+    registry.register::<NonUniformScale2DComponent>();
+    registry.register::<Rotation2DComponent>();
+    registry.register::<DrawOrderComponent>();
+    registry.register::<RigidBodyBallComponentDef>();
+    registry.register::<RigidBodyBoxComponentDef>();
+    registry
+}
+
+// Every component that can be stored in a prefab, so that changes to it on disk can be merged with
+// the editor's changes
+pub fn create_prefab_merge_registry() -> PrefabMergeRegistry {
+    let mut registry = PrefabMergeRegistry::default();
+    registry.register::<DrawSkiaCircleComponentDef>();
+    registry.register::<DrawSkiaBoxComponentDef>();
+    registry.register::<DrawSkiaRoundedRectComponentDef>();
+    registry.register::<DrawSkiaPolygonComponentDef>();
+    registry.register::<DrawSkiaLineStripComponentDef>();
+    registry.register::<DrawSkiaSpriteComponentDef>();
+    registry.register::<DrawSkiaVectorShapeComponentDef>();
     registry.register::<Position2DComponent>();
     registry.register::<UniformScale2DComponent>();
     registry.register::<NonUniformScale2DComponent>();
     registry.register::<Rotation2DComponent>();
+    registry.register::<DrawOrderComponent>();
     registry.register::<RigidBodyBallComponentDef>();
     registry.register::<RigidBodyBoxComponentDef>();
     registry
 }
 
+// Every component that can be kept from play. Components that are spawned from a def are turned
+// back into it
+pub fn create_keep_changes_registry() -> KeepChangesRegistry {
+    let mut registry = KeepChangesRegistry::new(create_component_spawn_mappings());
+    registry.register::<Position2DComponent>();
+    registry.register::<UniformScale2DComponent>();
+    registry.register::<NonUniformScale2DComponent>();
+    registry.register::<Rotation2DComponent>();
+    registry.register::<DrawOrderComponent>();
+    registry.register_converted::<DrawSkiaCircleComponent, DrawSkiaCircleComponentDef>();
+    registry.register_converted::<DrawSkiaBoxComponent, DrawSkiaBoxComponentDef>();
+    registry.register_converted::<DrawSkiaRoundedRectComponent, DrawSkiaRoundedRectComponentDef>();
+    registry.register_converted::<DrawSkiaPolygonComponent, DrawSkiaPolygonComponentDef>();
+    registry.register_converted::<DrawSkiaLineStripComponent, DrawSkiaLineStripComponentDef>();
+    registry.register_converted::<DrawSkiaSpriteComponent, DrawSkiaSpriteComponentDef>();
+    registry.register_converted::<DrawSkiaVectorShapeComponent, DrawSkiaVectorShapeComponentDef>();
+    registry.register_converted::<RigidBodyComponent, RigidBodyBallComponentDef>();
+    registry.register_converted::<RigidBodyComponent, RigidBodyBoxComponentDef>();
+    registry
+}
+
+pub fn create_editor_command_registry() -> EditorCommandRegistry {
+    let mut registry = EditorCommandRegistry::default();
+    for action in EditorAction::ALL.iter() {
+        registry.register(EditorCommand::from_action(*action));
+    }
+
+    registry.register(EditorCommand::toggle_window("ImGui Metrics", |w| &mut w.show_imgui_metrics));
+    registry.register(EditorCommand::toggle_window(
+        "ImGui Style Editor",
+        |w| &mut w.show_imgui_style_editor,
+    ));
+    registry.register(EditorCommand::toggle_window("ImGui Demo", |w| &mut w.show_imgui_demo));
+    registry.register(EditorCommand::toggle_window("Entity List", |w| &mut w.show_entity_list));
+    registry.register(EditorCommand::toggle_window("Inspector", |w| &mut w.show_inspector));
+    registry.register(EditorCommand::toggle_window("Documents", |w| &mut w.show_documents));
+    registry.register(EditorCommand::toggle_window("Undo History", |w| &mut w.show_undo_history));
+    registry.register(EditorCommand::toggle_window("Keybindings", |w| &mut w.show_keybindings));
+    registry.register(EditorCommand::toggle_window("Assets", |w| &mut w.show_assets));
+    registry.register(EditorCommand::toggle_window("Layers", |w| &mut w.show_layers));
+
+    registry.register_provider(editor_commands::open_prefab_commands);
+    registry.register_provider(editor_commands::document_commands);
+    registry.register_provider(editor_commands::undo_history_commands);
+    registry.register_provider(editor_commands::add_component_commands);
+    registry
+}
+
 pub struct DemoApp {
     update_schedules: HashMap<ScheduleCriteria, Schedule>,
     draw_schedules: HashMap<ScheduleCriteria, Schedule>,
+
+    // Where to load assets from. The asset daemon may be running in this process (see main.rs)
+    asset_source: AssetSource,
+
+    // The asset daemon started by this process, if any. It's stopped when the app shuts down
+    embedded_daemon: Option<daemon::AssetDaemonHandle>,
 }
 
 impl DemoApp {
-    #[allow(clippy::new_without_default)]
-    pub fn new() -> Self {
+    pub fn new(
+        asset_source: AssetSource,
+        embedded_daemon: Option<daemon::AssetDaemonHandle>,
+    ) -> Self {
         // The expected states for which we will generate schedules
         let expected_criteria = vec![
             ScheduleCriteria::new(false, EditorMode::Inactive),
@@ -146,6 +302,8 @@ impl DemoApp {
         DemoApp {
             update_schedules,
             draw_schedules,
+            asset_source,
+            embedded_daemon,
         }
     }
 
@@ -170,7 +328,7 @@ impl app::AppHandler for DemoApp {
         world: &mut World,
         resources: &mut Resources,
     ) {
-        let asset_manager = create_asset_manager();
+        let asset_manager = create_asset_manager(&self.asset_source);
         let physics = PhysicsResource::new(glam::Vec2::unit_y() * GRAVITY);
 
         let window_size = resources.get::<InputResource>().unwrap().window_size();
@@ -184,7 +342,14 @@ impl app::AppHandler for DemoApp {
         resources.insert(physics);
         resources.insert(FpsTextResource::new());
         resources.insert(asset_manager);
-        resources.insert(EditorStateResource::new());
+        resources.insert(AssetStatusResource::new());
+        let preferences = EditorPreferences::load();
+        let mut editor_state = EditorStateResource::new();
+        editor_state.apply_preferences(&preferences);
+
+        resources.insert(editor_state);
+        resources.insert(EditorKeybindingsResource::load());
+        resources.insert(create_editor_command_registry());
         resources.insert(camera);
         resources.insert(viewport);
         resources.insert(DebugDrawResource::new());
@@ -195,12 +360,34 @@ impl app::AppHandler for DemoApp {
 
         resources.insert(selection_resource);
 
-        // Start the application
-        EditorStateResource::open_prefab(
-            world,
-            resources,
-            asset_uuid!("3991506e-ed7e-4bcb-8cfd-3366b31a6439"),
-        );
+        // Start the application. Reopen the prefab from the last session if it still exists
+        let prefab_uuid = preferences
+            .last_opened_prefab
+            .filter(|last_opened_prefab| {
+                find_prefab_source_files()
+                    .iter()
+                    .any(|prefab_source_file| prefab_source_file.uuid == *last_opened_prefab)
+            })
+            .unwrap_or(asset_uuid!("3991506e-ed7e-4bcb-8cfd-3366b31a6439"));
+
+        EditorStateResource::open_prefab_document(world, resources, prefab_uuid);
+    }
+
+    fn shutdown(
+        &mut self,
+        world: &mut World,
+        resources: &mut Resources,
+    ) {
+        // Start from what's on disk so that cameras for prefabs that weren't opened are kept
+        let mut preferences = EditorPreferences::load();
+        let editor_state = resources.get::<EditorStateResource>().unwrap();
+        let camera = resources.get::<CameraResource>().unwrap();
+        editor_state.update_preferences(&mut preferences, &*camera);
+        preferences.save();
+
+        if let Some(embedded_daemon) = self.embedded_daemon.take() {
+            embedded_daemon.stop();
+        }
     }
 
     fn update(
@@ -223,6 +410,18 @@ impl app::AppHandler for DemoApp {
         schedule.execute(world, resources);
     }
 
+    fn close_requested(
+        &mut self,
+        _world: &mut World,
+        resources: &mut Resources,
+    ) {
+        // Goes through the same path as the quit keybinding so unsaved changes are confirmed
+        resources
+            .get_mut::<EditorStateResource>()
+            .unwrap()
+            .enqueue_quit();
+    }
+
     fn fatal_error(
         &mut self,
         error: &app::AppError,
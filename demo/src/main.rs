@@ -1,26 +1,75 @@
 use skulpin::app::LogicalSize;
 
 use std::ffi::CString;
+use std::net::SocketAddr;
+use std::path::PathBuf;
 
-use atelier_legion_demo::DemoApp;
+use structopt::StructOpt;
+
+use atelier_legion_demo::{AssetSource, DemoApp};
 use atelier_legion_demo::daemon;
 //use atelier_legion_demo::game;
 
+/// Parameters to the demo.
+///
+/// # Examples
+///
+/// ```bash
+/// # Connect to a daemon started with the asset_daemon binary
+/// atelier-legion-demo --external-daemon --daemon-address "127.0.0.1:9999"
+///
+/// # Load assets from a packfile created with the asset_pack binary, without a daemon
+/// atelier-legion-demo --packfile assets.pack
+/// ```
+#[derive(StructOpt)]
+struct DemoOpt {
+    /// Socket address of the asset daemon to load assets from, e.g. "127.0.0.1:9999". If the
+    /// daemon is embedded, it listens on this address.
+    #[structopt(
+    long,
+    parse(try_from_str = daemon::parse_socket_addr),
+    default_value = daemon::DEFAULT_DAEMON_ADDRESS
+    )]
+    daemon_address: SocketAddr,
+    /// Connect to an asset daemon that is already running instead of starting one in this process.
+    #[structopt(long)]
+    external_daemon: bool,
+    /// Load assets from this packfile instead of the asset daemon. The daemon isn't started.
+    #[structopt(long, parse(from_os_str))]
+    packfile: Option<PathBuf>,
+}
+
 fn main() {
+    let opt = DemoOpt::from_args();
+
     // Setup logging
     env_logger::Builder::from_default_env()
         .filter_level(log::LevelFilter::Debug)
         .filter_module("tokio_reactor", log::LevelFilter::Info)
         .init();
 
-    // Spawn the daemon in a background thread. This could be a different process, but
-    // for simplicity we'll launch it here.
-    std::thread::spawn(move || {
-        daemon::run();
-    });
+    let mut embedded_daemon = None;
+    let asset_source = match opt.packfile {
+        Some(packfile) => AssetSource::Packfile(packfile),
+        None => {
+            // Unless we were asked to use a daemon that's already running, spawn the daemon in a
+            // background thread. It could also be run as a separate process with the asset_daemon
+            // binary.
+            if !opt.external_daemon {
+                let daemon_config = daemon::AssetDaemonConfig {
+                    address: opt.daemon_address,
+                    ..Default::default()
+                };
+
+                embedded_daemon = Some(daemon::spawn(daemon_config));
+            }
+
+            AssetSource::Daemon(opt.daemon_address)
+        }
+    };
 
-    // Build the app and run it
-    let example_app = DemoApp::new();
+    // Build the app and run it. The app stops the embedded daemon when it shuts down
+    let example_app = DemoApp::new(asset_source, embedded_daemon);
     let renderer_builder = skulpin::RendererBuilder::new()
         .app_name(CString::new("Skulpin Example App").unwrap())
         .use_vulkan_debug_layer(true);
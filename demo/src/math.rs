@@ -119,15 +119,22 @@ impl InspectRenderDefault<Vec2> for Vec2 {
         }
 
         let mut changed = false;
-        let mut val = [data[0].x(), data[0].y()];
+        let mixed = data.iter().skip(1).any(|x| **x != *data[0]);
+        let original = [data[0].x(), data[0].y()];
+        let mut val = original;
         if ui
-            .input_float2(&imgui::im_str!("{}", label), &mut val)
+            .input_float2(&crate::inspect::mixed_label(label, mixed), &mut val)
             .build()
         {
+            // Only write the axes that were edited so that mixed values on other axes are kept
             changed = true;
             for d in data {
-                d.set_x(val[0]);
-                d.set_y(val[1]);
+                if val[0] != original[0] {
+                    d.set_x(val[0]);
+                }
+                if val[1] != original[1] {
+                    d.set_y(val[1]);
+                }
             }
         }
 
@@ -223,16 +230,24 @@ impl InspectRenderDefault<Vec3> for Vec3 {
         }
 
         let mut changed = false;
-        let mut val = [data[0].x(), data[0].y(), data[0].z()];
+        let mixed = data.iter().skip(1).any(|x| **x != *data[0]);
+        let original = [data[0].x(), data[0].y(), data[0].z()];
+        let mut val = original;
         if ui
-            .input_float3(&imgui::im_str!("{}", label), &mut val)
+            .input_float3(&crate::inspect::mixed_label(label, mixed), &mut val)
             .build()
         {
             changed = true;
             for d in data {
-                d.set_x(val[0]);
-                d.set_y(val[1]);
-                d.set_z(val[2]);
+                if val[0] != original[0] {
+                    d.set_x(val[0]);
+                }
+                if val[1] != original[1] {
+                    d.set_y(val[1]);
+                }
+                if val[2] != original[2] {
+                    d.set_z(val[2]);
+                }
             }
         }
 
@@ -329,17 +344,27 @@ impl InspectRenderDefault<Vec4> for Vec4 {
         }
 
         let mut changed = false;
-        let mut val = [data[0].x(), data[0].y(), data[0].z(), data[0].w()];
+        let mixed = data.iter().skip(1).any(|x| **x != *data[0]);
+        let original = [data[0].x(), data[0].y(), data[0].z(), data[0].w()];
+        let mut val = original;
         if ui
-            .input_float4(&imgui::im_str!("{}", label), &mut val)
+            .input_float4(&crate::inspect::mixed_label(label, mixed), &mut val)
             .build()
         {
             changed = true;
             for d in data {
-                d.set_x(val[0]);
-                d.set_y(val[1]);
-                d.set_z(val[2]);
-                d.set_w(val[3]);
+                if val[0] != original[0] {
+                    d.set_x(val[0]);
+                }
+                if val[1] != original[1] {
+                    d.set_y(val[1]);
+                }
+                if val[2] != original[2] {
+                    d.set_z(val[2]);
+                }
+                if val[3] != original[3] {
+                    d.set_w(val[3]);
+                }
             }
         }
 
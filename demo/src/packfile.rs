@@ -0,0 +1,580 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use atelier_core::{AssetRef, AssetUuid};
+use atelier_loader::{
+    crossbeam_channel::Receiver, handle::RefOp, AssetStorage, AssetTypeId, LoadHandle, LoadStatus,
+    LoaderInfoProvider,
+};
+use legion::prelude::*;
+use legion_prefab::CookedPrefab;
+use serde::{Deserialize, Serialize};
+use type_uuid::TypeUuid;
+
+use crate::asset_loader::AssetLoader;
+use crate::asset_storage::GenericAssetStorage;
+use crate::handle_def;
+use crate::pipeline::PrefabAsset;
+use crate::resources::{find_all_asset_source_files, AssetResource};
+use crate::AssetSource;
+
+// Incremented whenever the layout of Packfile changes, so that old packfiles are rejected rather
+// than misread
+const PACKFILE_VERSION: u32 = 1;
+
+/// A single built asset, in the same form the asset daemon sends it to the loader
+#[derive(Serialize, Deserialize)]
+pub struct PackfileAsset {
+    pub id: AssetUuid,
+    pub asset_type: [u8; 16],
+
+    /// Assets that must be loaded before this one
+    pub load_deps: Vec<AssetUuid>,
+
+    /// The asset serialized with bincode
+    pub data: Vec<u8>,
+}
+
+/// A prefab with all the prefabs it references already applied (see prefab_cooking)
+#[derive(Serialize, Deserialize)]
+pub struct PackfileCookedPrefab {
+    pub prefab_id: AssetUuid,
+
+    /// The CookedPrefab serialized with bincode
+    pub data: Vec<u8>,
+}
+
+/// Everything the app needs to run without the asset daemon or the source assets. Created by the
+/// asset_pack binary and loaded with PackfileLoader
+#[derive(Serialize, Deserialize)]
+pub struct Packfile {
+    pub version: u32,
+    pub assets: Vec<PackfileAsset>,
+    pub cooked_prefabs: Vec<PackfileCookedPrefab>,
+}
+
+impl Packfile {
+    pub fn new() -> Self {
+        Packfile {
+            version: PACKFILE_VERSION,
+            assets: vec![],
+            cooked_prefabs: vec![],
+        }
+    }
+
+    pub fn read(path: &Path) -> std::io::Result<Self> {
+        let bytes = std::fs::read(path)?;
+        let packfile: Packfile = bincode::deserialize(&bytes)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+
+        if packfile.version != PACKFILE_VERSION {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!(
+                    "Packfile {} has version {}, expected {}. It must be packed again",
+                    path.display(),
+                    packfile.version,
+                    PACKFILE_VERSION
+                ),
+            ));
+        }
+
+        Ok(packfile)
+    }
+
+    pub fn write(
+        &self,
+        path: &Path,
+    ) -> std::io::Result<()> {
+        let bytes = bincode::serialize(self)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        std::fs::write(path, bytes)
+    }
+}
+
+impl Default for Packfile {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Prefabs depend on the prefabs they reference and any asset referenced by a HandleDef. This
+// matches the load deps the prefab importer reports
+fn prefab_load_deps(data: &[u8]) -> Result<Vec<AssetUuid>, Box<dyn Error>> {
+    let prefab_asset: PrefabAsset = bincode::deserialize(data)?;
+
+    let (serialized, mut load_deps) =
+        handle_def::collect_asset_references(|| bincode::serialize(&prefab_asset));
+    serialized?;
+
+    for other_prefab_id in prefab_asset.prefab.prefab_meta.prefab_refs.keys() {
+        load_deps.push(AssetUuid(*other_prefab_id));
+    }
+
+    load_deps.sort_by_key(|id| id.0);
+    load_deps.dedup();
+    Ok(load_deps)
+}
+
+/// How long pack_assets waits for another asset to finish loading before giving up
+const PACK_LOAD_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Loads every imported asset from the asset daemon at the given address and returns a packfile
+/// containing all of them, plus a cooked version of every prefab. The daemon must be running and
+/// have finished importing.
+pub fn pack_assets(daemon_address: SocketAddr) -> Result<Packfile, Box<dyn Error>> {
+    let mut asset_resource = crate::create_asset_manager(&AssetSource::Daemon(daemon_address));
+    asset_resource.storage().start_recording_artifacts();
+
+    // Request every asset. Dependencies are loaded by the loader, so they are recorded too
+    let requested_assets: Vec<_> = find_all_asset_source_files()
+        .into_iter()
+        .map(|asset_source_file| {
+            let load_handle = asset_resource.loader().add_ref(asset_source_file.uuid);
+            (asset_source_file, load_handle)
+        })
+        .collect();
+
+    log::info!("Packing {} assets", requested_assets.len());
+
+    // Block until everything has loaded. Packing a partial set of assets would produce a build
+    // that fails at runtime, so any failure stops the pack. If nothing finishes loading for a
+    // while, the daemon is probably unreachable or stuck, so give up rather than waiting forever
+    let mut loaded_count = 0;
+    let mut deadline = Instant::now() + PACK_LOAD_TIMEOUT;
+    loop {
+        asset_resource.update();
+
+        let mut pending = vec![];
+        for (asset_source_file, load_handle) in &requested_assets {
+            if let Some(load_error) = asset_resource.storage().load_error(*load_handle) {
+                return Err(format!(
+                    "Failed to load {}: {}",
+                    asset_source_file.path.display(),
+                    load_error.error
+                )
+                .into());
+            }
+
+            match asset_resource.loader().get_load_status(*load_handle) {
+                LoadStatus::Loaded => {}
+                LoadStatus::DoesNotExist => {
+                    return Err(format!(
+                        "The asset daemon doesn't have {} ({})",
+                        asset_source_file.path.display(),
+                        asset_source_file.uuid
+                    )
+                    .into())
+                }
+                _ => pending.push(asset_source_file),
+            }
+        }
+
+        if pending.is_empty() {
+            break;
+        }
+
+        let now = Instant::now();
+        if requested_assets.len() - pending.len() > loaded_count {
+            loaded_count = requested_assets.len() - pending.len();
+            deadline = now + PACK_LOAD_TIMEOUT;
+        } else if now > deadline {
+            let pending_paths: Vec<_> = pending
+                .iter()
+                .map(|asset_source_file| asset_source_file.path.display().to_string())
+                .collect();
+
+            return Err(format!(
+                "Timed out after {} seconds with no progress. Still waiting for: {}",
+                PACK_LOAD_TIMEOUT.as_secs(),
+                pending_paths.join(", ")
+            )
+            .into());
+        }
+
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    let mut packfile = Packfile::new();
+    for (load_handle, artifact) in asset_resource.storage().take_recorded_artifacts() {
+        let id = asset_resource
+            .loader()
+            .asset_id(load_handle)
+            .ok_or_else(|| format!("No asset ID for {:?}", load_handle))?;
+
+        let load_deps = if artifact.asset_type == AssetTypeId(PrefabAsset::UUID) {
+            prefab_load_deps(&artifact.data)?
+        } else {
+            vec![]
+        };
+
+        packfile.assets.push(PackfileAsset {
+            id,
+            asset_type: artifact.asset_type.0,
+            load_deps,
+            data: artifact.data,
+        });
+    }
+
+    // Sort so that packing the same assets twice produces the same file
+    packfile.assets.sort_by_key(|asset| asset.id.0);
+
+    // Cook every prefab so that the app doesn't need to at runtime
+    let universe = Universe::new();
+    let component_registry = crate::create_component_registry();
+    let component_registry_by_uuid = crate::create_component_registry_by_uuid();
+    let prefab_ids: Vec<_> = packfile
+        .assets
+        .iter()
+        .filter(|asset| asset.asset_type == PrefabAsset::UUID)
+        .map(|asset| asset.id)
+        .collect();
+
+    for prefab_id in prefab_ids {
+        log::info!("Cooking prefab {}", prefab_id);
+        let cooked_prefab = crate::prefab_cooking::cook_prefab(
+            &universe,
+            &mut asset_resource,
+            &component_registry,
+            &component_registry_by_uuid,
+            &HashMap::new(),
+            prefab_id,
+        )?;
+
+        packfile.cooked_prefabs.push(PackfileCookedPrefab {
+            prefab_id,
+            data: bincode::serialize(&cooked_prefab)?,
+        });
+    }
+
+    Ok(packfile)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum PackfileLoadStatus {
+    // Just requested, dependencies haven't been requested yet
+    Requested,
+    WaitingForDependencies,
+    Loaded,
+    Failed,
+    DoesNotExist,
+}
+
+struct PackfileLoad {
+    asset_id: AssetUuid,
+    refs: u32,
+    status: PackfileLoadStatus,
+
+    // The loads of this asset's dependencies. This load holds a reference to each of them
+    dependencies: Vec<LoadHandle>,
+}
+
+#[derive(Default)]
+struct PackfileLoaderState {
+    next_load_handle: u64,
+    load_handles: HashMap<AssetUuid, LoadHandle>,
+    loads: HashMap<LoadHandle, PackfileLoad>,
+}
+
+impl PackfileLoaderState {
+    fn add_ref(
+        &mut self,
+        id: AssetUuid,
+    ) -> LoadHandle {
+        let load_handle = self.get_or_create_load(id);
+        self.loads.get_mut(&load_handle).unwrap().refs += 1;
+        load_handle
+    }
+
+    fn get_or_create_load(
+        &mut self,
+        id: AssetUuid,
+    ) -> LoadHandle {
+        if let Some(load_handle) = self.load_handles.get(&id) {
+            return *load_handle;
+        }
+
+        // Zero is never used so that it can't be confused with an uninitialized handle
+        self.next_load_handle += 1;
+        let load_handle = LoadHandle(self.next_load_handle);
+        self.load_handles.insert(id, load_handle);
+        self.loads.insert(
+            load_handle,
+            PackfileLoad {
+                asset_id: id,
+                refs: 0,
+                status: PackfileLoadStatus::Requested,
+                dependencies: vec![],
+            },
+        );
+
+        load_handle
+    }
+
+    fn remove_ref(
+        &mut self,
+        load_handle: LoadHandle,
+    ) {
+        if let Some(load) = self.loads.get_mut(&load_handle) {
+            load.refs = load.refs.saturating_sub(1);
+        }
+    }
+}
+
+/// Loads assets from a packfile instead of the asset daemon. Assets are loaded the same way the
+/// daemon's loader does it: an asset is loaded once something references it, after its load deps
+/// have loaded, and freed once nothing references it. Assets never change, so there is no hot
+/// reloading.
+pub struct PackfileLoader {
+    assets: HashMap<AssetUuid, PackfileAsset>,
+    cooked_prefabs: HashMap<AssetUuid, Vec<u8>>,
+    state: Mutex<PackfileLoaderState>,
+}
+
+impl PackfileLoader {
+    pub fn new(packfile: Packfile) -> Self {
+        PackfileLoader {
+            assets: packfile
+                .assets
+                .into_iter()
+                .map(|asset| (asset.id, asset))
+                .collect(),
+            cooked_prefabs: packfile
+                .cooked_prefabs
+                .into_iter()
+                .map(|cooked_prefab| (cooked_prefab.prefab_id, cooked_prefab.data))
+                .collect(),
+            state: Mutex::new(Default::default()),
+        }
+    }
+
+    pub fn open(path: &Path) -> std::io::Result<Self> {
+        let packfile = Packfile::read(path)?;
+        log::info!(
+            "Opened packfile {} with {} assets",
+            path.display(),
+            packfile.assets.len()
+        );
+        Ok(Self::new(packfile))
+    }
+
+    // Advances every load as far as it can go without calling into the storage. Returns the loads
+    // that are ready to be loaded and the loads that need to be freed
+    fn update_load_states(&self) -> (Vec<(LoadHandle, AssetUuid)>, Vec<(LoadHandle, AssetUuid)>) {
+        let mut state = self.state.lock().unwrap();
+        let state = &mut *state;
+
+        let mut ready_to_load = vec![];
+        let mut ready_to_free = vec![];
+
+        let load_handles: Vec<_> = state.loads.keys().cloned().collect();
+        for load_handle in load_handles {
+            let load = &state.loads[&load_handle];
+            let asset_id = load.asset_id;
+
+            if load.refs == 0 {
+                // Nothing references the asset anymore. Release the references this load held on
+                // its dependencies. Only loaded assets are in the storage
+                let load = state.loads.remove(&load_handle).unwrap();
+                state.load_handles.remove(&asset_id);
+                for dependency in load.dependencies {
+                    state.remove_ref(dependency);
+                }
+
+                if load.status == PackfileLoadStatus::Loaded {
+                    ready_to_free.push((load_handle, asset_id));
+                }
+                continue;
+            }
+
+            match load.status {
+                PackfileLoadStatus::Requested => {
+                    let new_status = match self.assets.get(&asset_id) {
+                        Some(asset) => {
+                            let dependencies: Vec<_> = asset
+                                .load_deps
+                                .iter()
+                                .map(|dependency_id| state.add_ref(*dependency_id))
+                                .collect();
+                            state.loads.get_mut(&load_handle).unwrap().dependencies = dependencies;
+                            PackfileLoadStatus::WaitingForDependencies
+                        }
+                        None => {
+                            log::warn!("Asset {} is not in the packfile", asset_id);
+                            PackfileLoadStatus::DoesNotExist
+                        }
+                    };
+
+                    state.loads.get_mut(&load_handle).unwrap().status = new_status;
+                }
+                PackfileLoadStatus::WaitingForDependencies => {
+                    // Assets that fail to load are still considered done, the same as the daemon's
+                    // loader does
+                    let dependencies_done = load.dependencies.iter().all(|dependency| {
+                        state
+                            .loads
+                            .get(dependency)
+                            .map(|dependency| {
+                                dependency.status != PackfileLoadStatus::Requested
+                                    && dependency.status
+                                        != PackfileLoadStatus::WaitingForDependencies
+                            })
+                            .unwrap_or(true)
+                    });
+
+                    if dependencies_done {
+                        ready_to_load.push((load_handle, asset_id));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        (ready_to_load, ready_to_free)
+    }
+}
+
+impl AssetLoader for PackfileLoader {
+    fn add_ref(
+        &self,
+        id: AssetUuid,
+    ) -> LoadHandle {
+        self.state.lock().unwrap().add_ref(id)
+    }
+
+    fn get_load_status(
+        &self,
+        load_handle: LoadHandle,
+    ) -> LoadStatus {
+        let state = self.state.lock().unwrap();
+        match state.loads.get(&load_handle).map(|load| load.status) {
+            None => LoadStatus::NotRequested,
+            Some(PackfileLoadStatus::Loaded) => LoadStatus::Loaded,
+            Some(PackfileLoadStatus::DoesNotExist) => LoadStatus::DoesNotExist,
+            // Failures are reported by the storage (see GenericAssetStorage::load_error)
+            Some(_) => LoadStatus::Loading,
+        }
+    }
+
+    fn get_active_loads(&self) -> Vec<LoadHandle> {
+        self.state.lock().unwrap().loads.keys().cloned().collect()
+    }
+
+    fn asset_id(
+        &self,
+        load_handle: LoadHandle,
+    ) -> Option<AssetUuid> {
+        let state = self.state.lock().unwrap();
+        state.loads.get(&load_handle).map(|load| load.asset_id)
+    }
+
+    fn ref_count(
+        &self,
+        load_handle: LoadHandle,
+    ) -> u32 {
+        let state = self.state.lock().unwrap();
+        state
+            .loads
+            .get(&load_handle)
+            .map(|load| load.refs)
+            .unwrap_or(0)
+    }
+
+    fn load_cooked_prefab(
+        &self,
+        prefab_id: AssetUuid,
+    ) -> Option<CookedPrefab> {
+        let data = self.cooked_prefabs.get(&prefab_id)?;
+        match bincode::deserialize(data) {
+            Ok(cooked_prefab) => Some(cooked_prefab),
+            Err(e) => {
+                log::error!("Failed to read cooked prefab {}: {}", prefab_id, e);
+                None
+            }
+        }
+    }
+
+    fn process_ref_ops(
+        &self,
+        ref_ops: &Receiver<RefOp>,
+    ) {
+        let mut state = self.state.lock().unwrap();
+        for ref_op in ref_ops.try_iter() {
+            match ref_op {
+                RefOp::Decrease(load_handle) => state.remove_ref(load_handle),
+                RefOp::Increase(load_handle) => {
+                    if let Some(load) = state.loads.get_mut(&load_handle) {
+                        load.refs += 1;
+                    }
+                }
+                RefOp::IncreaseUuid(id) => {
+                    state.add_ref(id);
+                }
+            }
+        }
+    }
+
+    fn process(
+        &mut self,
+        storage: &GenericAssetStorage,
+    ) -> Result<(), Box<dyn Error>> {
+        // Loading an asset can make its dependents ready, so keep going until nothing changes.
+        // The state isn't locked while calling the storage since it calls back into this loader
+        loop {
+            let (ready_to_load, ready_to_free) = self.update_load_states();
+
+            for (load_handle, asset_id) in &ready_to_free {
+                let asset = &self.assets[asset_id];
+                storage.free(&AssetTypeId(asset.asset_type), *load_handle);
+            }
+
+            for (load_handle, asset_id) in &ready_to_load {
+                // Packed assets never change, so every load is version 1
+                let asset = &self.assets[asset_id];
+                let asset_type = AssetTypeId(asset.asset_type);
+                let result = storage.load_asset(&*self, &asset_type, &asset.data, *load_handle, 1);
+
+                let status = match result {
+                    Ok(()) => {
+                        storage.commit_asset_version(&asset_type, *load_handle, 1);
+                        PackfileLoadStatus::Loaded
+                    }
+                    // The storage records and logs the error
+                    Err(_) => PackfileLoadStatus::Failed,
+                };
+
+                if let Some(load) = self.state.lock().unwrap().loads.get_mut(load_handle) {
+                    load.status = status;
+                }
+            }
+
+            if ready_to_load.is_empty() && ready_to_free.is_empty() {
+                return Ok(());
+            }
+        }
+    }
+}
+
+// Used by the storage to resolve handles inside assets while they're deserialized
+impl LoaderInfoProvider for PackfileLoader {
+    fn get_load_handle(
+        &self,
+        id: &AssetRef,
+    ) -> Option<LoadHandle> {
+        match id {
+            AssetRef::Uuid(uuid) => self.state.lock().unwrap().load_handles.get(uuid).cloned(),
+            _ => None,
+        }
+    }
+
+    fn get_asset_id(
+        &self,
+        load: LoadHandle,
+    ) -> Option<AssetUuid> {
+        self.asset_id(load)
+    }
+}
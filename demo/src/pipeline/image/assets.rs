@@ -1,10 +1,193 @@
-use image2::{color, ImageBuf};
+use image2::{color, Image, ImageBuf};
 use serde::{Deserialize, Serialize};
 use type_uuid::*;
 
+/// How color values in an image should be interpreted. Alpha is always linear.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum ImageColorSpace {
+    Srgb,
+    Linear,
+}
+
+/// The channel layout and bit depth of an image
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum ImageFormat {
+    Gray8,
+    Rgb8,
+    Rgba8,
+    Gray16,
+    Rgb16,
+    Rgba16,
+}
+
+impl ImageFormat {
+    pub fn channel_count(self) -> usize {
+        match self {
+            ImageFormat::Gray8 | ImageFormat::Gray16 => 1,
+            ImageFormat::Rgb8 | ImageFormat::Rgb16 => 3,
+            ImageFormat::Rgba8 | ImageFormat::Rgba16 => 4,
+        }
+    }
+
+    pub fn has_alpha(self) -> bool {
+        self.channel_count() == 4
+    }
+}
+
+/// Pixel data for a single image (or a single mip level of one)
+#[derive(Serialize, Deserialize, Debug)]
+pub enum ImageData {
+    Gray8(ImageBuf<u8, color::Gray>),
+    Rgb8(ImageBuf<u8, color::Rgb>),
+    Rgba8(ImageBuf<u8, color::Rgba>),
+    Gray16(ImageBuf<u16, color::Gray>),
+    Rgb16(ImageBuf<u16, color::Rgb>),
+    Rgba16(ImageBuf<u16, color::Rgba>),
+}
+
+impl ImageData {
+    pub fn format(&self) -> ImageFormat {
+        match self {
+            ImageData::Gray8(_) => ImageFormat::Gray8,
+            ImageData::Rgb8(_) => ImageFormat::Rgb8,
+            ImageData::Rgba8(_) => ImageFormat::Rgba8,
+            ImageData::Gray16(_) => ImageFormat::Gray16,
+            ImageData::Rgb16(_) => ImageFormat::Rgb16,
+            ImageData::Rgba16(_) => ImageFormat::Rgba16,
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        (match self {
+            ImageData::Gray8(image) => image.width(),
+            ImageData::Rgb8(image) => image.width(),
+            ImageData::Rgba8(image) => image.width(),
+            ImageData::Gray16(image) => image.width(),
+            ImageData::Rgb16(image) => image.width(),
+            ImageData::Rgba16(image) => image.width(),
+        }) as u32
+    }
+
+    pub fn height(&self) -> u32 {
+        (match self {
+            ImageData::Gray8(image) => image.height(),
+            ImageData::Rgb8(image) => image.height(),
+            ImageData::Rgba8(image) => image.height(),
+            ImageData::Gray16(image) => image.height(),
+            ImageData::Rgb16(image) => image.height(),
+            ImageData::Rgba16(image) => image.height(),
+        }) as u32
+    }
+
+    /// Returns every channel of every pixel scaled to 0..1, one row after another starting at the
+    /// top
+    pub fn to_f32(&self) -> Vec<f32> {
+        fn from_u8(data: &[u8]) -> Vec<f32> {
+            data.iter().map(|x| *x as f32 / 255.0).collect()
+        }
+
+        fn from_u16(data: &[u16]) -> Vec<f32> {
+            data.iter().map(|x| *x as f32 / 65535.0).collect()
+        }
+
+        match self {
+            ImageData::Gray8(image) => from_u8(image.data()),
+            ImageData::Rgb8(image) => from_u8(image.data()),
+            ImageData::Rgba8(image) => from_u8(image.data()),
+            ImageData::Gray16(image) => from_u16(image.data()),
+            ImageData::Rgb16(image) => from_u16(image.data()),
+            ImageData::Rgba16(image) => from_u16(image.data()),
+        }
+    }
+
+    /// Creates image data in the given format from values in 0..1 (see to_f32)
+    pub fn from_f32(
+        format: ImageFormat,
+        width: u32,
+        height: u32,
+        data: &[f32],
+    ) -> Self {
+        fn to_u8(data: &[f32]) -> Vec<u8> {
+            data.iter()
+                .map(|x| (x.max(0.0).min(1.0) * 255.0).round() as u8)
+                .collect()
+        }
+
+        fn to_u16(data: &[f32]) -> Vec<u16> {
+            data.iter()
+                .map(|x| (x.max(0.0).min(1.0) * 65535.0).round() as u16)
+                .collect()
+        }
+
+        let width = width as usize;
+        let height = height as usize;
+        match format {
+            ImageFormat::Gray8 => ImageData::Gray8(ImageBuf::new_from(width, height, to_u8(data))),
+            ImageFormat::Rgb8 => ImageData::Rgb8(ImageBuf::new_from(width, height, to_u8(data))),
+            ImageFormat::Rgba8 => ImageData::Rgba8(ImageBuf::new_from(width, height, to_u8(data))),
+            ImageFormat::Gray16 => {
+                ImageData::Gray16(ImageBuf::new_from(width, height, to_u16(data)))
+            }
+            ImageFormat::Rgb16 => ImageData::Rgb16(ImageBuf::new_from(width, height, to_u16(data))),
+            ImageFormat::Rgba16 => {
+                ImageData::Rgba16(ImageBuf::new_from(width, height, to_u16(data)))
+            }
+        }
+    }
+
+    /// Returns the pixels as 8-bit RGBA, one row after another starting at the top. This is the
+    /// layout skia expects when creating an image from raster data. Grayscale is copied into each
+    /// color channel, and images without alpha are opaque.
+    pub fn to_rgba8(&self) -> Vec<u8> {
+        let channel_count = self.format().channel_count();
+        self.to_f32()
+            .chunks(channel_count)
+            .flat_map(|pixel| {
+                let rgba = match channel_count {
+                    1 => [pixel[0], pixel[0], pixel[0], 1.0],
+                    3 => [pixel[0], pixel[1], pixel[2], 1.0],
+                    _ => [pixel[0], pixel[1], pixel[2], pixel[3]],
+                };
+
+                let mut bytes = [0; 4];
+                for (byte, value) in bytes.iter_mut().zip(rgba.iter()) {
+                    *byte = (value.max(0.0).min(1.0) * 255.0).round() as u8;
+                }
+
+                bytes.to_vec()
+            })
+            .collect()
+    }
+}
+
 #[derive(TypeUuid, Serialize, Deserialize, Debug)]
 #[uuid = "d4079e74-3ec9-4ebc-9b77-a87cafdfdada"]
-pub enum ImageAsset {
-    Rgb8(ImageBuf<u8, color::Rgb>),
-    // ...
+pub struct ImageAsset {
+    pub color_space: ImageColorSpace,
+
+    /// If true, color values have already been multiplied by alpha
+    pub premultiplied_alpha: bool,
+
+    /// The first level is the full-size image. If mipmaps were generated, each following level is
+    /// half the size of the one before it, down to 1x1
+    pub mip_levels: Vec<ImageData>,
+}
+
+impl ImageAsset {
+    pub fn format(&self) -> ImageFormat {
+        self.mip_levels[0].format()
+    }
+
+    pub fn width(&self) -> u32 {
+        self.mip_levels[0].width()
+    }
+
+    pub fn height(&self) -> u32 {
+        self.mip_levels[0].height()
+    }
+
+    /// See ImageData::to_rgba8. This returns the full-size image
+    pub fn to_rgba8(&self) -> Vec<u8> {
+        self.mip_levels[0].to_rgba8()
+    }
 }
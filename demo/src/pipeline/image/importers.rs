@@ -6,11 +6,60 @@ use serde::{Deserialize, Serialize};
 use type_uuid::*;
 
 use crate::pipeline::image::*;
+use crate::pipeline::image::processing::{self, FloatImage};
 
 #[derive(TypeUuid, Serialize, Deserialize, Default)]
 #[uuid = "3c8367c8-45fb-40bb-a229-00e5e9c3fc70"]
 struct SimpleState(Option<AssetUuid>);
 
+/// Settings for how an image is imported. These are stored in the image's .meta file, so they can
+/// be changed per image. Any setting that's missing from the file gets its default value
+#[derive(TypeUuid, Serialize, Deserialize, Clone, Debug)]
+#[uuid = "caaddf67-f270-4c7e-9c3d-c0f4c2eb3e48"]
+#[serde(default)]
+pub struct ImageImporterOptions {
+    /// If true, color values are multiplied by alpha. Images without alpha are unaffected
+    pub premultiply_alpha: bool,
+
+    /// Whether the source file's colors are sRGB-encoded or linear. Color textures are usually
+    /// sRGB, data textures (like normal maps) are usually linear
+    pub color_space: ImageColorSpace,
+
+    /// Images that are larger than this on either side are shrunk to fit, keeping the aspect
+    /// ratio. 0 means there is no limit
+    pub max_dimension: u32,
+
+    /// If true, mip levels down to 1x1 are generated and stored with the image
+    pub generate_mipmaps: bool,
+}
+
+impl Default for ImageImporterOptions {
+    fn default() -> Self {
+        ImageImporterOptions {
+            premultiply_alpha: false,
+            color_space: ImageColorSpace::Srgb,
+            max_dimension: 0,
+            generate_mipmaps: false,
+        }
+    }
+}
+
+// Decodes the image into the given format. image2 converts from whatever is in the file
+fn decode(
+    bytes: &[u8],
+    format: ImageFormat,
+) -> Result<ImageData> {
+    let to_error = |e| Error::Boxed(Box::new(e));
+    Ok(match format {
+        ImageFormat::Gray8 => ImageData::Gray8(image2::io::decode(bytes).map_err(to_error)?),
+        ImageFormat::Rgb8 => ImageData::Rgb8(image2::io::decode(bytes).map_err(to_error)?),
+        ImageFormat::Rgba8 => ImageData::Rgba8(image2::io::decode(bytes).map_err(to_error)?),
+        ImageFormat::Gray16 => ImageData::Gray16(image2::io::decode(bytes).map_err(to_error)?),
+        ImageFormat::Rgb16 => ImageData::Rgb16(image2::io::decode(bytes).map_err(to_error)?),
+        ImageFormat::Rgba16 => ImageData::Rgba16(image2::io::decode(bytes).map_err(to_error)?),
+    })
+}
+
 #[derive(TypeUuid)]
 #[uuid = "720d636b-b79c-42d4-8f46-a2d8e1ada46e"]
 struct ImageImporter;
@@ -19,13 +68,13 @@ impl Importer for ImageImporter {
     where
         Self: Sized,
     {
-        1
+        2
     }
     fn version(&self) -> u32 {
         Self::version_static()
     }
 
-    type Options = ();
+    type Options = ImageImporterOptions;
 
     type State = SimpleState;
 
@@ -33,7 +82,7 @@ impl Importer for ImageImporter {
     fn import(
         &self,
         source: &mut dyn Read,
-        _options: Self::Options,
+        options: Self::Options,
         state: &mut Self::State,
     ) -> Result<ImporterValue> {
         let id = state
@@ -42,8 +91,49 @@ impl Importer for ImageImporter {
         *state = SimpleState(Some(id));
         let mut bytes = Vec::new();
         source.read_to_end(&mut bytes)?;
-        let asset =
-            ImageAsset::Rgb8(image2::io::decode(&bytes).map_err(|e| Error::Boxed(Box::new(e)))?);
+
+        // Keep the channels and bit depth of the source file
+        let format = processing::detect_source_format(&bytes);
+        let image_data = decode(&bytes, format)?;
+
+        let premultiplied_alpha = options.premultiply_alpha && format.has_alpha();
+        let needs_processing =
+            premultiplied_alpha || options.max_dimension > 0 || options.generate_mipmaps;
+
+        let mip_levels = if needs_processing {
+            // Filtering and premultiplying are done on linear values
+            let mut image = FloatImage::from_image_data(&image_data);
+            image.decode_color_space(options.color_space);
+
+            if premultiplied_alpha {
+                image.premultiply_alpha();
+            }
+
+            let image = image.fit_to_max_dimension(options.max_dimension);
+
+            let float_mip_levels = if options.generate_mipmaps {
+                image.generate_mip_chain()
+            } else {
+                vec![image]
+            };
+
+            float_mip_levels
+                .into_iter()
+                .map(|mut mip_level| {
+                    mip_level.encode_color_space(options.color_space);
+                    mip_level.to_image_data(format)
+                })
+                .collect()
+        } else {
+            vec![image_data]
+        };
+
+        let asset = ImageAsset {
+            color_space: options.color_space,
+            premultiplied_alpha,
+            mip_levels,
+        };
+
         Ok(ImporterValue {
             assets: vec![ImportedAsset {
                 id,
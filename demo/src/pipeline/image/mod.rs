@@ -1,4 +1,8 @@
 mod assets;
 mod importers;
+mod processing;
 
 pub use assets::ImageAsset;
+pub use assets::ImageColorSpace;
+pub use assets::ImageData;
+pub use assets::ImageFormat;
@@ -0,0 +1,279 @@
+use crate::pipeline::image::{ImageColorSpace, ImageData, ImageFormat};
+
+/// Picks the format that can hold the source image without losing channels or precision. This
+/// only reads the file's header. PNG, JPEG and TGA are recognized, anything else is assumed to be
+/// 8-bit RGB.
+pub fn detect_source_format(bytes: &[u8]) -> ImageFormat {
+    const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+
+    if bytes.starts_with(&PNG_SIGNATURE) {
+        detect_png_format(bytes)
+    } else if bytes.starts_with(&[0xFF, 0xD8]) {
+        detect_jpeg_format(bytes)
+    } else {
+        detect_tga_format(bytes)
+    }
+}
+
+fn detect_png_format(bytes: &[u8]) -> ImageFormat {
+    // The IHDR chunk always comes first. Bit depth and color type follow the width and height
+    if bytes.len() < 26 {
+        return ImageFormat::Rgb8;
+    }
+
+    let is_16_bit = bytes[24] == 16;
+    let color_type = bytes[25];
+
+    // A tRNS chunk adds transparency to images that don't otherwise have an alpha channel. It must
+    // come before the image data. The compressed data isn't searched since it could match by chance
+    let image_data_start = bytes
+        .windows(4)
+        .position(|chunk_type| chunk_type == b"IDAT")
+        .unwrap_or(bytes.len());
+    let has_transparency_chunk = bytes[..image_data_start]
+        .windows(4)
+        .any(|chunk_type| chunk_type == b"tRNS");
+
+    let (channel_count, is_16_bit) = match color_type {
+        0 if has_transparency_chunk => (4, is_16_bit),
+        0 => (1, is_16_bit),
+        // Gray + alpha is expanded to RGBA
+        4 => (4, is_16_bit),
+        // Palettes are always 8-bit
+        3 if has_transparency_chunk => (4, false),
+        3 => (3, false),
+        2 if has_transparency_chunk => (4, is_16_bit),
+        6 => (4, is_16_bit),
+        _ => (3, is_16_bit),
+    };
+
+    match (channel_count, is_16_bit) {
+        (1, false) => ImageFormat::Gray8,
+        (1, true) => ImageFormat::Gray16,
+        (3, false) => ImageFormat::Rgb8,
+        (3, true) => ImageFormat::Rgb16,
+        (_, false) => ImageFormat::Rgba8,
+        (_, true) => ImageFormat::Rgba16,
+    }
+}
+
+fn detect_jpeg_format(bytes: &[u8]) -> ImageFormat {
+    // Walk the segments until a start-of-frame marker, which holds the number of components
+    let mut position = 2;
+    while position + 9 < bytes.len() {
+        if bytes[position] != 0xFF {
+            break;
+        }
+
+        let marker = bytes[position + 1];
+        let is_start_of_frame =
+            (0xC0..=0xCF).contains(&marker) && marker != 0xC4 && marker != 0xC8 && marker != 0xCC;
+
+        if is_start_of_frame {
+            let component_count = bytes[position + 9];
+            return if component_count == 1 {
+                ImageFormat::Gray8
+            } else {
+                ImageFormat::Rgb8
+            };
+        }
+
+        let segment_length = ((bytes[position + 2] as usize) << 8) | bytes[position + 3] as usize;
+        position += 2 + segment_length;
+    }
+
+    ImageFormat::Rgb8
+}
+
+fn detect_tga_format(bytes: &[u8]) -> ImageFormat {
+    if bytes.len() < 18 {
+        return ImageFormat::Rgb8;
+    }
+
+    let image_type = bytes[2];
+    let alpha_bits = bytes[17] & 0x0F;
+
+    // Types 3 and 11 are (uncompressed and RLE) grayscale
+    if image_type == 3 || image_type == 11 {
+        ImageFormat::Gray8
+    } else if alpha_bits > 0 || bytes[16] == 32 {
+        ImageFormat::Rgba8
+    } else {
+        ImageFormat::Rgb8
+    }
+}
+
+/// An image with every channel stored as an f32. Processing is done in this form so that it works
+/// the same for every format
+pub struct FloatImage {
+    pub width: u32,
+    pub height: u32,
+    pub channel_count: usize,
+    pub data: Vec<f32>,
+}
+
+impl FloatImage {
+    pub fn from_image_data(image_data: &ImageData) -> Self {
+        FloatImage {
+            width: image_data.width(),
+            height: image_data.height(),
+            channel_count: image_data.format().channel_count(),
+            data: image_data.to_f32(),
+        }
+    }
+
+    pub fn to_image_data(
+        &self,
+        format: ImageFormat,
+    ) -> ImageData {
+        ImageData::from_f32(format, self.width, self.height, &self.data)
+    }
+
+    // Alpha is never gamma-encoded, so only the color channels are converted
+    fn color_channels_mut(&mut self) -> impl Iterator<Item = &mut f32> {
+        let channel_count = self.channel_count;
+        let color_channel_count = if channel_count == 4 { 3 } else { channel_count };
+        self.data
+            .iter_mut()
+            .enumerate()
+            .filter(move |(index, _)| index % channel_count < color_channel_count)
+            .map(|(_, value)| value)
+    }
+
+    /// Filtering and premultiplying must be done on linear values. This converts sRGB-encoded
+    /// colors to linear (it does nothing to images that are already linear)
+    pub fn decode_color_space(
+        &mut self,
+        color_space: ImageColorSpace,
+    ) {
+        if color_space == ImageColorSpace::Srgb {
+            for value in self.color_channels_mut() {
+                *value = srgb_to_linear(*value);
+            }
+        }
+    }
+
+    /// Undoes decode_color_space
+    pub fn encode_color_space(
+        &mut self,
+        color_space: ImageColorSpace,
+    ) {
+        if color_space == ImageColorSpace::Srgb {
+            for value in self.color_channels_mut() {
+                *value = linear_to_srgb(*value);
+            }
+        }
+    }
+
+    /// Multiplies the color channels by alpha. Does nothing if there is no alpha channel
+    pub fn premultiply_alpha(&mut self) {
+        if self.channel_count != 4 {
+            return;
+        }
+
+        for pixel in self.data.chunks_mut(4) {
+            let alpha = pixel[3];
+            pixel[0] *= alpha;
+            pixel[1] *= alpha;
+            pixel[2] *= alpha;
+        }
+    }
+
+    /// Resizes the image with a box filter. Each destination pixel is the average of the source
+    /// pixels it covers. This is intended for shrinking, enlarging will look blocky
+    pub fn resize(
+        &self,
+        width: u32,
+        height: u32,
+    ) -> FloatImage {
+        let channel_count = self.channel_count;
+        let scale_x = self.width as f32 / width as f32;
+        let scale_y = self.height as f32 / height as f32;
+
+        let mut data = Vec::with_capacity(width as usize * height as usize * channel_count);
+        for y in 0..height {
+            let src_y_begin = (y as f32 * scale_y) as u32;
+            let src_y_end = (((y + 1) as f32 * scale_y).ceil() as u32)
+                .min(self.height)
+                .max(src_y_begin + 1);
+
+            for x in 0..width {
+                let src_x_begin = (x as f32 * scale_x) as u32;
+                let src_x_end = (((x + 1) as f32 * scale_x).ceil() as u32)
+                    .min(self.width)
+                    .max(src_x_begin + 1);
+
+                let mut sum = [0.0; 4];
+                for src_y in src_y_begin..src_y_end {
+                    for src_x in src_x_begin..src_x_end {
+                        let index = (src_y * self.width + src_x) as usize * channel_count;
+                        for channel in 0..channel_count {
+                            sum[channel] += self.data[index + channel];
+                        }
+                    }
+                }
+
+                let sample_count = ((src_y_end - src_y_begin) * (src_x_end - src_x_begin)) as f32;
+                for channel in 0..channel_count {
+                    data.push(sum[channel] / sample_count);
+                }
+            }
+        }
+
+        FloatImage {
+            width,
+            height,
+            channel_count,
+            data,
+        }
+    }
+
+    /// Shrinks the image (keeping the aspect ratio) so that neither side is larger than
+    /// max_dimension. Images that already fit are returned unchanged
+    pub fn fit_to_max_dimension(
+        self,
+        max_dimension: u32,
+    ) -> FloatImage {
+        let largest_dimension = self.width.max(self.height);
+        if max_dimension == 0 || largest_dimension <= max_dimension {
+            return self;
+        }
+
+        let scale = max_dimension as f32 / largest_dimension as f32;
+        let width = ((self.width as f32 * scale).round() as u32).max(1);
+        let height = ((self.height as f32 * scale).round() as u32).max(1);
+        self.resize(width, height)
+    }
+
+    /// Returns this image followed by successively half-sized copies, ending with a 1x1 image
+    pub fn generate_mip_chain(self) -> Vec<FloatImage> {
+        let mut mip_levels = vec![self];
+        loop {
+            let previous = mip_levels.last().unwrap();
+            if previous.width == 1 && previous.height == 1 {
+                break;
+            }
+
+            let next = previous.resize((previous.width / 2).max(1), (previous.height / 2).max(1));
+            mip_levels.push(next);
+        }
+
+        mip_levels
+    }
+}
+
+fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
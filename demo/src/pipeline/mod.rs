@@ -1,5 +1,11 @@
 mod image;
 pub use image::ImageAsset;
+pub use image::ImageColorSpace;
 
 mod prefab;
 pub use prefab::PrefabAsset;
+
+mod tiled;
+
+mod vector_shape;
+pub use vector_shape::*;
@@ -1,10 +1,11 @@
 use atelier_importer::{ImportedAsset, Importer, ImporterValue, SourceFileImporter};
-use atelier_core::AssetUuid;
+use atelier_core::{AssetRef, AssetUuid};
 use serde::{Deserialize, Serialize};
 use std::io::Read;
 use type_uuid::TypeUuid;
 
 use crate::pipeline::PrefabAsset;
+use crate::handle_def;
 
 use legion::prelude::*;
 use legion_prefab::ComponentRegistration;
@@ -32,7 +33,7 @@ impl Importer for PrefabImporter {
     type Options = PrefabImporterOptions;
 
     fn version_static() -> u32 {
-        1
+        2
     }
 
     fn version(&self) -> u32 {
@@ -107,6 +108,18 @@ impl Importer for PrefabImporter {
             );
         }
 
+        ///////////////////////////////////////////////////////////////
+        // STEP 4: Find the assets that components reference
+        ///////////////////////////////////////////////////////////////
+
+        // Any asset referenced by a HandleDef is a load dependency, so it will be loaded before
+        // the prefab is. They're found by serializing the prefab and recording each HandleDef
+        let (serialized, referenced_assets) =
+            handle_def::collect_asset_references(|| bincode::serialize(&prefab_asset));
+        serialized.map_err(|e| atelier_importer::Error::Boxed(Box::new(e)))?;
+
+        let load_deps = referenced_assets.into_iter().map(AssetRef::Uuid).collect();
+
         // Add the ID to the .meta
         let prefab_id = prefab_asset.prefab.prefab_id();
         state.id = Some(AssetUuid(prefab_id));
@@ -116,7 +129,7 @@ impl Importer for PrefabImporter {
                 id: state.id.expect("AssetUuid not generated"),
                 search_tags: Vec::new(),
                 build_deps: Vec::new(),
-                load_deps: Vec::new(),
+                load_deps,
                 asset_data: Box::new(prefab_asset),
                 build_pipeline: None,
             }],
@@ -0,0 +1,402 @@
+use std::collections::HashMap;
+use std::io::Read;
+
+use atelier_core::AssetUuid;
+use atelier_importer::{Error, ImportedAsset, Importer, ImporterValue, SourceFileImporter};
+use legion::prelude::*;
+use prefab_format::EntityUuid;
+use serde::{Deserialize, Serialize};
+use type_uuid::TypeUuid;
+
+use crate::components::{
+    DrawSkiaBoxComponentDef, DrawSkiaCircleComponentDef, PaintDef, Position2DComponent,
+    RigidBodyBallComponentDef, RigidBodyBoxComponentDef, Rotation2DComponent,
+};
+use crate::pipeline::tiled::map::{
+    TiledLayer, TiledLayerKind, TiledMap, TiledMapError, TiledObject, TiledObjectShape,
+};
+use crate::pipeline::PrefabAsset;
+
+/// Settings for how a map is imported. These are stored in the map's .meta file
+#[derive(TypeUuid, Serialize, Deserialize, Clone, Debug)]
+#[uuid = "2a8f7e64-4a1c-4f61-9bd6-5f0c3e2c1b7a"]
+#[serde(default)]
+pub struct TiledImporterOptions {
+    /// How many of the map's pixels make up one unit in the world
+    pub pixels_per_unit: f32,
+
+    /// The stroke width of the shapes drawn for objects and tiles, in world units
+    pub stroke_width: f32,
+}
+
+impl Default for TiledImporterOptions {
+    fn default() -> Self {
+        TiledImporterOptions {
+            pixels_per_unit: 64.0,
+            stroke_width: 0.02,
+        }
+    }
+}
+
+/// Keeps the prefab's UUID the same between imports
+#[derive(Default, Deserialize, Serialize, TypeUuid)]
+#[uuid = "b3f0d5a1-7c2e-4d8b-a6f4-91e2c0d7f35b"]
+pub struct TiledImporterState {
+    pub id: Option<AssetUuid>,
+}
+
+/// Imports a map made with the Tiled editor as a prefab. The map's origin (its top left corner) is
+/// placed at the world origin.
+///
+/// Object layers: each rectangle, ellipse and tile object becomes an entity with a position,
+/// rotation and a box or circle draw component. Point objects only get a position. Setting a
+/// "body" property on the object (or its layer) to "static" or "dynamic" adds a matching rigid
+/// body. A "color" property overrides the layer's color.
+///
+/// Tile layers: each horizontal run of tiles becomes a static box collider. Set a "collision"
+/// property on the layer to false to only draw the tiles.
+///
+/// Entity UUIDs are derived from the prefab's UUID and the object's ID in Tiled (or the tile's
+/// position), so prefabs that reference the map keep their overrides when the map is reimported.
+#[derive(Default, TypeUuid)]
+#[uuid = "e7c4a9d2-3b5f-4e1a-8c6d-0f2b7a9e4c13"]
+pub struct TiledImporter {}
+
+impl Importer for TiledImporter {
+    type State = TiledImporterState;
+    type Options = TiledImporterOptions;
+
+    fn version_static() -> u32 {
+        1
+    }
+
+    fn version(&self) -> u32 {
+        Self::version_static()
+    }
+
+    fn import(
+        &self,
+        source: &mut dyn Read,
+        options: Self::Options,
+        state: &mut Self::State,
+    ) -> atelier_importer::Result<ImporterValue> {
+        let mut text = String::new();
+        source.read_to_string(&mut text)?;
+
+        let map = TiledMap::parse(&text).map_err(|e| Error::Boxed(Box::new(e)))?;
+
+        let prefab_id = state
+            .id
+            .unwrap_or_else(|| AssetUuid(*uuid::Uuid::new_v4().as_bytes()));
+        state.id = Some(prefab_id);
+
+        let prefab =
+            build_prefab(&map, &options, prefab_id).map_err(|e| Error::Boxed(Box::new(e)))?;
+        let prefab_asset = PrefabAsset { prefab };
+
+        Ok(ImporterValue {
+            assets: vec![ImportedAsset {
+                id: prefab_id,
+                search_tags: vec![],
+                build_deps: vec![],
+                load_deps: vec![],
+                build_pipeline: None,
+                asset_data: Box::new(prefab_asset),
+            }],
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum BodyType {
+    None,
+    Static,
+    Dynamic,
+}
+
+// Reads the "body" property from the object, falling back to the layer
+fn body_type(
+    layer: &TiledLayer,
+    object: &TiledObject,
+) -> Result<BodyType, TiledMapError> {
+    let body = object
+        .properties
+        .get("body")
+        .or_else(|| layer.properties.get("body"));
+
+    match body {
+        None | Some("none") => Ok(BodyType::None),
+        Some("static") => Ok(BodyType::Static),
+        Some("dynamic") => Ok(BodyType::Dynamic),
+        Some(body) => Err(TiledMapError(format!(
+            "Object {} has an invalid body {}. It must be none, static or dynamic",
+            object.id, body
+        ))),
+    }
+}
+
+struct MapPrefabBuilder<'a> {
+    options: &'a TiledImporterOptions,
+    prefab_id: AssetUuid,
+    world: World,
+    entities: HashMap<EntityUuid, Entity>,
+}
+
+impl<'a> MapPrefabBuilder<'a> {
+    // Entity UUIDs are name-based UUIDs in the prefab's namespace, so the same object always gets
+    // the same UUID
+    fn entity_uuid(
+        &self,
+        name: &str,
+    ) -> EntityUuid {
+        let namespace = uuid::Uuid::from_bytes(self.prefab_id.0);
+        *uuid::Uuid::new_v5(&namespace, name.as_bytes()).as_bytes()
+    }
+
+    // Tiled's y axis points down and the world's points up
+    fn to_world(
+        &self,
+        x: f32,
+        y: f32,
+    ) -> glam::Vec2 {
+        glam::Vec2::new(x, -y) / self.options.pixels_per_unit
+    }
+
+    fn paint(
+        &self,
+        color: [f32; 4],
+    ) -> PaintDef {
+        PaintDef {
+            color: glam::Vec4::new(color[0], color[1], color[2], color[3]).into(),
+            stroke_width: self.options.stroke_width,
+            ..Default::default()
+        }
+    }
+
+    fn add_object(
+        &mut self,
+        layer: &TiledLayer,
+        object: &TiledObject,
+    ) -> Result<(), TiledMapError> {
+        let body_type = body_type(layer, object)?;
+
+        // Find the center of the object in pixels. Tiled positions objects by a corner and rotates
+        // them around it
+        let (corner_to_center_x, corner_to_center_y) = match object.shape {
+            TiledObjectShape::Tile(_) => (object.width / 2.0, -object.height / 2.0),
+            TiledObjectShape::Point => (0.0, 0.0),
+            _ => (object.width / 2.0, object.height / 2.0),
+        };
+
+        let radians = object.rotation.to_radians();
+        let center_x =
+            object.x + corner_to_center_x * radians.cos() - corner_to_center_y * radians.sin();
+        let center_y =
+            object.y + corner_to_center_x * radians.sin() + corner_to_center_y * radians.cos();
+
+        let position = Position2DComponent {
+            position: self.to_world(center_x, center_y).into(),
+        };
+
+        // Clockwise with y down is counter-clockwise with y up
+        let rotation = Rotation2DComponent { rotation: -radians };
+
+        let color = object
+            .properties
+            .get_color("color")
+            .or(layer.color)
+            .unwrap_or([1.0, 1.0, 1.0, 1.0]);
+        let paint = self.paint(color);
+
+        let half_extents =
+            glam::Vec2::new(object.width, object.height) / (2.0 * self.options.pixels_per_unit);
+        let is_static = body_type == BodyType::Static;
+
+        let entity = match (object.shape, body_type) {
+            (TiledObjectShape::Rectangle, BodyType::None)
+            | (TiledObjectShape::Tile(_), BodyType::None) => {
+                let draw = DrawSkiaBoxComponentDef {
+                    half_extents: half_extents.into(),
+                    paint,
+                };
+                self.world.insert((), vec![(position, rotation, draw)])[0]
+            }
+            (TiledObjectShape::Rectangle, _) | (TiledObjectShape::Tile(_), _) => {
+                let draw = DrawSkiaBoxComponentDef {
+                    half_extents: half_extents.into(),
+                    paint,
+                };
+                let body = RigidBodyBoxComponentDef {
+                    half_extents: half_extents.into(),
+                    is_static,
+                };
+                self.world
+                    .insert((), vec![(position, rotation, draw, body)])[0]
+            }
+            (TiledObjectShape::Ellipse, body_type) => {
+                if (object.width - object.height).abs() > 0.01 {
+                    log::warn!(
+                        "Ellipse {} in layer {} will be imported as a circle",
+                        object.id,
+                        layer.name
+                    );
+                }
+
+                let radius = (half_extents.x() + half_extents.y()) / 2.0;
+                let draw = DrawSkiaCircleComponentDef { radius, paint };
+                if body_type == BodyType::None {
+                    self.world.insert((), vec![(position, rotation, draw)])[0]
+                } else {
+                    let body = RigidBodyBallComponentDef { radius, is_static };
+                    self.world
+                        .insert((), vec![(position, rotation, draw, body)])[0]
+                }
+            }
+            (TiledObjectShape::Point, body_type) => {
+                if body_type != BodyType::None {
+                    log::warn!(
+                        "Point {} in layer {} can't have a body, it will be ignored",
+                        object.id,
+                        layer.name
+                    );
+                }
+
+                self.world.insert((), vec![(position, rotation)])[0]
+            }
+            (TiledObjectShape::Unsupported, _) => {
+                log::warn!(
+                    "Object {} in layer {} has a shape that isn't supported, it will be skipped",
+                    object.id,
+                    layer.name
+                );
+                return Ok(());
+            }
+        };
+
+        // Object IDs are unique within the map
+        let entity_uuid = self.entity_uuid(&format!("object/{}", object.id));
+        self.entities.insert(entity_uuid, entity);
+        Ok(())
+    }
+
+    fn add_tiles(
+        &mut self,
+        layer: &TiledLayer,
+        map: &TiledMap,
+        width: u32,
+        tiles: &[u32],
+    ) {
+        if width == 0 {
+            return;
+        }
+
+        let has_collision = layer.properties.get_bool("collision").unwrap_or(true);
+        let color = layer
+            .properties
+            .get_color("color")
+            .unwrap_or([1.0, 1.0, 1.0, 1.0]);
+
+        // Merge each horizontal run of tiles into a single box, which keeps the number of
+        // colliders down
+        for (row, row_tiles) in tiles.chunks(width as usize).enumerate() {
+            let mut column = 0;
+            while column < row_tiles.len() {
+                if row_tiles[column] == 0 {
+                    column += 1;
+                    continue;
+                }
+
+                let run_start = column;
+                while column < row_tiles.len() && row_tiles[column] != 0 {
+                    column += 1;
+                }
+
+                let run_width = ((column - run_start) as u32 * map.tile_width) as f32;
+                let run_height = map.tile_height as f32;
+                let center_x = (run_start as u32 * map.tile_width) as f32 + run_width / 2.0;
+                let center_y = (row as u32 * map.tile_height) as f32 + run_height / 2.0;
+
+                let position = Position2DComponent {
+                    position: self.to_world(center_x, center_y).into(),
+                };
+                let half_extents =
+                    glam::Vec2::new(run_width, run_height) / (2.0 * self.options.pixels_per_unit);
+                let draw = DrawSkiaBoxComponentDef {
+                    half_extents: half_extents.into(),
+                    paint: self.paint(color),
+                };
+
+                let entity = if has_collision {
+                    let body = RigidBodyBoxComponentDef {
+                        half_extents: half_extents.into(),
+                        is_static: true,
+                    };
+                    self.world.insert((), vec![(position, draw, body)])[0]
+                } else {
+                    self.world.insert((), vec![(position, draw)])[0]
+                };
+
+                // A run is identified by where it starts. If tiles are added to the end of a run,
+                // it keeps its UUID
+                let entity_uuid =
+                    self.entity_uuid(&format!("tiles/{}/{}/{}", layer.id, row, run_start));
+                self.entities.insert(entity_uuid, entity);
+            }
+        }
+    }
+}
+
+fn build_prefab(
+    map: &TiledMap,
+    options: &TiledImporterOptions,
+    prefab_id: AssetUuid,
+) -> Result<legion_prefab::Prefab, TiledMapError> {
+    if options.pixels_per_unit <= 0.0 {
+        return Err(TiledMapError(
+            "pixels_per_unit must be greater than 0".to_string(),
+        ));
+    }
+
+    let universe = Universe::new();
+    let mut builder = MapPrefabBuilder {
+        options,
+        prefab_id,
+        world: universe.create_world(),
+        entities: HashMap::new(),
+    };
+
+    // Hidden layers and objects are left out, as they are in Tiled
+    for layer in map.layers.iter().filter(|layer| layer.visible) {
+        match &layer.kind {
+            TiledLayerKind::Objects(objects) => {
+                for object in objects.iter().filter(|object| object.visible) {
+                    builder.add_object(layer, object)?;
+                }
+            }
+            TiledLayerKind::Tiles { width, tiles, .. } => {
+                builder.add_tiles(layer, map, *width, tiles);
+            }
+        }
+    }
+
+    Ok(legion_prefab::Prefab {
+        world: builder.world,
+        prefab_meta: legion_prefab::PrefabMeta {
+            id: prefab_id.0,
+            prefab_refs: HashMap::new(),
+            entities: builder.entities,
+        },
+    })
+}
+
+inventory::submit!(SourceFileImporter {
+    extension: "tmx",
+    instantiator: || Box::new(TiledImporter::default())
+});
+
+// Tiled's JSON maps use .tmj so that other JSON files in the asset directories aren't claimed by
+// this importer. Maps saved as .json need to be renamed
+inventory::submit!(SourceFileImporter {
+    extension: "tmj",
+    instantiator: || Box::new(TiledImporter::default())
+});
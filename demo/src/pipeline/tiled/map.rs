@@ -0,0 +1,760 @@
+use std::collections::HashMap;
+use std::io::Read;
+
+/// An error in the contents of a Tiled map file
+#[derive(Debug)]
+pub struct TiledMapError(pub String);
+
+impl std::fmt::Display for TiledMapError {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for TiledMapError {}
+
+type Result<T> = std::result::Result<T, TiledMapError>;
+
+fn error<T, S: Into<String>>(message: S) -> Result<T> {
+    Err(TiledMapError(message.into()))
+}
+
+// The top bits of a tile's global ID say whether it's flipped. They aren't part of the ID
+const TILE_FLIP_FLAGS: u32 = 0xE000_0000;
+
+/// Custom properties set on a layer or object in Tiled. Values are kept as strings regardless of
+/// the property's type in Tiled
+#[derive(Default, Debug, Clone)]
+pub struct TiledProperties(HashMap<String, String>);
+
+impl TiledProperties {
+    pub fn get(
+        &self,
+        name: &str,
+    ) -> Option<&str> {
+        self.0.get(name).map(|value| value.as_str())
+    }
+
+    pub fn get_bool(
+        &self,
+        name: &str,
+    ) -> Option<bool> {
+        self.get(name).and_then(|value| value.parse().ok())
+    }
+
+    /// Reads a color property, which Tiled stores as "#AARRGGBB"
+    pub fn get_color(
+        &self,
+        name: &str,
+    ) -> Option<[f32; 4]> {
+        self.get(name).and_then(parse_color)
+    }
+}
+
+/// Parses a color in the form Tiled uses, "#RRGGBB" or "#AARRGGBB". The returned color is RGBA
+pub fn parse_color(value: &str) -> Option<[f32; 4]> {
+    let hex = value.trim_start_matches('#');
+    let value = u32::from_str_radix(hex, 16).ok()?;
+    let channel = |shift: u32| ((value >> shift) & 0xFF) as f32 / 255.0;
+
+    match hex.len() {
+        6 => Some([channel(16), channel(8), channel(0), 1.0]),
+        8 => Some([channel(16), channel(8), channel(0), channel(24)]),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TiledObjectShape {
+    Rectangle,
+    Ellipse,
+    Point,
+
+    /// A tile placed as an object. Unlike other objects, its position is the bottom-left corner
+    Tile(u32),
+
+    /// Polygons, polylines and text aren't supported
+    Unsupported,
+}
+
+/// An object in an object layer. Positions and sizes are in pixels, with y pointing down
+#[derive(Debug, Clone)]
+pub struct TiledObject {
+    /// Unique within the map, and never reused when objects are deleted
+    pub id: u32,
+    pub name: String,
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+
+    /// In degrees, clockwise around the object's position
+    pub rotation: f32,
+    pub visible: bool,
+    pub shape: TiledObjectShape,
+    pub properties: TiledProperties,
+}
+
+#[derive(Debug, Clone)]
+pub enum TiledLayerKind {
+    /// Global tile IDs, one row after another starting at the top left. 0 is an empty tile
+    Tiles {
+        width: u32,
+        height: u32,
+        tiles: Vec<u32>,
+    },
+    Objects(Vec<TiledObject>),
+}
+
+/// A tile or object layer. Layers inside group layers are flattened into the map's list of layers
+#[derive(Debug, Clone)]
+pub struct TiledLayer {
+    /// Unique within the map. Maps saved by older versions of Tiled don't have layer IDs, so the
+    /// layer's position in the map is used instead
+    pub id: u32,
+    pub name: String,
+    pub visible: bool,
+
+    /// The color objects in the layer are shown with in Tiled
+    pub color: Option<[f32; 4]>,
+    pub properties: TiledProperties,
+    pub kind: TiledLayerKind,
+}
+
+/// A map saved by the Tiled editor, in either its XML (.tmx) or JSON (.tmj) format. Only the parts
+/// of the map that the importer uses are read. Tilesets aren't read, tiles are only used for their
+/// position
+#[derive(Debug, Clone)]
+pub struct TiledMap {
+    pub tile_width: u32,
+    pub tile_height: u32,
+    pub layers: Vec<TiledLayer>,
+}
+
+impl TiledMap {
+    /// Reads a map in either format. JSON maps are recognized by their first character
+    pub fn parse(text: &str) -> Result<Self> {
+        if text.trim_start().starts_with('{') {
+            Self::parse_json(text)
+        } else {
+            Self::parse_tmx(text)
+        }
+    }
+
+    pub fn parse_tmx(text: &str) -> Result<Self> {
+        let document = roxmltree::Document::parse(text)
+            .map_err(|e| TiledMapError(format!("Invalid XML: {}", e)))?;
+
+        let map = document.root_element();
+        if !map.has_tag_name("map") {
+            return error("The root element of a .tmx file must be <map>");
+        }
+
+        if map.attribute("infinite") == Some("1") {
+            return error("Infinite maps aren't supported");
+        }
+
+        let mut layers = vec![];
+        read_tmx_layers(map, true, &mut layers)?;
+
+        Ok(TiledMap {
+            tile_width: parse_tmx_attribute(map, "tilewidth")?,
+            tile_height: parse_tmx_attribute(map, "tileheight")?,
+            layers,
+        })
+    }
+
+    pub fn parse_json(text: &str) -> Result<Self> {
+        let map: serde_json::Value = serde_json::from_str(text)
+            .map_err(|e| TiledMapError(format!("Invalid JSON: {}", e)))?;
+
+        if map["infinite"].as_bool() == Some(true) {
+            return error("Infinite maps aren't supported");
+        }
+
+        let mut layers = vec![];
+        read_json_layers(&map["layers"], true, &mut layers)?;
+
+        Ok(TiledMap {
+            tile_width: json_u32(&map, "tilewidth")?,
+            tile_height: json_u32(&map, "tileheight")?,
+            layers,
+        })
+    }
+}
+
+// Decodes tile data stored as base64, optionally compressed. Each tile is a little-endian u32
+fn decode_base64_tiles(
+    data: &str,
+    compression: Option<&str>,
+) -> Result<Vec<u32>> {
+    let bytes = base64::decode(data.trim())
+        .map_err(|e| TiledMapError(format!("Invalid base64 tile data: {}", e)))?;
+
+    let bytes = match compression {
+        None | Some("") => bytes,
+        Some("zlib") => {
+            let mut decompressed = vec![];
+            flate2::read::ZlibDecoder::new(bytes.as_slice())
+                .read_to_end(&mut decompressed)
+                .map_err(|e| TiledMapError(format!("Invalid zlib tile data: {}", e)))?;
+            decompressed
+        }
+        Some("gzip") => {
+            let mut decompressed = vec![];
+            flate2::read::GzDecoder::new(bytes.as_slice())
+                .read_to_end(&mut decompressed)
+                .map_err(|e| TiledMapError(format!("Invalid gzip tile data: {}", e)))?;
+            decompressed
+        }
+        Some(compression) => {
+            return error(format!(
+                "Tile data compression {} isn't supported",
+                compression
+            ))
+        }
+    };
+
+    if bytes.len() % 4 != 0 {
+        return error(format!(
+            "Tile data is {} bytes, which isn't a whole number of tiles",
+            bytes.len()
+        ));
+    }
+
+    Ok(bytes
+        .chunks_exact(4)
+        .map(|tile| u32::from_le_bytes([tile[0], tile[1], tile[2], tile[3]]))
+        .collect())
+}
+
+fn check_tile_count(
+    name: &str,
+    width: u32,
+    height: u32,
+    tiles: &[u32],
+) -> Result<()> {
+    let expected_count = match width.checked_mul(height) {
+        Some(expected_count) => expected_count,
+        None => {
+            return error(format!(
+                "Layer {} is too large ({}x{} tiles)",
+                name, width, height
+            ))
+        }
+    };
+
+    if tiles.len() != expected_count as usize {
+        return error(format!(
+            "Layer {} should have {} tiles but has {}",
+            name,
+            expected_count,
+            tiles.len()
+        ));
+    }
+
+    Ok(())
+}
+
+//
+// XML (.tmx)
+//
+
+fn parse_tmx_attribute<T: std::str::FromStr>(
+    node: roxmltree::Node,
+    name: &str,
+) -> Result<T> {
+    let value = node.attribute(name).ok_or_else(|| {
+        TiledMapError(format!(
+            "<{}> is missing the {} attribute",
+            node.tag_name().name(),
+            name
+        ))
+    })?;
+
+    value.parse().map_err(|_| {
+        TiledMapError(format!(
+            "<{}> has an invalid {}: {}",
+            node.tag_name().name(),
+            name,
+            value
+        ))
+    })
+}
+
+// Like parse_tmx_attribute, but for attributes that Tiled leaves out when they have their default
+// value
+fn parse_optional_tmx_attribute<T: std::str::FromStr>(
+    node: roxmltree::Node,
+    name: &str,
+    default: T,
+) -> Result<T> {
+    if node.attribute(name).is_some() {
+        parse_tmx_attribute(node, name)
+    } else {
+        Ok(default)
+    }
+}
+
+fn read_tmx_properties(node: roxmltree::Node) -> TiledProperties {
+    let mut properties = HashMap::new();
+    let property_nodes = node
+        .children()
+        .filter(|child| child.has_tag_name("properties"))
+        .flat_map(|child| child.children())
+        .filter(|child| child.has_tag_name("property"));
+
+    for property in property_nodes {
+        if let Some(name) = property.attribute("name") {
+            // Multi-line strings are stored as the element's text instead of the value attribute
+            let value = property
+                .attribute("value")
+                .or_else(|| property.text())
+                .unwrap_or("");
+            properties.insert(name.to_string(), value.to_string());
+        }
+    }
+
+    TiledProperties(properties)
+}
+
+fn read_tmx_layers(
+    parent: roxmltree::Node,
+    parent_visible: bool,
+    layers: &mut Vec<TiledLayer>,
+) -> Result<()> {
+    for node in parent.children().filter(|node| node.is_element()) {
+        let is_layer = node.has_tag_name("layer")
+            || node.has_tag_name("objectgroup")
+            || node.has_tag_name("group");
+        if !is_layer {
+            continue;
+        }
+
+        let visible = parent_visible && node.attribute("visible") != Some("0");
+
+        if node.has_tag_name("group") {
+            read_tmx_layers(node, visible, layers)?;
+            continue;
+        }
+
+        let id = parse_optional_tmx_attribute(node, "id", layers.len() as u32)?;
+        let name = node.attribute("name").unwrap_or("").to_string();
+
+        let kind = if node.has_tag_name("layer") {
+            read_tmx_tiles(node, &name)?
+        } else {
+            let objects = node
+                .children()
+                .filter(|child| child.has_tag_name("object"))
+                .map(read_tmx_object)
+                .collect::<Result<_>>()?;
+            TiledLayerKind::Objects(objects)
+        };
+
+        layers.push(TiledLayer {
+            id,
+            name,
+            visible,
+            color: node.attribute("color").and_then(parse_color),
+            properties: read_tmx_properties(node),
+            kind,
+        });
+    }
+
+    Ok(())
+}
+
+fn read_tmx_tiles(
+    node: roxmltree::Node,
+    name: &str,
+) -> Result<TiledLayerKind> {
+    let width = parse_tmx_attribute(node, "width")?;
+    let height = parse_tmx_attribute(node, "height")?;
+
+    let data = match node.children().find(|child| child.has_tag_name("data")) {
+        Some(data) => data,
+        None => return error(format!("Layer {} has no tile data", name)),
+    };
+
+    let tiles: Vec<u32> = match data.attribute("encoding") {
+        Some("csv") => data
+            .text()
+            .unwrap_or("")
+            .split(',')
+            .map(|tile| tile.trim())
+            .filter(|tile| !tile.is_empty())
+            .map(|tile| {
+                tile.parse()
+                    .map_err(|_| TiledMapError(format!("Invalid tile {} in layer {}", tile, name)))
+            })
+            .collect::<Result<_>>()?,
+        Some("base64") => {
+            decode_base64_tiles(data.text().unwrap_or(""), data.attribute("compression"))?
+        }
+        Some(encoding) => return error(format!("Tile data encoding {} isn't supported", encoding)),
+        // Without an encoding, every tile is its own element
+        None => data
+            .children()
+            .filter(|child| child.has_tag_name("tile"))
+            .map(|tile| parse_optional_tmx_attribute(tile, "gid", 0))
+            .collect::<Result<_>>()?,
+    };
+
+    check_tile_count(name, width, height, &tiles)?;
+
+    Ok(TiledLayerKind::Tiles {
+        width,
+        height,
+        tiles: tiles
+            .into_iter()
+            .map(|tile| tile & !TILE_FLIP_FLAGS)
+            .collect(),
+    })
+}
+
+fn read_tmx_object(node: roxmltree::Node) -> Result<TiledObject> {
+    let has_child = |name: &str| node.children().any(|child| child.has_tag_name(name));
+
+    let shape = if let Some(gid) = node.attribute("gid") {
+        let gid: u32 = gid
+            .parse()
+            .map_err(|_| TiledMapError(format!("Invalid object gid: {}", gid)))?;
+        TiledObjectShape::Tile(gid & !TILE_FLIP_FLAGS)
+    } else if has_child("ellipse") {
+        TiledObjectShape::Ellipse
+    } else if has_child("point") {
+        TiledObjectShape::Point
+    } else if has_child("polygon") || has_child("polyline") || has_child("text") {
+        TiledObjectShape::Unsupported
+    } else {
+        TiledObjectShape::Rectangle
+    };
+
+    Ok(TiledObject {
+        id: parse_tmx_attribute(node, "id")?,
+        name: node.attribute("name").unwrap_or("").to_string(),
+        x: parse_optional_tmx_attribute(node, "x", 0.0)?,
+        y: parse_optional_tmx_attribute(node, "y", 0.0)?,
+        width: parse_optional_tmx_attribute(node, "width", 0.0)?,
+        height: parse_optional_tmx_attribute(node, "height", 0.0)?,
+        rotation: parse_optional_tmx_attribute(node, "rotation", 0.0)?,
+        visible: node.attribute("visible") != Some("0"),
+        shape,
+        properties: read_tmx_properties(node),
+    })
+}
+
+//
+// JSON (.tmj)
+//
+
+fn json_u32(
+    value: &serde_json::Value,
+    name: &str,
+) -> Result<u32> {
+    value[name]
+        .as_u64()
+        .map(|x| x as u32)
+        .ok_or_else(|| TiledMapError(format!("Missing or invalid {}", name)))
+}
+
+fn json_f32(
+    value: &serde_json::Value,
+    name: &str,
+) -> f32 {
+    value[name].as_f64().unwrap_or(0.0) as f32
+}
+
+fn json_str<'a>(
+    value: &'a serde_json::Value,
+    name: &str,
+) -> &'a str {
+    value[name].as_str().unwrap_or("")
+}
+
+fn read_json_properties(value: &serde_json::Value) -> TiledProperties {
+    let mut properties = HashMap::new();
+    for property in value["properties"].as_array().into_iter().flatten() {
+        let value = match &property["value"] {
+            serde_json::Value::String(value) => value.clone(),
+            value => value.to_string(),
+        };
+
+        properties.insert(json_str(property, "name").to_string(), value);
+    }
+
+    TiledProperties(properties)
+}
+
+fn read_json_layers(
+    value: &serde_json::Value,
+    parent_visible: bool,
+    layers: &mut Vec<TiledLayer>,
+) -> Result<()> {
+    for layer in value.as_array().into_iter().flatten() {
+        let visible = parent_visible && layer["visible"].as_bool().unwrap_or(true);
+        let name = json_str(layer, "name").to_string();
+
+        let kind = match json_str(layer, "type") {
+            "group" => {
+                read_json_layers(&layer["layers"], visible, layers)?;
+                continue;
+            }
+            "tilelayer" => read_json_tiles(layer, &name)?,
+            "objectgroup" => {
+                let objects = layer["objects"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .map(read_json_object)
+                    .collect::<Result<_>>()?;
+                TiledLayerKind::Objects(objects)
+            }
+            // Image layers
+            _ => continue,
+        };
+
+        let id = layer["id"]
+            .as_u64()
+            .map(|id| id as u32)
+            .unwrap_or(layers.len() as u32);
+
+        layers.push(TiledLayer {
+            id,
+            name,
+            visible,
+            color: layer["color"].as_str().and_then(parse_color),
+            properties: read_json_properties(layer),
+            kind,
+        });
+    }
+
+    Ok(())
+}
+
+fn read_json_tiles(
+    layer: &serde_json::Value,
+    name: &str,
+) -> Result<TiledLayerKind> {
+    let width = json_u32(layer, "width")?;
+    let height = json_u32(layer, "height")?;
+
+    let tiles: Vec<u32> = match &layer["data"] {
+        serde_json::Value::Array(tiles) => tiles
+            .iter()
+            .map(|tile| {
+                tile.as_u64()
+                    .map(|tile| tile as u32)
+                    .ok_or_else(|| TiledMapError(format!("Invalid tile in layer {}", name)))
+            })
+            .collect::<Result<_>>()?,
+        serde_json::Value::String(data) => {
+            decode_base64_tiles(data, layer["compression"].as_str())?
+        }
+        _ => return error(format!("Layer {} has no tile data", name)),
+    };
+
+    check_tile_count(name, width, height, &tiles)?;
+
+    Ok(TiledLayerKind::Tiles {
+        width,
+        height,
+        tiles: tiles
+            .into_iter()
+            .map(|tile| tile & !TILE_FLIP_FLAGS)
+            .collect(),
+    })
+}
+
+fn read_json_object(object: &serde_json::Value) -> Result<TiledObject> {
+    let shape = if let Some(gid) = object["gid"].as_u64() {
+        TiledObjectShape::Tile(gid as u32 & !TILE_FLIP_FLAGS)
+    } else if object["ellipse"].as_bool() == Some(true) {
+        TiledObjectShape::Ellipse
+    } else if object["point"].as_bool() == Some(true) {
+        TiledObjectShape::Point
+    } else if !object["polygon"].is_null()
+        || !object["polyline"].is_null()
+        || !object["text"].is_null()
+    {
+        TiledObjectShape::Unsupported
+    } else {
+        TiledObjectShape::Rectangle
+    };
+
+    Ok(TiledObject {
+        id: json_u32(object, "id")?,
+        name: json_str(object, "name").to_string(),
+        x: json_f32(object, "x"),
+        y: json_f32(object, "y"),
+        width: json_f32(object, "width"),
+        height: json_f32(object, "height"),
+        rotation: json_f32(object, "rotation"),
+        visible: object["visible"].as_bool().unwrap_or(true),
+        shape,
+        properties: read_json_properties(object),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    // Tile IDs used by the tests, including one with the horizontal flip flag set
+    const TILES: [u32; 6] = [1, 0, 2, 0x8000_0003, 0, 4];
+
+    fn tile_bytes(tiles: &[u32]) -> Vec<u8> {
+        tiles
+            .iter()
+            .flat_map(|tile| tile.to_le_bytes().to_vec())
+            .collect()
+    }
+
+    fn zlib_compress(bytes: &[u8]) -> Vec<u8> {
+        let mut encoder = flate2::write::ZlibEncoder::new(vec![], flate2::Compression::default());
+        encoder.write_all(bytes).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn gzip_compress(bytes: &[u8]) -> Vec<u8> {
+        let mut encoder = flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+        encoder.write_all(bytes).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn tmx_map(data: &str) -> String {
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<map version="1.2" orientation="orthogonal" width="3" height="2" tilewidth="32" tileheight="32">
+  <layer id="1" name="ground" width="3" height="2">
+    {}
+  </layer>
+</map>"#,
+            data
+        )
+    }
+
+    fn layer_tiles(map: &TiledMap) -> &[u32] {
+        match &map.layers[0].kind {
+            TiledLayerKind::Tiles { tiles, .. } => tiles,
+            TiledLayerKind::Objects(_) => panic!("expected a tile layer"),
+        }
+    }
+
+    #[test]
+    fn decode_uncompressed_base64() {
+        let data = base64::encode(&tile_bytes(&TILES));
+        assert_eq!(decode_base64_tiles(&data, None).unwrap(), TILES);
+        assert_eq!(decode_base64_tiles(&data, Some("")).unwrap(), TILES);
+    }
+
+    #[test]
+    fn decode_zlib_base64() {
+        let data = base64::encode(&zlib_compress(&tile_bytes(&TILES)));
+        assert_eq!(decode_base64_tiles(&data, Some("zlib")).unwrap(), TILES);
+    }
+
+    #[test]
+    fn decode_gzip_base64() {
+        let data = base64::encode(&gzip_compress(&tile_bytes(&TILES)));
+        assert_eq!(decode_base64_tiles(&data, Some("gzip")).unwrap(), TILES);
+    }
+
+    #[test]
+    fn decode_base64_ignores_surrounding_whitespace() {
+        // Tiled writes the data on its own indented line in .tmx files
+        let data = format!("\n    {}\n  ", base64::encode(&tile_bytes(&TILES)));
+        assert_eq!(decode_base64_tiles(&data, None).unwrap(), TILES);
+    }
+
+    #[test]
+    fn decode_base64_rejects_bad_data() {
+        assert!(decode_base64_tiles("not base64!", None).is_err());
+
+        let uncompressed = base64::encode(&tile_bytes(&TILES));
+        assert!(decode_base64_tiles(&uncompressed, Some("zlib")).is_err());
+        assert!(decode_base64_tiles(&uncompressed, Some("gzip")).is_err());
+        assert!(decode_base64_tiles(&uncompressed, Some("zstd")).is_err());
+
+        // A partial tile at the end
+        let truncated = base64::encode(&tile_bytes(&TILES)[..7]);
+        assert!(decode_base64_tiles(&truncated, None).is_err());
+    }
+
+    #[test]
+    fn tile_count_must_match_layer_size() {
+        assert!(check_tile_count("ground", 3, 2, &TILES).is_ok());
+        assert!(check_tile_count("ground", 2, 2, &TILES).is_err());
+
+        // Too large to count, rather than overflowing
+        assert!(check_tile_count("ground", u32::max_value(), 2, &TILES).is_err());
+    }
+
+    #[test]
+    fn parse_tmx_compressed_layers_and_strip_flip_flags() {
+        let expected = [1, 0, 2, 3, 0, 4];
+        for (compression, bytes) in &[
+            ("zlib", zlib_compress(&tile_bytes(&TILES))),
+            ("gzip", gzip_compress(&tile_bytes(&TILES))),
+        ] {
+            let data = format!(
+                r#"<data encoding="base64" compression="{}">{}</data>"#,
+                compression,
+                base64::encode(bytes)
+            );
+            let map = TiledMap::parse(&tmx_map(&data)).unwrap();
+            assert_eq!(layer_tiles(&map), expected);
+        }
+    }
+
+    #[test]
+    fn parse_tmx_csv_layer() {
+        let map = TiledMap::parse(&tmx_map(
+            r#"<data encoding="csv">1,0,2,
+2147483651,0,4</data>"#,
+        ))
+        .unwrap();
+        assert_eq!(layer_tiles(&map), [1, 0, 2, 3, 0, 4]);
+    }
+
+    #[test]
+    fn parse_json_base64_layer() {
+        let text = format!(
+            r#"{{
+                "tilewidth": 32,
+                "tileheight": 32,
+                "layers": [{{
+                    "id": 1,
+                    "name": "ground",
+                    "type": "tilelayer",
+                    "visible": true,
+                    "width": 3,
+                    "height": 2,
+                    "encoding": "base64",
+                    "compression": "zlib",
+                    "data": "{}"
+                }}]
+            }}"#,
+            base64::encode(&zlib_compress(&tile_bytes(&TILES)))
+        );
+
+        let map = TiledMap::parse(&text).unwrap();
+        assert_eq!(layer_tiles(&map), [1, 0, 2, 3, 0, 4]);
+    }
+
+    #[test]
+    fn parse_rejects_oversized_layer() {
+        let text = r#"<?xml version="1.0" encoding="UTF-8"?>
+<map version="1.2" orientation="orthogonal" tilewidth="32" tileheight="32">
+  <layer id="1" name="ground" width="4294967295" height="2">
+    <data encoding="csv">1</data>
+  </layer>
+</map>"#;
+        assert!(TiledMap::parse(text).is_err());
+    }
+}
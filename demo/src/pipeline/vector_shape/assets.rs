@@ -0,0 +1,181 @@
+use serde::{Deserialize, Serialize};
+use type_uuid::*;
+
+/// A 2D affine transform, stored the same way as SVG's matrix(a b c d e f). A point (x, y) is
+/// transformed to (a * x + c * y + e, b * x + d * y + f)
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct VectorTransform(pub [f32; 6]);
+
+impl VectorTransform {
+    pub fn identity() -> Self {
+        VectorTransform([1.0, 0.0, 0.0, 1.0, 0.0, 0.0])
+    }
+
+    pub fn apply(
+        &self,
+        point: [f32; 2],
+    ) -> [f32; 2] {
+        let [a, b, c, d, e, f] = self.0;
+        [
+            a * point[0] + c * point[1] + e,
+            b * point[0] + d * point[1] + f,
+        ]
+    }
+
+    /// Returns the transform that applies `other` first and then this transform
+    pub fn then(
+        &self,
+        other: &VectorTransform,
+    ) -> VectorTransform {
+        let [a1, b1, c1, d1, e1, f1] = self.0;
+        let [a2, b2, c2, d2, e2, f2] = other.0;
+        VectorTransform([
+            a1 * a2 + c1 * b2,
+            b1 * a2 + d1 * b2,
+            a1 * c2 + c1 * d2,
+            b1 * c2 + d1 * d2,
+            a1 * e2 + c1 * f2 + e1,
+            b1 * e2 + d1 * f2 + f1,
+        ])
+    }
+
+    /// How much the transform scales lengths, on average. Used to transform stroke widths
+    pub fn mean_scale(&self) -> f32 {
+        let [a, b, c, d, _, _] = self.0;
+        (a * d - b * c).abs().sqrt()
+    }
+}
+
+impl Default for VectorTransform {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum VectorPathCommand {
+    MoveTo([f32; 2]),
+    LineTo([f32; 2]),
+    QuadTo([f32; 2], [f32; 2]),
+    CubicTo([f32; 2], [f32; 2], [f32; 2]),
+    Close,
+}
+
+/// How the inside of a path that overlaps itself is decided
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum VectorFillRule {
+    NonZero,
+    EvenOdd,
+}
+
+/// What a gradient does past its first and last stops
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum VectorSpreadMethod {
+    Pad,
+    Reflect,
+    Repeat,
+}
+
+// Same as SVG's default
+impl Default for VectorSpreadMethod {
+    fn default() -> Self {
+        VectorSpreadMethod::Pad
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct VectorGradientStop {
+    /// Where the stop is along the gradient, from 0 to 1
+    pub offset: f32,
+    pub color: [f32; 4],
+}
+
+/// What a path is filled or stroked with. Gradient points are in the gradient's own space, which
+/// `transform` maps into the shape's space
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum VectorPaint {
+    Color([f32; 4]),
+    LinearGradient {
+        start: [f32; 2],
+        end: [f32; 2],
+        stops: Vec<VectorGradientStop>,
+        spread_method: VectorSpreadMethod,
+        transform: VectorTransform,
+    },
+    RadialGradient {
+        center: [f32; 2],
+        radius: f32,
+
+        /// Where the gradient starts from. This is the center unless the focal point was moved
+        focal_point: [f32; 2],
+        stops: Vec<VectorGradientStop>,
+        spread_method: VectorSpreadMethod,
+        transform: VectorTransform,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum VectorLineCap {
+    Butt,
+    Round,
+    Square,
+}
+
+// Same as SVG's default
+impl Default for VectorLineCap {
+    fn default() -> Self {
+        VectorLineCap::Butt
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum VectorLineJoin {
+    Miter,
+    Round,
+    Bevel,
+}
+
+// Same as SVG's default
+impl Default for VectorLineJoin {
+    fn default() -> Self {
+        VectorLineJoin::Miter
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct VectorStroke {
+    pub paint: VectorPaint,
+    pub width: f32,
+    pub line_cap: VectorLineCap,
+    pub line_join: VectorLineJoin,
+    pub miter_limit: f32,
+
+    /// Alternating lengths of dashes and gaps. Empty for a solid line
+    pub dash_pattern: Vec<f32>,
+    pub dash_offset: f32,
+}
+
+/// A single path of a vector shape. If it has both, the fill is drawn before the stroke
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct VectorPath {
+    pub commands: Vec<VectorPathCommand>,
+    pub fill: Option<VectorPaint>,
+    pub fill_rule: VectorFillRule,
+    pub stroke: Option<VectorStroke>,
+
+    /// Multiplied with the alpha of the fill and stroke
+    pub opacity: f32,
+}
+
+/// Vector art made of paths, in world units with y pointing up. The shape is centered on the
+/// origin, so it's drawn centered on the entity's position
+#[derive(TypeUuid, Serialize, Deserialize, Debug, Clone)]
+#[uuid = "6c5b0a3e-58d4-4c71-9f27-e0a84d1b2c96"]
+pub struct VectorShapeAsset {
+    /// Drawn in order, so later paths are on top
+    pub paths: Vec<VectorPath>,
+
+    /// A convex polygon around every path, counter-clockwise. Used to select the shape in the
+    /// editor
+    pub collision_polygon: Vec<[f32; 2]>,
+}
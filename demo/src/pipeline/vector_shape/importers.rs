@@ -0,0 +1,433 @@
+use std::io::Read;
+
+use atelier_core::AssetUuid;
+use atelier_importer::{Error, ImportedAsset, Importer, ImporterValue, Result};
+use serde::{Deserialize, Serialize};
+use type_uuid::*;
+
+use crate::pipeline::vector_shape::*;
+
+#[derive(TypeUuid, Serialize, Deserialize, Default)]
+#[uuid = "0e8f3c2a-9d41-4b6e-a5f7-3c1d8e2b9a40"]
+struct SimpleState(Option<AssetUuid>);
+
+/// Settings for how an SVG is imported. These are stored in the SVG's .meta file
+#[derive(TypeUuid, Serialize, Deserialize, Clone, Debug)]
+#[uuid = "9a2d6e41-0c7b-4f83-b1e5-7d4f2a8c6e19"]
+#[serde(default)]
+pub struct VectorShapeImporterOptions {
+    /// How many of the SVG's pixels (user units) make up one unit in the world
+    pub pixels_per_unit: f32,
+}
+
+impl Default for VectorShapeImporterOptions {
+    fn default() -> Self {
+        VectorShapeImporterOptions {
+            pixels_per_unit: 64.0,
+        }
+    }
+}
+
+fn to_vector_transform(transform: &usvg::Transform) -> VectorTransform {
+    VectorTransform([
+        transform.a as f32,
+        transform.b as f32,
+        transform.c as f32,
+        transform.d as f32,
+        transform.e as f32,
+        transform.f as f32,
+    ])
+}
+
+fn to_color(
+    color: usvg::Color,
+    opacity: f64,
+) -> [f32; 4] {
+    [
+        color.red as f32 / 255.0,
+        color.green as f32 / 255.0,
+        color.blue as f32 / 255.0,
+        opacity as f32,
+    ]
+}
+
+// Returns the bounds of the path's points, before any transform, as [x, y, width, height]. Control
+// points are included, so this can be larger than the path itself
+fn path_bounds(path: &usvg::Path) -> Option<[f32; 4]> {
+    let mut min = [std::f32::MAX, std::f32::MAX];
+    let mut max = [std::f32::MIN, std::f32::MIN];
+    let mut add_point = |x: f64, y: f64| {
+        min = [min[0].min(x as f32), min[1].min(y as f32)];
+        max = [max[0].max(x as f32), max[1].max(y as f32)];
+    };
+
+    for segment in path.data.iter() {
+        match *segment {
+            usvg::PathSegment::MoveTo { x, y } | usvg::PathSegment::LineTo { x, y } => {
+                add_point(x, y)
+            }
+            usvg::PathSegment::CurveTo {
+                x1,
+                y1,
+                x2,
+                y2,
+                x,
+                y,
+            } => {
+                add_point(x1, y1);
+                add_point(x2, y2);
+                add_point(x, y);
+            }
+            usvg::PathSegment::ClosePath => {}
+        }
+    }
+
+    if min[0] > max[0] {
+        return None;
+    }
+
+    Some([min[0], min[1], max[0] - min[0], max[1] - min[1]])
+}
+
+fn convert_stops(
+    gradient: &usvg::BaseGradient,
+    opacity: f64,
+) -> Vec<VectorGradientStop> {
+    gradient
+        .stops
+        .iter()
+        .map(|stop| VectorGradientStop {
+            offset: stop.offset.value() as f32,
+            color: to_color(stop.color, stop.opacity.value() * opacity),
+        })
+        .collect()
+}
+
+fn convert_spread_method(spread_method: usvg::SpreadMethod) -> VectorSpreadMethod {
+    match spread_method {
+        usvg::SpreadMethod::Pad => VectorSpreadMethod::Pad,
+        usvg::SpreadMethod::Reflect => VectorSpreadMethod::Reflect,
+        usvg::SpreadMethod::Repeat => VectorSpreadMethod::Repeat,
+    }
+}
+
+// Finds the transform from the gradient's space to the shape's space. Gradients in bounding box
+// units are relative to the bounds of the path they're used on
+fn gradient_transform(
+    gradient: &usvg::BaseGradient,
+    path_transform: &VectorTransform,
+    path_bounds: Option<[f32; 4]>,
+) -> Option<VectorTransform> {
+    let mut transform = *path_transform;
+    if gradient.units == usvg::Units::ObjectBoundingBox {
+        // SVG doesn't draw bounding box gradients on paths with no width or height
+        let [x, y, width, height] = path_bounds?;
+        if width <= 0.0 || height <= 0.0 {
+            return None;
+        }
+
+        transform = transform.then(&VectorTransform([width, 0.0, 0.0, height, x, y]));
+    }
+
+    Some(transform.then(&to_vector_transform(&gradient.transform)))
+}
+
+fn convert_paint(
+    tree: &usvg::Tree,
+    paint: &usvg::Paint,
+    opacity: f64,
+    path_transform: &VectorTransform,
+    path_bounds: Option<[f32; 4]>,
+) -> Option<VectorPaint> {
+    let id = match paint {
+        usvg::Paint::Color(color) => return Some(VectorPaint::Color(to_color(*color, opacity))),
+        usvg::Paint::Link(id) => id,
+    };
+
+    let node = match tree.defs_by_id(id) {
+        Some(node) => node,
+        None => {
+            log::warn!("Paint {} doesn't exist", id);
+            return None;
+        }
+    };
+
+    let paint = match *node.borrow() {
+        usvg::NodeKind::LinearGradient(ref gradient) => VectorPaint::LinearGradient {
+            start: [gradient.x1 as f32, gradient.y1 as f32],
+            end: [gradient.x2 as f32, gradient.y2 as f32],
+            stops: convert_stops(&gradient.base, opacity),
+            spread_method: convert_spread_method(gradient.base.spread_method),
+            transform: gradient_transform(&gradient.base, path_transform, path_bounds)?,
+        },
+        usvg::NodeKind::RadialGradient(ref gradient) => VectorPaint::RadialGradient {
+            center: [gradient.cx as f32, gradient.cy as f32],
+            radius: gradient.r.value() as f32,
+            focal_point: [gradient.fx as f32, gradient.fy as f32],
+            stops: convert_stops(&gradient.base, opacity),
+            spread_method: convert_spread_method(gradient.base.spread_method),
+            transform: gradient_transform(&gradient.base, path_transform, path_bounds)?,
+        },
+        _ => {
+            log::warn!("Paint {} isn't a gradient. Patterns aren't supported", id);
+            return None;
+        }
+    };
+
+    Some(paint)
+}
+
+fn convert_path(
+    tree: &usvg::Tree,
+    path: &usvg::Path,
+    transform: &VectorTransform,
+    opacity: f32,
+) -> VectorPath {
+    let commands = path
+        .data
+        .iter()
+        .map(|segment| match *segment {
+            usvg::PathSegment::MoveTo { x, y } => {
+                VectorPathCommand::MoveTo(transform.apply([x as f32, y as f32]))
+            }
+            usvg::PathSegment::LineTo { x, y } => {
+                VectorPathCommand::LineTo(transform.apply([x as f32, y as f32]))
+            }
+            usvg::PathSegment::CurveTo {
+                x1,
+                y1,
+                x2,
+                y2,
+                x,
+                y,
+            } => VectorPathCommand::CubicTo(
+                transform.apply([x1 as f32, y1 as f32]),
+                transform.apply([x2 as f32, y2 as f32]),
+                transform.apply([x as f32, y as f32]),
+            ),
+            usvg::PathSegment::ClosePath => VectorPathCommand::Close,
+        })
+        .collect();
+
+    let bounds = path_bounds(path);
+
+    let fill = path
+        .fill
+        .as_ref()
+        .and_then(|fill| convert_paint(tree, &fill.paint, fill.opacity.value(), transform, bounds));
+
+    let fill_rule = match path.fill.as_ref().map(|fill| fill.rule) {
+        Some(usvg::FillRule::EvenOdd) => VectorFillRule::EvenOdd,
+        _ => VectorFillRule::NonZero,
+    };
+
+    // Stroke widths and dashes are lengths, so they're scaled but not moved
+    let scale = transform.mean_scale();
+    let stroke = path.stroke.as_ref().and_then(|stroke| {
+        let paint = convert_paint(
+            tree,
+            &stroke.paint,
+            stroke.opacity.value(),
+            transform,
+            bounds,
+        )?;
+        Some(VectorStroke {
+            paint,
+            width: stroke.width.value() as f32 * scale,
+            line_cap: match stroke.linecap {
+                usvg::LineCap::Butt => VectorLineCap::Butt,
+                usvg::LineCap::Round => VectorLineCap::Round,
+                usvg::LineCap::Square => VectorLineCap::Square,
+            },
+            line_join: match stroke.linejoin {
+                usvg::LineJoin::Miter => VectorLineJoin::Miter,
+                usvg::LineJoin::Round => VectorLineJoin::Round,
+                usvg::LineJoin::Bevel => VectorLineJoin::Bevel,
+            },
+            miter_limit: stroke.miterlimit.value() as f32,
+            dash_pattern: stroke
+                .dasharray
+                .iter()
+                .flatten()
+                .map(|length| *length as f32 * scale)
+                .collect(),
+            dash_offset: stroke.dashoffset * scale,
+        })
+    });
+
+    VectorPath {
+        commands,
+        fill,
+        fill_rule,
+        stroke,
+        opacity,
+    }
+}
+
+// Converts every visible path under the node. Groups are flattened, their opacity is applied to
+// each path in them. That isn't exactly how SVG blends group opacity, but is close when paths in
+// a group don't overlap
+fn collect_paths(
+    tree: &usvg::Tree,
+    node: &usvg::Node,
+    svg_to_shape: &VectorTransform,
+    opacity: f32,
+    paths: &mut Vec<VectorPath>,
+) {
+    for child in node.children() {
+        match *child.borrow() {
+            usvg::NodeKind::Path(ref path) => {
+                if path.visibility != usvg::Visibility::Visible {
+                    continue;
+                }
+
+                let transform = svg_to_shape.then(&to_vector_transform(&child.abs_transform()));
+                paths.push(convert_path(tree, path, &transform, opacity));
+            }
+            usvg::NodeKind::Group(ref group) => {
+                let opacity = opacity * group.opacity.value() as f32;
+                collect_paths(tree, &child, svg_to_shape, opacity, paths);
+            }
+            // Definitions (gradients, clip paths, etc.) are only drawn when something uses them.
+            // Images and text aren't supported
+            _ => {}
+        }
+    }
+}
+
+// Returns the convex hull of the points, counter-clockwise (Andrew's monotone chain)
+fn convex_hull(mut points: Vec<[f32; 2]>) -> Vec<[f32; 2]> {
+    points.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    points.dedup();
+
+    if points.len() < 3 {
+        return points;
+    }
+
+    let cross = |o: [f32; 2], a: [f32; 2], b: [f32; 2]| {
+        (a[0] - o[0]) * (b[1] - o[1]) - (a[1] - o[1]) * (b[0] - o[0])
+    };
+
+    let mut lower: Vec<[f32; 2]> = vec![];
+    for point in &points {
+        while lower.len() >= 2
+            && cross(lower[lower.len() - 2], lower[lower.len() - 1], *point) <= 0.0
+        {
+            lower.pop();
+        }
+        lower.push(*point);
+    }
+
+    let mut upper: Vec<[f32; 2]> = vec![];
+    for point in points.iter().rev() {
+        while upper.len() >= 2
+            && cross(upper[upper.len() - 2], upper[upper.len() - 1], *point) <= 0.0
+        {
+            upper.pop();
+        }
+        upper.push(*point);
+    }
+
+    // The last point of each half is the first point of the other
+    lower.pop();
+    upper.pop();
+    lower.extend(upper);
+    lower
+}
+
+// The hull of every point of every path. Control points are included, which keeps curves inside
+// the hull
+fn create_collision_polygon(paths: &[VectorPath]) -> Vec<[f32; 2]> {
+    let points = paths
+        .iter()
+        .flat_map(|path| path.commands.iter())
+        .flat_map(|command| match *command {
+            VectorPathCommand::MoveTo(p) | VectorPathCommand::LineTo(p) => vec![p],
+            VectorPathCommand::QuadTo(p1, p2) => vec![p1, p2],
+            VectorPathCommand::CubicTo(p1, p2, p3) => vec![p1, p2, p3],
+            VectorPathCommand::Close => vec![],
+        })
+        .collect();
+
+    convex_hull(points)
+}
+
+/// Imports an SVG as a vector shape. Paths and basic shapes with fills, strokes and gradients are
+/// supported. Images, text, patterns, filters, clip paths and masks are not.
+#[derive(TypeUuid)]
+#[uuid = "4f1b7d93-2e6a-4c08-8a3d-b5e9f0c21d67"]
+struct VectorShapeImporter;
+impl Importer for VectorShapeImporter {
+    fn version_static() -> u32
+    where
+        Self: Sized,
+    {
+        1
+    }
+    fn version(&self) -> u32 {
+        Self::version_static()
+    }
+
+    type Options = VectorShapeImporterOptions;
+
+    type State = SimpleState;
+
+    /// Reads the given bytes and produces assets.
+    fn import(
+        &self,
+        source: &mut dyn Read,
+        options: Self::Options,
+        state: &mut Self::State,
+    ) -> Result<ImporterValue> {
+        let id = state
+            .0
+            .unwrap_or_else(|| AssetUuid(*uuid::Uuid::new_v4().as_bytes()));
+        *state = SimpleState(Some(id));
+        let mut bytes = Vec::new();
+        source.read_to_end(&mut bytes)?;
+
+        if options.pixels_per_unit <= 0.0 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "pixels_per_unit must be greater than 0",
+            )
+            .into());
+        }
+
+        let tree = usvg::Tree::from_data(&bytes, &usvg::Options::default())
+            .map_err(|e| Error::Boxed(Box::new(e)))?;
+
+        // Center the view box on the origin, scale it to world units and flip it so y points up
+        let view_box = tree.svg_node().view_box.rect;
+        let scale = 1.0 / options.pixels_per_unit;
+        let center_x = (view_box.x() + view_box.width() / 2.0) as f32;
+        let center_y = (view_box.y() + view_box.height() / 2.0) as f32;
+        let svg_to_shape =
+            VectorTransform([scale, 0.0, 0.0, -scale, -center_x * scale, center_y * scale]);
+
+        let mut paths = vec![];
+        collect_paths(&tree, &tree.root(), &svg_to_shape, 1.0, &mut paths);
+
+        let collision_polygon = create_collision_polygon(&paths);
+        let asset = VectorShapeAsset {
+            paths,
+            collision_polygon,
+        };
+
+        Ok(ImporterValue {
+            assets: vec![ImportedAsset {
+                id,
+                search_tags: vec![],
+                build_deps: vec![],
+                load_deps: vec![],
+                build_pipeline: None,
+                asset_data: Box::new(asset),
+            }],
+        })
+    }
+}
+
+inventory::submit!(atelier_importer::SourceFileImporter {
+    extension: "svg",
+    instantiator: || Box::new(VectorShapeImporter {}),
+});
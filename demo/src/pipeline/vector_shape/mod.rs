@@ -0,0 +1,14 @@
+mod assets;
+mod importers;
+
+pub use assets::VectorFillRule;
+pub use assets::VectorGradientStop;
+pub use assets::VectorLineCap;
+pub use assets::VectorLineJoin;
+pub use assets::VectorPaint;
+pub use assets::VectorPath;
+pub use assets::VectorPathCommand;
+pub use assets::VectorShapeAsset;
+pub use assets::VectorSpreadMethod;
+pub use assets::VectorStroke;
+pub use assets::VectorTransform;
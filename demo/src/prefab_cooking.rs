@@ -1,27 +1,29 @@
-use crate::resources::AssetResource;
+use crate::resources::{asset_load_state, AssetLoadState, AssetResource};
 
-use atelier_loader::{
-    handle::{AssetHandle, Handle},
-    rpc_loader::RpcLoader,
-    LoadStatus, Loader,
-};
+use atelier_loader::handle::{AssetHandle, Handle};
 use std::collections::HashMap;
 use legion::prelude::*;
 use legion_transaction::{SpawnCloneImpl, CopyCloneImpl};
 
 use legion::storage::ComponentTypeId;
 use prefab_format::ComponentTypeUuid;
-use legion_prefab::{ComponentRegistration, CookedPrefab};
+use legion_prefab::{ComponentRegistration, CookedPrefab, Prefab};
 use crate::pipeline::PrefabAsset;
 use atelier_core::AssetUuid;
+use std::sync::Arc;
 
+/// Cooks the given prefab and all prefabs it references into a single world. Any prefab in
+/// in_memory_prefabs is cooked from that copy instead of the one in asset storage. The editor uses
+/// this so that unsaved changes to a prefab show up in the prefabs that reference it. Returns an
+/// error if the prefab or any prefab it references fails to load.
 pub fn cook_prefab(
     universe: &Universe,
     asset_manager: &mut AssetResource,
     registered_components: &HashMap<ComponentTypeId, ComponentRegistration>,
     registered_components_by_uuid: &HashMap<ComponentTypeUuid, ComponentRegistration>,
+    in_memory_prefabs: &HashMap<AssetUuid, Arc<Prefab>>,
     prefab_uuid: AssetUuid,
-) -> CookedPrefab {
+) -> Result<CookedPrefab, String> {
     let resources = Resources::default();
 
     // Create the clone_merge impl. For prefab cooking, we will clone everything so we don't need to
@@ -42,10 +44,11 @@ pub fn cook_prefab(
     // populates prefab_lookup and prefab_cook_order
     request_prefab_dependency(
         asset_manager,
+        in_memory_prefabs,
         prefab_uuid,
         &mut prefab_lookup,
         &mut prefab_cook_order,
-    );
+    )?;
 
     for id in &prefab_cook_order {
         log::trace!("prefabs_in_cook_order: {}", id);
@@ -56,14 +59,21 @@ pub fn cook_prefab(
 
     // merge all entity data from all prefabs. This data doesn't include any overrides, so order
     // doesn't matter
-    for (_, prefab_handle) in &prefab_lookup {
-        let prefab_asset: &PrefabAsset = prefab_handle.asset(asset_manager.storage()).unwrap();
+    for (prefab_id, prefab_handle) in &prefab_lookup {
+        let prefab_asset;
+        let prefab = match in_memory_prefabs.get(prefab_id) {
+            Some(prefab) => &**prefab,
+            None => {
+                prefab_asset = asset_manager.storage().get(prefab_handle).unwrap();
+                &prefab_asset.prefab
+            }
+        };
 
         log::trace!(
             "Cloning entities from prefab {}",
-            AssetUuid(prefab_asset.prefab.prefab_meta.id)
+            AssetUuid(prefab.prefab_meta.id)
         );
-        log::trace!("{:#?}", prefab_asset.prefab.prefab_meta.entities);
+        log::trace!("{:#?}", prefab.prefab_meta.entities);
 
         // Clone all the entities from the prefab into the cooked world. As the data is copied,
         // entity will get a new Entity assigned to it in the cooked world. result_mappings will
@@ -71,7 +81,7 @@ pub fn cook_prefab(
         // the cooked world
         let mut result_mappings = HashMap::new();
         world.clone_from(
-            &prefab_asset.prefab.world,
+            &prefab.world,
             &clone_merge_impl,
             &mut legion::world::HashMapCloneImplResult(&mut result_mappings),
             &legion::world::NoneEntityReplacePolicy,
@@ -79,7 +89,7 @@ pub fn cook_prefab(
 
         // Iterate the entities in this prefab. Determine where they are stored in the cooked
         // world and store this in entity_lookup
-        for (entity_uuid, prefab_entity) in &prefab_asset.prefab.prefab_meta.entities {
+        for (entity_uuid, prefab_entity) in &prefab.prefab_meta.entities {
             let cooked_entity = result_mappings[prefab_entity];
             entity_lookup.insert(*entity_uuid, cooked_entity);
             log::trace!(
@@ -96,16 +106,21 @@ pub fn cook_prefab(
     for prefab_id in &prefab_cook_order {
         // fetch the data for the prefab
         let prefab_handle = &prefab_lookup[prefab_id];
-        let prefab_asset: &PrefabAsset = prefab_handle.asset(asset_manager.storage()).unwrap();
+        let prefab_asset;
+        let prefab = match in_memory_prefabs.get(prefab_id) {
+            Some(prefab) => &**prefab,
+            None => {
+                prefab_asset = asset_manager.storage().get(prefab_handle).unwrap();
+                &prefab_asset.prefab
+            }
+        };
 
         // Iterate all the other prefabs that this prefab references
         log::trace!(
             "Iterating prefabs referenced by prefab {}",
-            uuid::Uuid::from_bytes(prefab_asset.prefab.prefab_meta.id)
+            uuid::Uuid::from_bytes(prefab.prefab_meta.id)
         );
-        for (dependency_prefab_id, dependency_prefab_ref) in
-            &prefab_asset.prefab.prefab_meta.prefab_refs
-        {
+        for (dependency_prefab_id, dependency_prefab_ref) in &prefab.prefab_meta.prefab_refs {
             // Iterate all the entities for which we have override data
             log::trace!(
                 "Processing reference to prefab {}",
@@ -161,35 +176,54 @@ pub fn cook_prefab(
         log::trace!("{}", cooked_prefab_string2);
     }
 
-    cooked_prefab
+    Ok(cooked_prefab)
 }
 
 // This function does a recursive blocking load on the provided prefab asset and all prefabs
-// that it references. As it does this, prefab_lookup and prefab_cook_order are populated
+// that it references. As it does this, prefab_lookup and prefab_cook_order are populated. If any of
+// the prefabs fails to load, the error says which one and why
 fn request_prefab_dependency(
     asset_manager: &mut AssetResource,
+    in_memory_prefabs: &HashMap<AssetUuid, Arc<Prefab>>,
     id: AssetUuid,
     prefab_lookup: &mut HashMap<AssetUuid, Handle<PrefabAsset>>,
     prefab_cook_order: &mut Vec<AssetUuid>,
-) {
+) -> Result<(), String> {
     // Request the asset
     let load_handle = asset_manager.loader().add_ref(id);
     let handle = Handle::<PrefabAsset>::new(asset_manager.tx().clone(), load_handle);
 
-    // Block until it loads
+    // Block until it loads or fails
     loop {
         asset_manager.update();
-        if let LoadStatus::Loaded = handle.load_status::<RpcLoader>(asset_manager.loader()) {
-            break;
+        match asset_load_state(asset_manager, handle.load_handle()) {
+            (AssetLoadState::Loaded, _) => break,
+            // A failed reload leaves the previous version in storage, and that one can be cooked
+            (AssetLoadState::Failed, _) if asset_manager.storage().get(&handle).is_some() => break,
+            (AssetLoadState::Failed, error) => {
+                return Err(format!(
+                    "Failed to load prefab {}: {}",
+                    id,
+                    error.unwrap_or_default()
+                ));
+            }
+            (AssetLoadState::Pending, _) => {}
         }
     }
 
-    // Grab a reference to the asset
-    let prefab_asset: &PrefabAsset = handle.asset(asset_manager.storage()).unwrap();
+    // Grab a reference to the asset. An in-memory copy may reference different prefabs than the one
+    // in asset storage, so follow its references instead
+    let prefab_asset;
+    let prefab = match in_memory_prefabs.get(&id) {
+        Some(prefab) => &**prefab,
+        None => {
+            prefab_asset = asset_manager.storage().get(&handle).unwrap();
+            &prefab_asset.prefab
+        }
+    };
 
     // Get a list of prefabs this asset references. We clone these into a new list due to borrowing restrictions
-    let other_prefab_ids: Vec<_> = prefab_asset
-        .prefab
+    let other_prefab_ids: Vec<_> = prefab
         .prefab_meta
         .prefab_refs
         .iter()
@@ -201,14 +235,16 @@ fn request_prefab_dependency(
         if !prefab_lookup.contains_key(&other_prefab_id) {
             request_prefab_dependency(
                 asset_manager,
+                in_memory_prefabs,
                 other_prefab_id,
                 prefab_lookup,
                 prefab_cook_order,
-            );
+            )?;
         }
     }
 
     // Write data.. this needs to happen after we visit prefabs that we reference
     prefab_lookup.insert(id, handle);
     prefab_cook_order.push(id);
+    Ok(())
 }
@@ -1,13 +1,17 @@
-use atelier_loader::{handle::RefOp, rpc_loader::RpcLoader, Loader};
+use atelier_loader::{handle::RefOp, rpc_loader::RpcLoader};
 
+use std::net::SocketAddr;
+use std::path::Path;
 use std::sync::Arc;
 
+use crate::asset_loader::AssetLoader;
 use crate::asset_storage::GenericAssetStorage;
+use crate::packfile::PackfileLoader;
 
 use type_uuid::TypeUuid;
 
 pub struct AssetResource {
-    loader: RpcLoader,
+    loader: Box<dyn AssetLoader>,
     storage: GenericAssetStorage,
     tx: Arc<atelier_loader::crossbeam_channel::Sender<RefOp>>,
     rx: atelier_loader::crossbeam_channel::Receiver<RefOp>,
@@ -15,12 +19,30 @@ pub struct AssetResource {
 
 impl Default for AssetResource {
     fn default() -> Self {
+        Self::with_loader(Box::new(RpcLoader::default()))
+    }
+}
+
+impl AssetResource {
+    /// Creates an asset resource that loads from the asset daemon at the given address. The
+    /// connection is made in the background, assets are requested once it succeeds
+    pub fn new(daemon_address: SocketAddr) -> std::io::Result<Self> {
+        let loader = RpcLoader::new(daemon_address.to_string())?;
+        Ok(Self::with_loader(Box::new(loader)))
+    }
+
+    /// Creates an asset resource that loads from a packfile (see packfile::pack_assets). This
+    /// doesn't need the asset daemon or the source assets
+    pub fn from_packfile(path: &Path) -> std::io::Result<Self> {
+        let loader = PackfileLoader::open(path)?;
+        Ok(Self::with_loader(Box::new(loader)))
+    }
+
+    fn with_loader(loader: Box<dyn AssetLoader>) -> Self {
         let (tx, rx) = atelier_loader::crossbeam_channel::unbounded();
         let tx = Arc::new(tx);
         let storage = GenericAssetStorage::new(tx.clone());
 
-        let loader = RpcLoader::default();
-
         AssetResource {
             loader,
             storage,
@@ -28,22 +50,24 @@ impl Default for AssetResource {
             rx,
         }
     }
-}
 
-impl AssetResource {
-    pub fn add_storage<T: TypeUuid + for<'a> serde::Deserialize<'a> + 'static + Send>(&mut self) {
+    pub fn add_storage<T: TypeUuid + for<'a> serde::Deserialize<'a> + 'static + Send + Sync>(
+        &mut self
+    ) {
         self.storage.add_storage::<T>();
     }
 
     pub fn update(&mut self) {
-        atelier_loader::handle::process_ref_ops(&self.loader, &self.rx);
-        self.loader
-            .process(&self.storage)
-            .expect("failed to process loader");
+        self.loader.process_ref_ops(&self.rx);
+        // Failures of individual assets are recorded by the storage (see AssetStatusResource), so
+        // an error here shouldn't bring down the app
+        if let Err(e) = self.loader.process(&self.storage) {
+            log::error!("Failed to process asset loads: {}", e);
+        }
     }
 
-    pub fn loader(&self) -> &RpcLoader {
-        &self.loader
+    pub fn loader(&self) -> &dyn AssetLoader {
+        &*self.loader
     }
 
     pub fn storage(&self) -> &GenericAssetStorage {
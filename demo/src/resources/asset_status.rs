@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+
+use atelier_core::AssetUuid;
+use atelier_loader::{LoadHandle, LoadStatus};
+
+use crate::resources::AssetResource;
+use crate::resources::{find_asset_source_file, invalidate_asset_source_files};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AssetLoadState {
+    /// The asset has been requested but hasn't finished loading
+    Pending,
+
+    /// A version of the asset is loaded and can be used
+    Loaded,
+
+    /// The most recent load failed. If an earlier version was loaded, it is still in use
+    Failed,
+}
+
+/// Returns the load state of the asset, and why it failed if it did. This is what
+/// AssetStatusResource tracks, but it can also be called while blocking on a load, since the
+/// resource is only refreshed once per frame
+pub fn asset_load_state(
+    asset_resource: &AssetResource,
+    load_handle: LoadHandle,
+) -> (AssetLoadState, Option<String>) {
+    let load_error = asset_resource.storage().load_error(load_handle);
+    match (
+        asset_resource.loader().get_load_status(load_handle),
+        load_error,
+    ) {
+        (_, Some(load_error)) => (AssetLoadState::Failed, Some(load_error.error)),
+        (LoadStatus::Loaded, None) => (AssetLoadState::Loaded, None),
+        (LoadStatus::DoesNotExist, None) => (
+            AssetLoadState::Failed,
+            Some("The asset daemon doesn't have an asset with this ID".to_string()),
+        ),
+        (_, None) => (AssetLoadState::Pending, None),
+    }
+}
+
+/// The load state of a single requested asset
+#[derive(Clone, Debug)]
+pub struct AssetStatus {
+    pub load_handle: LoadHandle,
+    pub state: AssetLoadState,
+
+    /// The number of handles that are keeping the asset loaded
+    pub ref_count: u32,
+
+    /// Why the asset failed to load. Only set if the state is Failed
+    pub error: Option<String>,
+
+    /// The file the asset was imported from, if it could be found
+    pub source_file_name: Option<String>,
+}
+
+/// Tracks the load state of every asset that has been requested, keyed by asset ID. This is
+/// refreshed from the loader and asset storage once per frame (see update_asset_manager)
+#[derive(Default)]
+pub struct AssetStatusResource {
+    assets: HashMap<AssetUuid, AssetStatus>,
+}
+
+impl AssetStatusResource {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn assets(&self) -> &HashMap<AssetUuid, AssetStatus> {
+        &self.assets
+    }
+
+    pub fn get(
+        &self,
+        asset_id: &AssetUuid,
+    ) -> Option<&AssetStatus> {
+        self.assets.get(asset_id)
+    }
+
+    /// Returns the number of assets in the given state
+    pub fn count(
+        &self,
+        state: AssetLoadState,
+    ) -> usize {
+        self.assets
+            .values()
+            .filter(|status| status.state == state)
+            .count()
+    }
+
+    pub fn update(
+        &mut self,
+        asset_resource: &AssetResource,
+    ) {
+        let loader = asset_resource.loader();
+
+        let mut assets = HashMap::new();
+        for load_handle in loader.get_active_loads() {
+            let asset_id = match loader.asset_id(load_handle) {
+                Some(asset_id) => asset_id,
+                None => continue,
+            };
+
+            let (state, error) = asset_load_state(asset_resource, load_handle);
+
+            // The source file is looked up below, and only for assets that haven't been seen before
+            let source_file_name = self
+                .assets
+                .get(&asset_id)
+                .and_then(|status| status.source_file_name.clone());
+
+            assets.insert(
+                asset_id,
+                AssetStatus {
+                    load_handle,
+                    state,
+                    ref_count: loader.ref_count(load_handle),
+                    error,
+                    source_file_name,
+                },
+            );
+        }
+
+        // When assets are added, removed or change state, the asset daemon has probably imported
+        // something, so the source files that were found before may be out of date
+        let status_changed = assets.len() != self.assets.len()
+            || assets.iter().any(|(asset_id, status)| {
+                self.assets
+                    .get(asset_id)
+                    .map(|previous_status| previous_status.state != status.state)
+                    .unwrap_or(true)
+            });
+
+        if status_changed {
+            invalidate_asset_source_files();
+
+            for (asset_id, status) in &mut assets {
+                if !self.assets.contains_key(asset_id) {
+                    status.source_file_name = find_asset_source_file(asset_id)
+                        .map(|asset_source_file| asset_source_file.name());
+                }
+            }
+        }
+
+        self.assets = assets;
+    }
+}
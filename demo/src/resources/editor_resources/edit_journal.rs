@@ -0,0 +1,187 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use atelier_core::AssetUuid;
+use legion_transaction::TransactionDiffs;
+
+/// Journals are written to this directory, one file per prefab
+const JOURNAL_DIRECTORY: &str = ".editor_journal";
+
+/// How often pending journal changes are flushed to disk
+const JOURNAL_WRITE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// A single committed change, stored in the order it was applied to the prefab
+#[derive(Serialize, Deserialize)]
+pub struct JournalStep {
+    pub description: String,
+    pub diffs: TransactionDiffs,
+}
+
+/// The on-disk representation of a journal
+#[derive(Serialize, Deserialize)]
+struct JournalFile {
+    prefab_uuid: AssetUuid,
+    steps: Vec<JournalStep>,
+}
+
+/// Records every change applied to the opened prefab since it was last loaded or saved. This is
+/// periodically written to disk so that unsaved edits can be recovered if the editor exits without
+/// saving. Undo and redo are recorded as steps too, so replaying the steps in order always
+/// produces the current state.
+pub struct EditJournal {
+    prefab_uuid: Option<AssetUuid>,
+    steps: Vec<JournalStep>,
+    dirty: bool,
+    last_write: Instant,
+}
+
+impl EditJournal {
+    pub fn new() -> Self {
+        EditJournal {
+            prefab_uuid: None,
+            steps: Default::default(),
+            dirty: false,
+            last_write: Instant::now(),
+        }
+    }
+
+    fn journal_path(prefab_uuid: &AssetUuid) -> PathBuf {
+        PathBuf::from(JOURNAL_DIRECTORY)
+            .join(format!("{}.journal", uuid::Uuid::from_bytes(prefab_uuid.0)))
+    }
+
+    /// Starts a new, empty journal for the given prefab. Anything already on disk for the prefab
+    /// is left alone until the next write or clear.
+    pub fn begin(
+        &mut self,
+        prefab_uuid: AssetUuid,
+    ) {
+        self.prefab_uuid = Some(prefab_uuid);
+        self.steps.clear();
+        self.dirty = false;
+    }
+
+    pub fn record(
+        &mut self,
+        description: String,
+        diffs: TransactionDiffs,
+    ) {
+        if self.prefab_uuid.is_none() {
+            return;
+        }
+
+        self.steps.push(JournalStep { description, diffs });
+        self.dirty = true;
+    }
+
+    /// Writes the journal to disk if there are unwritten changes and enough time has passed since
+    /// the last write
+    pub fn write_if_due(&mut self) {
+        if self.dirty && self.last_write.elapsed() >= JOURNAL_WRITE_INTERVAL {
+            self.write();
+        }
+    }
+
+    pub fn write(&mut self) {
+        let prefab_uuid = match self.prefab_uuid {
+            Some(prefab_uuid) => prefab_uuid,
+            None => return,
+        };
+
+        self.dirty = false;
+        self.last_write = Instant::now();
+
+        let journal_file = JournalFile {
+            prefab_uuid,
+            steps: std::mem::replace(&mut self.steps, vec![]),
+        };
+
+        let result = bincode::serialize(&journal_file);
+        self.steps = journal_file.steps;
+
+        let data = match result {
+            Ok(data) => data,
+            Err(e) => {
+                log::warn!("Failed to serialize edit journal: {}", e);
+                return;
+            }
+        };
+
+        // Write to a temporary file first so that a crash mid-write doesn't corrupt the journal
+        let path = Self::journal_path(&prefab_uuid);
+        let tmp_path = path.with_extension("journal.tmp");
+        let result = std::fs::create_dir_all(JOURNAL_DIRECTORY)
+            .and_then(|_| std::fs::write(&tmp_path, data))
+            .and_then(|_| std::fs::rename(&tmp_path, &path));
+
+        match result {
+            Ok(_) => log::info!(
+                "Wrote edit journal with {} steps to {:?}",
+                self.steps.len(),
+                path
+            ),
+            Err(e) => log::warn!("Failed to write edit journal to {:?}: {}", path, e),
+        }
+    }
+
+    /// Drops all recorded steps and deletes the journal from disk. Called when the prefab is saved
+    /// or the user declines to recover a journal
+    pub fn clear(&mut self) {
+        self.steps.clear();
+        self.dirty = false;
+
+        if let Some(prefab_uuid) = &self.prefab_uuid {
+            let path = Self::journal_path(prefab_uuid);
+            if path.exists() {
+                if let Err(e) = std::fs::remove_file(&path) {
+                    log::warn!("Failed to remove edit journal {:?}: {}", path, e);
+                }
+            }
+        }
+    }
+
+    /// Looks for a journal for the given prefab that was written after the prefab's source file
+    /// was last modified. Journals that are older than the source file are stale and are deleted.
+    pub fn find_recoverable(
+        prefab_uuid: &AssetUuid,
+        source_path: &Path,
+    ) -> Option<Vec<JournalStep>> {
+        let path = Self::journal_path(prefab_uuid);
+        let journal_modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+
+        // If the source file can't be read, treat the journal as newer
+        let source_modified = std::fs::metadata(source_path)
+            .and_then(|m| m.modified())
+            .ok();
+        if let Some(source_modified) = source_modified {
+            if source_modified >= journal_modified {
+                log::info!("Removing stale edit journal {:?}", path);
+                std::fs::remove_file(&path).ok();
+                return None;
+            }
+        }
+
+        let data = match std::fs::read(&path) {
+            Ok(data) => data,
+            Err(e) => {
+                log::warn!("Failed to read edit journal {:?}: {}", path, e);
+                return None;
+            }
+        };
+
+        match bincode::deserialize::<JournalFile>(&data) {
+            Ok(journal_file) => {
+                if journal_file.prefab_uuid != *prefab_uuid || journal_file.steps.is_empty() {
+                    return None;
+                }
+
+                Some(journal_file.steps)
+            }
+            Err(e) => {
+                log::warn!("Failed to deserialize edit journal {:?}: {}", path, e);
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,480 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use skulpin::app::VirtualKeyCode;
+
+use crate::resources::{EditorStateResource, EditorTool, InputResource};
+
+/// Keybindings are loaded from and saved to this file. Any action that isn't listed in the file
+/// gets its default binding
+const KEYBINDINGS_PATH: &str = "editor_keybindings.ron";
+
+/// Everything in the editor that can be triggered by a key. Editor ops that need more information
+/// than a key press can provide (like which prefab to open) are triggered from the UI instead.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum EditorAction {
+    TranslateTool,
+    ScaleTool,
+    RotateTool,
+    Save,
+    CloseDocument,
+    NextDocument,
+    PreviousDocument,
+    Play,
+    Pause,
+    TogglePause,
+    Reset,
+    KeepChangesFromPlay,
+    Undo,
+    Redo,
+    ConfirmDiscardChanges,
+    CancelDiscardChanges,
+    ApplyMerge,
+    AbortMerge,
+    RecoverJournal,
+    DiscardJournal,
+    CommandPalette,
+    Quit,
+}
+
+impl EditorAction {
+    /// All actions, in the order they are shown in the UI
+    pub const ALL: [EditorAction; 22] = [
+        EditorAction::TranslateTool,
+        EditorAction::ScaleTool,
+        EditorAction::RotateTool,
+        EditorAction::Save,
+        EditorAction::CloseDocument,
+        EditorAction::NextDocument,
+        EditorAction::PreviousDocument,
+        EditorAction::Play,
+        EditorAction::Pause,
+        EditorAction::TogglePause,
+        EditorAction::Reset,
+        EditorAction::KeepChangesFromPlay,
+        EditorAction::Undo,
+        EditorAction::Redo,
+        EditorAction::ConfirmDiscardChanges,
+        EditorAction::CancelDiscardChanges,
+        EditorAction::ApplyMerge,
+        EditorAction::AbortMerge,
+        EditorAction::RecoverJournal,
+        EditorAction::DiscardJournal,
+        EditorAction::CommandPalette,
+        EditorAction::Quit,
+    ];
+
+    /// A human-readable name for the action
+    pub fn name(self) -> &'static str {
+        match self {
+            EditorAction::TranslateTool => "Translate Tool",
+            EditorAction::ScaleTool => "Scale Tool",
+            EditorAction::RotateTool => "Rotate Tool",
+            EditorAction::Save => "Save",
+            EditorAction::CloseDocument => "Close Document",
+            EditorAction::NextDocument => "Next Document",
+            EditorAction::PreviousDocument => "Previous Document",
+            EditorAction::Play => "Play",
+            EditorAction::Pause => "Pause",
+            EditorAction::TogglePause => "Toggle Pause",
+            EditorAction::Reset => "Reset",
+            EditorAction::KeepChangesFromPlay => "Keep Changes From Play",
+            EditorAction::Undo => "Undo",
+            EditorAction::Redo => "Redo",
+            EditorAction::ConfirmDiscardChanges => "Confirm Discard Changes",
+            EditorAction::CancelDiscardChanges => "Cancel Discard Changes",
+            EditorAction::ApplyMerge => "Apply Merge",
+            EditorAction::AbortMerge => "Abort Merge",
+            EditorAction::RecoverJournal => "Recover Unsaved Changes",
+            EditorAction::DiscardJournal => "Discard Recoverable Changes",
+            EditorAction::CommandPalette => "Command Palette",
+            EditorAction::Quit => "Quit",
+        }
+    }
+
+    /// Queues up the editor op(s) for this action
+    pub fn enqueue(
+        self,
+        editor_state: &mut EditorStateResource,
+    ) {
+        match self {
+            EditorAction::TranslateTool => {
+                editor_state.enqueue_set_active_editor_tool(EditorTool::Translate)
+            }
+            EditorAction::ScaleTool => {
+                editor_state.enqueue_set_active_editor_tool(EditorTool::Scale)
+            }
+            EditorAction::RotateTool => {
+                editor_state.enqueue_set_active_editor_tool(EditorTool::Rotate)
+            }
+            EditorAction::Save => editor_state.enqueue_save_prefab(),
+            EditorAction::CloseDocument => {
+                if let Some(active_document) = editor_state.active_document() {
+                    editor_state.enqueue_close_document(active_document);
+                }
+            }
+            EditorAction::NextDocument => enqueue_switch_document_by_offset(editor_state, 1),
+            EditorAction::PreviousDocument => enqueue_switch_document_by_offset(editor_state, -1),
+            EditorAction::Play => editor_state.enqueue_play(),
+            EditorAction::Pause => editor_state.enqueue_pause(),
+            EditorAction::TogglePause => editor_state.enqueue_toggle_pause(),
+            EditorAction::Reset => editor_state.enqueue_reset(),
+            EditorAction::KeepChangesFromPlay => editor_state.enqueue_keep_changes_from_play(),
+            EditorAction::Undo => editor_state.enqueue_undo(),
+            EditorAction::Redo => editor_state.enqueue_redo(),
+            EditorAction::ConfirmDiscardChanges => editor_state.enqueue_confirm_discard_changes(),
+            EditorAction::CancelDiscardChanges => editor_state.enqueue_cancel_discard_changes(),
+            EditorAction::ApplyMerge => editor_state.enqueue_apply_merge(),
+            EditorAction::AbortMerge => editor_state.enqueue_abort_merge(),
+            EditorAction::RecoverJournal => editor_state.enqueue_recover_journal(),
+            EditorAction::DiscardJournal => editor_state.enqueue_discard_journal(),
+            EditorAction::CommandPalette => editor_state.toggle_command_palette(),
+            EditorAction::Quit => editor_state.enqueue_quit(),
+        }
+    }
+}
+
+// Switches to the document before/after the active one, wrapping around at the ends
+fn enqueue_switch_document_by_offset(
+    editor_state: &mut EditorStateResource,
+    offset: isize,
+) {
+    let document_count = editor_state.open_documents().len() as isize;
+    if document_count == 0 {
+        return;
+    }
+
+    let active_index = editor_state
+        .open_documents()
+        .iter()
+        .position(|document| Some(document.uuid()) == editor_state.active_document())
+        .unwrap_or(0) as isize;
+
+    let index = (active_index + offset).rem_euclid(document_count) as usize;
+    let uuid = editor_state.open_documents()[index].uuid();
+    editor_state.enqueue_switch_document(uuid);
+}
+
+/// Modifier keys that must be held for a key chord. Left and right modifiers are treated the same
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct KeyModifiers {
+    #[serde(default)]
+    pub ctrl: bool,
+    #[serde(default)]
+    pub shift: bool,
+    #[serde(default)]
+    pub alt: bool,
+}
+
+impl KeyModifiers {
+    pub const NONE: KeyModifiers = KeyModifiers {
+        ctrl: false,
+        shift: false,
+        alt: false,
+    };
+
+    pub const CTRL: KeyModifiers = KeyModifiers {
+        ctrl: true,
+        shift: false,
+        alt: false,
+    };
+
+    pub const CTRL_SHIFT: KeyModifiers = KeyModifiers {
+        ctrl: true,
+        shift: true,
+        alt: false,
+    };
+
+    /// The modifiers that are currently held down
+    pub fn current(input: &InputResource) -> Self {
+        KeyModifiers {
+            ctrl: input.is_key_down(VirtualKeyCode::LControl)
+                || input.is_key_down(VirtualKeyCode::RControl),
+            shift: input.is_key_down(VirtualKeyCode::LShift)
+                || input.is_key_down(VirtualKeyCode::RShift),
+            alt: input.is_key_down(VirtualKeyCode::LAlt) || input.is_key_down(VirtualKeyCode::RAlt),
+        }
+    }
+
+    /// Returns true if the key is one of the modifiers. These can't be bound on their own
+    pub fn is_modifier_key(key: VirtualKeyCode) -> bool {
+        match key {
+            VirtualKeyCode::LControl
+            | VirtualKeyCode::RControl
+            | VirtualKeyCode::LShift
+            | VirtualKeyCode::RShift
+            | VirtualKeyCode::LAlt
+            | VirtualKeyCode::RAlt
+            | VirtualKeyCode::LWin
+            | VirtualKeyCode::RWin => true,
+            _ => false,
+        }
+    }
+}
+
+/// A key plus the modifiers that must be held with it
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct KeyChord {
+    pub key: VirtualKeyCode,
+    #[serde(default)]
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyChord {
+    pub fn new(
+        key: VirtualKeyCode,
+        modifiers: KeyModifiers,
+    ) -> Self {
+        KeyChord { key, modifiers }
+    }
+
+    /// Returns true if the key went down this frame with exactly these modifiers held. Requiring
+    /// an exact match keeps Ctrl+Z from also triggering Ctrl+Shift+Z (and vice versa)
+    pub fn is_just_pressed(
+        &self,
+        input: &InputResource,
+    ) -> bool {
+        input.is_key_just_down(self.key) && KeyModifiers::current(input) == self.modifiers
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter,
+    ) -> fmt::Result {
+        if self.modifiers.ctrl {
+            write!(f, "Ctrl+")?;
+        }
+
+        if self.modifiers.shift {
+            write!(f, "Shift+")?;
+        }
+
+        if self.modifiers.alt {
+            write!(f, "Alt+")?;
+        }
+
+        write!(f, "{:?}", self.key)
+    }
+}
+
+/// The on-disk representation of the keybindings. An action mapped to None is explicitly unbound.
+#[derive(Serialize, Deserialize, Default)]
+struct KeybindingsFile {
+    bindings: BTreeMap<EditorAction, Option<KeyChord>>,
+}
+
+/// Maps editor actions to the key chords that trigger them
+pub struct EditorKeybindingsResource {
+    bindings: BTreeMap<EditorAction, Option<KeyChord>>,
+
+    // If set, the next key chord that is pressed will be bound to this action
+    rebinding_action: Option<EditorAction>,
+}
+
+impl EditorKeybindingsResource {
+    /// Loads the keybindings from disk, falling back to the defaults for anything that isn't
+    /// listed or if the file can't be read
+    pub fn load() -> Self {
+        let mut bindings = Self::default_bindings();
+
+        match std::fs::read_to_string(KEYBINDINGS_PATH) {
+            Ok(contents) => match ron::de::from_str::<KeybindingsFile>(&contents) {
+                Ok(keybindings_file) => bindings.extend(keybindings_file.bindings),
+                Err(e) => log::warn!("Failed to parse keybindings {}: {}", KEYBINDINGS_PATH, e),
+            },
+            Err(e) => log::info!(
+                "Could not read keybindings {}, using defaults: {}",
+                KEYBINDINGS_PATH,
+                e
+            ),
+        }
+
+        EditorKeybindingsResource {
+            bindings,
+            rebinding_action: None,
+        }
+    }
+
+    /// Writes the keybindings to disk
+    pub fn save(&self) {
+        let keybindings_file = KeybindingsFile {
+            bindings: self.bindings.clone(),
+        };
+
+        let result = ron::ser::to_string_pretty(&keybindings_file, Default::default())
+            .map_err(|e| e.to_string())
+            .and_then(|output| std::fs::write(KEYBINDINGS_PATH, output).map_err(|e| e.to_string()));
+
+        if let Err(e) = result {
+            log::warn!("Failed to write keybindings {}: {}", KEYBINDINGS_PATH, e);
+        }
+    }
+
+    fn default_bindings() -> BTreeMap<EditorAction, Option<KeyChord>> {
+        let mut bindings: BTreeMap<_, _> = EditorAction::ALL
+            .iter()
+            .map(|action| (*action, None))
+            .collect();
+
+        let mut bind = |action, key, modifiers| {
+            bindings.insert(action, Some(KeyChord::new(key, modifiers)));
+        };
+
+        bind(
+            EditorAction::TranslateTool,
+            VirtualKeyCode::Key1,
+            KeyModifiers::NONE,
+        );
+        bind(
+            EditorAction::ScaleTool,
+            VirtualKeyCode::Key2,
+            KeyModifiers::NONE,
+        );
+        bind(
+            EditorAction::RotateTool,
+            VirtualKeyCode::Key3,
+            KeyModifiers::NONE,
+        );
+        bind(EditorAction::Save, VirtualKeyCode::S, KeyModifiers::CTRL);
+        bind(
+            EditorAction::CloseDocument,
+            VirtualKeyCode::W,
+            KeyModifiers::CTRL,
+        );
+        bind(
+            EditorAction::NextDocument,
+            VirtualKeyCode::Tab,
+            KeyModifiers::CTRL,
+        );
+        bind(
+            EditorAction::PreviousDocument,
+            VirtualKeyCode::Tab,
+            KeyModifiers::CTRL_SHIFT,
+        );
+        bind(
+            EditorAction::TogglePause,
+            VirtualKeyCode::Space,
+            KeyModifiers::NONE,
+        );
+        bind(EditorAction::Reset, VirtualKeyCode::R, KeyModifiers::CTRL);
+        bind(
+            EditorAction::KeepChangesFromPlay,
+            VirtualKeyCode::K,
+            KeyModifiers::CTRL,
+        );
+        bind(EditorAction::Undo, VirtualKeyCode::Z, KeyModifiers::CTRL);
+        bind(
+            EditorAction::Redo,
+            VirtualKeyCode::Z,
+            KeyModifiers::CTRL_SHIFT,
+        );
+        bind(
+            EditorAction::CommandPalette,
+            VirtualKeyCode::P,
+            KeyModifiers::CTRL_SHIFT,
+        );
+        bind(
+            EditorAction::Quit,
+            VirtualKeyCode::Escape,
+            KeyModifiers::NONE,
+        );
+
+        bindings
+    }
+
+    pub fn binding(
+        &self,
+        action: EditorAction,
+    ) -> Option<KeyChord> {
+        self.bindings.get(&action).cloned().flatten()
+    }
+
+    /// Binds the action to the given key chord, or unbinds it if None. Any other action that was
+    /// bound to the same chord is unbound.
+    pub fn set_binding(
+        &mut self,
+        action: EditorAction,
+        key_chord: Option<KeyChord>,
+    ) {
+        if key_chord.is_some() {
+            for binding in self.bindings.values_mut() {
+                if *binding == key_chord {
+                    *binding = None;
+                }
+            }
+        }
+
+        self.bindings.insert(action, key_chord);
+        self.save();
+    }
+
+    pub fn reset_to_defaults(&mut self) {
+        self.bindings = Self::default_bindings();
+        self.rebinding_action = None;
+        self.save();
+    }
+
+    pub fn rebinding_action(&self) -> Option<EditorAction> {
+        self.rebinding_action
+    }
+
+    /// The next key chord that is pressed will be bound to the given action
+    pub fn begin_rebinding(
+        &mut self,
+        action: EditorAction,
+    ) {
+        self.rebinding_action = Some(action);
+    }
+
+    pub fn cancel_rebinding(&mut self) {
+        self.rebinding_action = None;
+    }
+
+    /// If waiting for a key chord to rebind an action, bind the first non-modifier key that went
+    /// down this frame. Escape cancels the rebind instead, so it can't be bound this way. Returns
+    /// true if a rebind is in progress, in which case the key press should not trigger any actions.
+    pub fn update_rebinding(
+        &mut self,
+        input: &InputResource,
+    ) -> bool {
+        let action = match self.rebinding_action {
+            Some(action) => action,
+            None => return false,
+        };
+
+        let key = input
+            .keys_just_down()
+            .iter()
+            .find(|key| !KeyModifiers::is_modifier_key(**key))
+            .cloned();
+
+        if key == Some(VirtualKeyCode::Escape) {
+            log::info!("Cancelled rebinding {}", action.name());
+            self.rebinding_action = None;
+        } else if let Some(key) = key {
+            let key_chord = KeyChord::new(key, KeyModifiers::current(input));
+            log::info!("Binding {} to {}", action.name(), key_chord);
+            self.rebinding_action = None;
+            self.set_binding(action, Some(key_chord));
+        }
+
+        true
+    }
+
+    /// Returns all actions whose key chord was pressed this frame
+    pub fn just_pressed_actions(
+        &self,
+        input: &InputResource,
+    ) -> Vec<EditorAction> {
+        self.bindings
+            .iter()
+            .filter_map(|(action, key_chord)| match key_chord {
+                Some(key_chord) if key_chord.is_just_pressed(input) => Some(*action),
+                _ => None,
+            })
+            .collect()
+    }
+}
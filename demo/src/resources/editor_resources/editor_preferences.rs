@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+
+use atelier_core::AssetUuid;
+use serde::{Deserialize, Serialize};
+
+use super::editor_state::{
+    DrawLayerSettings, EditorTool, GizmoPivotMode, GizmoSpace, SnapSettings, WindowOptions,
+};
+
+/// Preferences are loaded from this file at startup and written to it on exit. Window positions
+/// and sizes are persisted separately by imgui (see init_imgui)
+const PREFERENCES_PATH: &str = "editor_preferences.ron";
+
+/// Where the camera was looking when a prefab was last edited
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct CameraPreferences {
+    pub position: glam::Vec2,
+    pub x_half_extents: f32,
+}
+
+/// Editor settings that are kept between sessions. Anything missing from the file gets its
+/// default value so that older files still load.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct EditorPreferences {
+    pub window_options_editing: WindowOptions,
+    pub window_options_running: WindowOptions,
+    pub active_editor_tool: EditorTool,
+    pub gizmo_pivot_mode: GizmoPivotMode,
+    pub gizmo_space: GizmoSpace,
+    pub snap_settings: SnapSettings,
+    pub draw_layer_settings: DrawLayerSettings,
+
+    /// The prefab that was active when the editor last exited
+    pub last_opened_prefab: Option<AssetUuid>,
+
+    /// The camera for every prefab that has been edited, so that reopening a prefab looks at the
+    /// same place
+    pub cameras: HashMap<AssetUuid, CameraPreferences>,
+}
+
+impl Default for EditorPreferences {
+    fn default() -> Self {
+        EditorPreferences {
+            window_options_editing: WindowOptions::new_editing(),
+            window_options_running: WindowOptions::new_runtime(),
+            active_editor_tool: EditorTool::Translate,
+            gizmo_pivot_mode: GizmoPivotMode::IndividualOrigins,
+            gizmo_space: GizmoSpace::World,
+            snap_settings: SnapSettings::new(),
+            draw_layer_settings: Default::default(),
+            last_opened_prefab: None,
+            cameras: Default::default(),
+        }
+    }
+}
+
+impl EditorPreferences {
+    /// Loads preferences from disk, or returns the defaults if they can't be read
+    pub fn load() -> Self {
+        let contents = match std::fs::read_to_string(PREFERENCES_PATH) {
+            Ok(contents) => contents,
+            Err(e) => {
+                log::info!(
+                    "Could not read editor preferences {}, using defaults: {}",
+                    PREFERENCES_PATH,
+                    e
+                );
+                return Default::default();
+            }
+        };
+
+        match ron::de::from_str(&contents) {
+            Ok(preferences) => preferences,
+            Err(e) => {
+                log::warn!(
+                    "Failed to parse editor preferences {}: {}",
+                    PREFERENCES_PATH,
+                    e
+                );
+                Default::default()
+            }
+        }
+    }
+
+    pub fn save(&self) {
+        let result = ron::ser::to_string_pretty(self, Default::default())
+            .map_err(|e| e.to_string())
+            .and_then(|output| std::fs::write(PREFERENCES_PATH, output).map_err(|e| e.to_string()));
+
+        match result {
+            Ok(_) => log::info!("Wrote editor preferences to {}", PREFERENCES_PATH),
+            Err(e) => log::warn!(
+                "Failed to write editor preferences {}: {}",
+                PREFERENCES_PATH,
+                e
+            ),
+        }
+    }
+}
@@ -23,6 +23,9 @@ pub struct EditorSelectionResource {
     // These are entities in the world
     selected_entities: HashSet<Entity>,
 
+    // The most recently selected entity, used as a pivot for some gizmo operations
+    active_entity: Option<Entity>,
+
     pending_selection_ops: Vec<SelectionOp>,
 }
 
@@ -38,6 +41,7 @@ impl EditorSelectionResource {
             registry: Arc::new(registry),
             editor_selection_world,
             selected_entities: Default::default(),
+            active_entity: None,
             pending_selection_ops: Default::default(),
         }
     }
@@ -70,6 +74,29 @@ impl EditorSelectionResource {
         Self::get_entity_aabbs(&self.selected_entities, &mut self.editor_selection_world)
     }
 
+    /// Returns the AABB that contains all selected entities, or None if none of the selected
+    /// entities have a shape in the selection world
+    pub fn selection_aabb(&mut self) -> Option<AABB<f32>> {
+        use ncollide2d::bounding_volume::BoundingVolume;
+        let mut selection_aabb: Option<AABB<f32>> = None;
+        for (_, aabb) in self.selected_entity_aabbs() {
+            if let Some(aabb) = aabb {
+                if let Some(selection_aabb) = &mut selection_aabb {
+                    selection_aabb.merge(&aabb);
+                } else {
+                    selection_aabb = Some(aabb);
+                }
+            }
+        }
+
+        selection_aabb
+    }
+
+    /// The most recently selected entity, if it is still selected
+    pub fn active_entity(&self) -> Option<Entity> {
+        self.active_entity
+    }
+
     pub fn enqueue_add_to_selection(
         &mut self,
         entities: Vec<Entity>,
@@ -123,6 +150,7 @@ impl EditorSelectionResource {
                     let mut changed = false;
                     for e in entities {
                         changed |= self.selected_entities.insert(e);
+                        self.active_entity = Some(e);
                     }
 
                     changed
@@ -136,6 +164,7 @@ impl EditorSelectionResource {
                     changed
                 }
                 SelectionOp::Clear => {
+                    self.active_entity = None;
                     if self.selected_entities.len() > 0 {
                         self.selected_entities.clear();
                         true
@@ -145,10 +174,27 @@ impl EditorSelectionResource {
                 }
                 SelectionOp::Set(entities) => {
                     self.selected_entities = entities.iter().map(|x| *x).collect();
+                    self.active_entity = entities.last().copied();
                     true
                 }
             }
         }
+
+        // Entities on hidden or locked layers can't be selected. This also drops entities that were
+        // selected before their layer was hidden or locked, so that gizmos and deletes don't
+        // affect them
+        let draw_layer_settings = editor_state.draw_layer_settings();
+        let selection_count = self.selected_entities.len();
+        self.selected_entities
+            .retain(|entity| draw_layer_settings.is_entity_selectable(world, *entity));
+        changed |= self.selected_entities.len() != selection_count;
+
+        // Don't allow the active entity to refer to something that is no longer selected
+        if let Some(active_entity) = self.active_entity {
+            if !self.selected_entities.contains(&active_entity) {
+                self.active_entity = self.selected_entities.iter().next().copied();
+            }
+        }
     }
 
     // The main reason for having such a specific function here is that it's awkward for an external
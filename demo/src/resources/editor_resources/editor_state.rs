@@ -1,13 +1,22 @@
 use std::collections::{HashSet, HashMap, VecDeque};
 use legion::prelude::*;
 use legion::storage::ComponentTypeId;
-use crate::resources::{TimeResource, AssetResource, UniverseResource, EditorSelectionResource};
+use crate::resources::{
+    TimeResource, AssetResource, UniverseResource, EditorSelectionResource, InputResource,
+    PhysicsResource,
+};
+use crate::resources::{asset_load_state, AssetLoadState};
 use crate::resources::SimulationTimePauseReason;
+use crate::resources::AppControlResource;
+use crate::resources::CameraResource;
 use atelier_core::AssetUuid;
 use legion_prefab::{CookedPrefab, ComponentRegistration, Prefab};
 use std::sync::Arc;
 use crate::resources::time::TimeState;
-use atelier_loader::handle::{TypedAssetStorage, AssetHandle};
+use atelier_loader::handle::AssetHandle;
+use atelier_loader::crossbeam_channel::Receiver;
+use atelier_loader::LoadHandle;
+use crate::asset_storage::AssetVersionChanged;
 use crate::pipeline::PrefabAsset;
 use legion_transaction::{ComponentDiff, apply_diff_to_prefab, WorldDiff};
 use prefab_format::{ComponentTypeUuid, EntityUuid};
@@ -16,6 +25,18 @@ use std::collections::vec_deque;
 use legion_transaction::CopyCloneImpl;
 use legion_transaction::{TransactionBuilder, TransactionDiffs, TransactionEntityInfo, Transaction};
 use imgui::ImString;
+use skulpin::app::VirtualKeyCode;
+use crate::spawn_mappings::ComponentSpawnMappings;
+use crate::editor_commands::EditorCommand;
+use crate::components::{DrawLayer, DrawOrderComponent};
+use super::edit_journal::{EditJournal, JournalStep};
+use super::EditorShapeDragState;
+use super::prefab_merge::{self, ComponentConflict, MergeResolution};
+use super::prefab_source_files;
+use super::editor_preferences::{EditorPreferences, CameraPreferences};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Copy)]
 pub enum PostCommitSelection {
@@ -29,13 +50,21 @@ pub enum PostCommitSelection {
 /// Operations that can be performed in the editor. These get queued up to be executed later at a
 /// single place in the frame in FIFO order
 enum EditorOp {
-    /// Clear the world and load the given prefab into it
+    /// Open the given prefab in a new document and make it active. If it's already open, its
+    /// document is made active instead
     OpenPrefab(AssetUuid),
 
+    /// Make the document for the given prefab active, spawning it into the world
+    SwitchDocument(AssetUuid),
+
+    /// Close the document for the given prefab
+    CloseDocument(AssetUuid),
+
     /// Save the current pre-play state to the currently open prefab file
     SavePrefab,
 
-    /// Unpauses the simulation, allowing in-editor testing
+    /// Unpauses the simulation, allowing in-editor testing. If not already playing, the edit world
+    /// is set aside and cloned into a separate play world, which is dropped on Reset
     Play,
 
     /// Pause the simulation
@@ -47,23 +76,140 @@ enum EditorOp {
     /// Pause the simulation and revert everything back to pre-play state
     Reset,
 
+    /// Copy the selected entities' component values from the play world back into the prefab
+    /// (see create_keep_changes_registry)
+    KeepChangesFromPlay,
+
     /// Undo the previous change
     Undo,
 
     /// Redo a change that was previously undone
     Redo,
 
+    /// Undo or redo until the undo chain is at the given position
+    JumpToUndoPosition(usize),
+
+    /// Close the editor
+    Quit,
+
+    /// Perform the action that is waiting for confirmation to discard unsaved changes
+    ConfirmDiscardChanges,
+
+    /// Cancel the action that is waiting for confirmation to discard unsaved changes
+    CancelDiscardChanges,
+
+    /// Save the active document, then perform the action that is waiting for confirmation. If the
+    /// save fails, the action keeps waiting so that the changes aren't lost
+    SaveAndConfirmDiscardChanges,
+
+    /// Merge the file on disk with the editor's changes using the chosen conflict resolutions
+    ApplyMerge,
+
+    /// Keep the editor's changes and ignore the file on disk
+    AbortMerge,
+
+    /// Replay the edit journal that was found when the prefab was opened
+    RecoverJournal,
+
+    /// Delete the edit journal that was found when the prefab was opened
+    DiscardJournal,
+
     /// Sets the current editor tool (translate, scale, etc.)
     SetActiveEditorTool(EditorTool),
 }
 
+/// External changes to the opened prefab that conflict with changes made in the editor. This is
+/// held until the user picks how to resolve each conflict
+pub struct PendingMerge {
+    /// The version of the prefab on disk that is being merged
+    version: u32,
+
+    /// The components that were changed both on disk and in the editor
+    pub conflicts: Vec<ComponentConflict>,
+}
+
+/// An action that would discard unsaved changes. These are held until the user confirms them
+#[derive(Clone, Copy, PartialEq)]
+pub enum DiscardChangesAction {
+    /// Close the document for the given prefab
+    CloseDocument(AssetUuid),
+
+    /// Reload the opened prefab because its source file changed on disk. Holds the version that
+    /// was detected
+    HotReload(u32),
+
+    /// Close the editor
+    Quit,
+}
+
+/// The editing state of an open document that isn't currently active. When a document is made
+/// active, this is moved back into the EditorStateResource and the prefab is spawned into the world.
+struct DocumentState {
+    opened_prefab: Arc<OpenedPrefabState>,
+    undo_chain: VecDeque<UndoStep>,
+    undo_chain_position: usize,
+    saved_undo_chain_position: Option<usize>,
+    edit_journal: EditJournal,
+    recoverable_journal_steps: Option<Vec<JournalStep>>,
+    declined_reload_version: Option<u32>,
+    selected_uuids: HashSet<EntityUuid>,
+    camera_position: glam::Vec2,
+    camera_x_half_extents: f32,
+}
+
+impl DocumentState {
+    fn has_unsaved_changes(&self) -> bool {
+        self.saved_undo_chain_position != Some(self.undo_chain_position)
+    }
+}
+
+/// While playing, the edit world and its physics are set aside here and a separate play world takes
+/// their place. Nothing that happens during play reaches them, and they are put back when play ends
+struct PlaySession {
+    edit_world: World,
+    edit_physics: PhysicsResource,
+
+    // The opened prefab's mappings to the edit world. While playing, the opened prefab is mapped to
+    // the play world instead
+    prefab_to_world_mappings: HashMap<Entity, Entity>,
+    world_to_prefab_mappings: HashMap<Entity, Entity>,
+}
+
+/// A prefab that is open for editing. Only one document is active (spawned into the world) at a
+/// time.
+pub struct OpenDocument {
+    uuid: AssetUuid,
+    name: String,
+
+    // None if this is the active document, in which case its state is held directly by
+    // EditorStateResource
+    parked_state: Option<DocumentState>,
+}
+
+impl OpenDocument {
+    pub fn uuid(&self) -> AssetUuid {
+        self.uuid
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
 /// Tracks which windows are open
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
 pub struct WindowOptions {
     pub show_imgui_metrics: bool,
     pub show_imgui_style_editor: bool,
     pub show_imgui_demo: bool,
     pub show_entity_list: bool,
     pub show_inspector: bool,
+    pub show_undo_history: bool,
+    pub show_documents: bool,
+    pub show_keybindings: bool,
+    pub show_assets: bool,
+    pub show_layers: bool,
 }
 
 impl WindowOptions {
@@ -74,6 +220,11 @@ impl WindowOptions {
             show_imgui_demo: false,
             show_entity_list: false,
             show_inspector: false,
+            show_undo_history: false,
+            show_documents: false,
+            show_keybindings: false,
+            show_assets: false,
+            show_layers: false,
         }
     }
 
@@ -81,6 +232,7 @@ impl WindowOptions {
         let mut options = Self::new();
         options.show_entity_list = true;
         options.show_inspector = true;
+        options.show_documents = true;
         options
     }
 
@@ -88,12 +240,319 @@ impl WindowOptions {
         let mut options = Self::new();
         options.show_entity_list = true;
         options.show_inspector = true;
+        options.show_documents = true;
         options
     }
 }
 
+impl Default for WindowOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// State of the command palette popup. The palette isn't a window, so it isn't part of
+/// WindowOptions
+pub struct CommandPaletteState {
+    open: bool,
+    search_text: ImString,
+    selected_index: usize,
+
+    // The commands listed in the palette. Some providers scan the disk, so they are only run when
+    // the palette is opened rather than every frame it's shown
+    commands: Option<Vec<EditorCommand>>,
+}
+
+impl Default for CommandPaletteState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CommandPaletteState {
+    pub fn new() -> Self {
+        CommandPaletteState {
+            open: false,
+            search_text: ImString::with_capacity(255),
+            selected_index: 0,
+            commands: None,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Opens the palette with an empty search and no cached commands
+    pub fn open(&mut self) {
+        self.open = true;
+        self.search_text.clear();
+        self.selected_index = 0;
+        self.commands = None;
+    }
+
+    pub fn close(&mut self) {
+        self.open = false;
+        self.commands = None;
+    }
+
+    pub fn search_text(&self) -> &ImString {
+        &self.search_text
+    }
+
+    pub fn search_text_mut(&mut self) -> &mut ImString {
+        &mut self.search_text
+    }
+
+    pub fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    pub fn set_selected_index(&mut self, selected_index: usize) {
+        self.selected_index = selected_index;
+    }
+
+    /// The commands gathered when the palette was opened, or None if they haven't been gathered yet
+    pub fn commands(&self) -> Option<&Vec<EditorCommand>> {
+        self.commands.as_ref()
+    }
+
+    pub fn set_commands(&mut self, commands: Vec<EditorCommand>) {
+        self.commands = Some(commands);
+    }
+}
+
+/// Grid and snapping settings used by the gizmos
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct SnapSettings {
+    /// If true, gizmo interactions snap to the steps below
+    pub snap_enabled: bool,
+
+    /// If true, the grid is drawn in the viewport
+    pub show_grid: bool,
+
+    /// World-space distance between grid lines. Translation snaps to this step
+    pub grid_step: f32,
+
+    /// Rotation snaps to this increment (in degrees)
+    pub rotate_step_degrees: f32,
+
+    /// Scale snaps to this increment
+    pub scale_step: f32,
+
+    /// Multiplier applied to mouse movement when the precision modifier is held
+    pub fine_adjustment_multiplier: f32,
+}
+
+impl Default for SnapSettings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SnapSettings {
+    pub fn new() -> Self {
+        SnapSettings {
+            snap_enabled: false,
+            show_grid: true,
+            grid_step: 0.25,
+            rotate_step_degrees: 15.0,
+            scale_step: 0.1,
+            fine_adjustment_multiplier: 0.1,
+        }
+    }
+
+    /// Determine the snapping behavior for this frame. Holding control inverts whether snapping is
+    /// enabled. Holding shift slows down mouse movement for fine adjustment.
+    pub fn resolve(
+        &self,
+        input_state: &InputResource,
+    ) -> ResolvedSnapSettings {
+        let invert_snap = input_state.is_key_down(VirtualKeyCode::LControl)
+            || input_state.is_key_down(VirtualKeyCode::RControl);
+        let fine_adjustment = input_state.is_key_down(VirtualKeyCode::LShift)
+            || input_state.is_key_down(VirtualKeyCode::RShift);
+
+        ResolvedSnapSettings {
+            snap_enabled: self.snap_enabled != invert_snap,
+            grid_step: self.grid_step,
+            rotate_step: self.rotate_step_degrees.to_radians(),
+            scale_step: self.scale_step,
+            input_multiplier: if fine_adjustment {
+                self.fine_adjustment_multiplier
+            } else {
+                1.0
+            },
+        }
+    }
+}
+
+/// Per-layer visibility and locking in the editor. Hidden layers aren't drawn while editing, and
+/// entities on hidden or locked layers can't be selected
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct DrawLayerSettings {
+    hidden_layers: HashSet<DrawLayer>,
+    locked_layers: HashSet<DrawLayer>,
+}
+
+impl DrawLayerSettings {
+    pub fn is_visible(
+        &self,
+        layer: DrawLayer,
+    ) -> bool {
+        !self.hidden_layers.contains(&layer)
+    }
+
+    pub fn set_visible(
+        &mut self,
+        layer: DrawLayer,
+        visible: bool,
+    ) {
+        if visible {
+            self.hidden_layers.remove(&layer);
+        } else {
+            self.hidden_layers.insert(layer);
+        }
+    }
+
+    pub fn is_locked(
+        &self,
+        layer: DrawLayer,
+    ) -> bool {
+        self.locked_layers.contains(&layer)
+    }
+
+    pub fn set_locked(
+        &mut self,
+        layer: DrawLayer,
+        locked: bool,
+    ) {
+        if locked {
+            self.locked_layers.insert(layer);
+        } else {
+            self.locked_layers.remove(&layer);
+        }
+    }
+
+    /// Returns true if the entity can be selected in the editor. Entities without a
+    /// DrawOrderComponent are on the default layer
+    pub fn is_entity_selectable(
+        &self,
+        world: &World,
+        entity: Entity,
+    ) -> bool {
+        let layer = world
+            .get_component::<DrawOrderComponent>(entity)
+            .map(|draw_order| draw_order.layer)
+            .unwrap_or_default();
+
+        self.is_visible(layer) && !self.is_locked(layer)
+    }
+}
+
+/// Snapping behavior for a single frame, after considering held modifier keys
+#[derive(Copy, Clone, Debug)]
+pub struct ResolvedSnapSettings {
+    pub snap_enabled: bool,
+    pub grid_step: f32,
+    pub rotate_step: f32,
+    pub scale_step: f32,
+    pub input_multiplier: f32,
+}
+
+impl ResolvedSnapSettings {
+    /// Rounds the value to the nearest multiple of step. A step of zero or less disables snapping
+    pub fn snap(
+        value: f32,
+        step: f32,
+    ) -> f32 {
+        if step > 0.0 {
+            (value / step).round() * step
+        } else {
+            value
+        }
+    }
+
+    /// Given the total amount a drag has moved so far and the amount it moved this frame, returns
+    /// how much should be applied this frame. When snapping, this only returns non-zero values in
+    /// multiples of step. The input multiplier should already be applied to the drag (see
+    /// scale_drag)
+    pub fn drag_increment(
+        &self,
+        accumulated: f32,
+        previous_frame_delta: f32,
+        step: f32,
+    ) -> f32 {
+        if self.snap_enabled {
+            Self::snap(accumulated, step) - Self::snap(accumulated - previous_frame_delta, step)
+        } else {
+            previous_frame_delta
+        }
+    }
+
+    /// Applies the input multiplier to the drag's movement this frame, and adds it to the total
+    /// from previous frames (None at the start of a drag). Only the frame's movement is scaled so
+    /// that pressing or releasing the fine adjustment modifier mid-drag doesn't make the value jump
+    pub fn scale_drag(
+        &self,
+        drag: &EditorShapeDragState,
+        previous_accumulated: Option<glam::Vec2>,
+    ) -> EditorShapeDragState {
+        let frame_delta = drag.world_space_previous_frame_delta * self.input_multiplier;
+        let previous_accumulated = previous_accumulated.unwrap_or_else(|| {
+            (drag.world_space_accumulated_frame_delta - drag.world_space_previous_frame_delta)
+                * self.input_multiplier
+        });
+
+        let mut scaled_drag = drag.clone();
+        scaled_drag.world_space_previous_frame_delta = frame_delta;
+        scaled_drag.world_space_accumulated_frame_delta = previous_accumulated + frame_delta;
+        scaled_drag
+    }
+
+    /// Applies an increment produced by drag_increment. When snapping, the result is aligned to
+    /// step so that values that started off the grid will land on it
+    pub fn apply_increment(
+        &self,
+        value: f32,
+        increment: f32,
+        step: f32,
+    ) -> f32 {
+        if self.snap_enabled && increment != 0.0 {
+            Self::snap(value + increment, step)
+        } else {
+            value + increment
+        }
+    }
+}
+
+/// The point that rotate and scale gizmos operate around when multiple entities are selected
+#[derive(PartialEq, Debug, Copy, Clone, Serialize, Deserialize)]
+pub enum GizmoPivotMode {
+    /// Each entity rotates/scales around its own position
+    IndividualOrigins,
+
+    /// All selected entities rotate/scale around the center of the selection's bounds
+    SelectionCenter,
+
+    /// All selected entities rotate/scale around the most recently selected entity
+    ActiveEntity,
+}
+
+/// The coordinate space used for the translate gizmo's axes
+#[derive(PartialEq, Debug, Copy, Clone, Serialize, Deserialize)]
+pub enum GizmoSpace {
+    /// Axes are aligned with the world
+    World,
+
+    /// Axes are aligned with each entity's rotation
+    Local,
+}
+
 // If adding to this, don't forget to hook up keyboard shortcuts and buttons
-#[derive(PartialEq, Debug, Copy, Clone)]
+#[derive(PartialEq, Debug, Copy, Clone, Serialize, Deserialize)]
 pub enum EditorTool {
     Translate,
     Scale,
@@ -127,6 +586,10 @@ pub struct OpenedPrefabState {
     /// The opened prefab in cooked form. This is used for reloads and applying edits against
     cooked_prefab: Arc<CookedPrefab>,
 
+    /// The prefab as it was when it was last loaded from or saved to disk. When the file changes on
+    /// disk, this is the common ancestor used to merge the external changes with the editor's
+    base_prefab: Arc<Prefab>,
+
     /// Assists in finding the world entity that corresponds with a prefab entity
     prefab_to_world_mappings: HashMap<Entity, Entity>,
 
@@ -139,6 +602,24 @@ impl OpenedPrefabState {
         &self.cooked_prefab
     }
 
+    // A copy of this state that's mapped to a different world
+    fn with_world_mappings(
+        &self,
+        prefab_to_world_mappings: HashMap<Entity, Entity>,
+        world_to_prefab_mappings: HashMap<Entity, Entity>,
+    ) -> Self {
+        OpenedPrefabState {
+            uuid: self.uuid,
+            version: self.version,
+            prefab_handle: self.prefab_handle.clone(),
+            uncooked_prefab: self.uncooked_prefab.clone(),
+            cooked_prefab: self.cooked_prefab.clone(),
+            base_prefab: self.base_prefab.clone(),
+            prefab_to_world_mappings,
+            world_to_prefab_mappings,
+        }
+    }
+
     pub fn prefab_to_world_mappings(&self) -> &HashMap<Entity, Entity> {
         &self.prefab_to_world_mappings
     }
@@ -152,11 +633,35 @@ impl OpenedPrefabState {
     }
 }
 
+/// The default number of steps kept in the undo chain
+const DEFAULT_MAX_UNDO_STEPS: usize = 100;
+
+/// How long the main menu shows that an edit was rejected because the editor is playing
+const REJECTED_EDIT_MESSAGE_DURATION: Duration = Duration::from_secs(3);
+
+/// A single step in the undo chain
+pub struct UndoStep {
+    /// Human-readable description of the change, shown in the undo history
+    description: String,
+
+    /// The diffs required to apply/revert the change
+    diffs: Arc<TransactionDiffs>,
+}
+
+impl UndoStep {
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+}
+
 /// Diffs that are pending being applied
 struct TransactionDiffsPendingApply {
     /// The diffs required to apply/revert the transaction
     diffs: TransactionDiffs,
 
+    /// Human-readable description of the transaction, used to label the undo step
+    description: String,
+
     /// If true, an undo step will be recorded
     commit_changes: bool,
 
@@ -172,6 +677,9 @@ struct CurrentTransactionInfo {
 
     /// The diffs required to commit or cancel the transaction (apply vs. revert)
     diffs: TransactionDiffs,
+
+    /// Human-readable description of the transaction
+    description: String,
 }
 
 pub struct EditorStateResource {
@@ -182,11 +690,34 @@ pub struct EditorStateResource {
     window_options_running: WindowOptions,
     window_options_editing: WindowOptions,
     active_editor_tool: EditorTool,
+    snap_settings: SnapSettings,
+    draw_layer_settings: DrawLayerSettings,
+    gizmo_pivot_mode: GizmoPivotMode,
+    gizmo_space: GizmoSpace,
     pub add_component_search_text: ImString,
 
+    command_palette: CommandPaletteState,
+
     // If a prefab is opened, this holds the state associated with editing it
     opened_prefab: Option<Arc<OpenedPrefabState>>,
 
+    // Holds the edit world while it is cloned into a play world. While playing,
+    // the prefab and undo chain are frozen. The only changes allowed are explicitly keeping changes
+    // from play
+    play_session: Option<PlaySession>,
+
+    // Set while changes kept from play are being queued, so that enqueue_diffs accepts them
+    accepting_changes_from_play: bool,
+
+    // The most recent edit that was rejected because the editor is playing, and when. Shown in the
+    // main menu so that the edit doesn't look like it was silently lost
+    rejected_edit: Option<(&'static str, Instant)>,
+
+    // All documents that are open. The state of the active document (the undo chain, opened
+    // prefab, etc.) is held directly by EditorStateResource and swapped out when switching
+    open_documents: Vec<OpenDocument>,
+    active_document: Option<AssetUuid>,
+
     // We queue important operations to happen as many of them require taking fairly invasive
     // mut references to the world and resources. Each frame we drain this and execute each
     // operation
@@ -197,17 +728,71 @@ pub struct EditorStateResource {
     diffs_pending_apply: Vec<TransactionDiffsPendingApply>,
 
     // Undo/redo steps. Each slot in the chain contains diffs to go forward/backward in the
-    // chain.
-    undo_chain: VecDeque<Arc<TransactionDiffs>>,
+    // chain. The oldest steps are dropped when the chain grows beyond max_undo_steps, and undone
+    // steps are dropped from the redo end if that isn't enough
+    undo_chain: VecDeque<UndoStep>,
     undo_chain_position: usize,
+    max_undo_steps: usize,
+
+    // The undo chain position that matches what is saved on disk. If this doesn't match
+    // undo_chain_position, there are unsaved changes. This is None if the saved state is no
+    // longer reachable through the undo chain
+    saved_undo_chain_position: Option<usize>,
+
+    // If an action would discard unsaved changes, it is held here until the user confirms it
+    pending_discard_changes_action: Option<DiscardChangesAction>,
+
+    // Why the last save failed, cleared by the next successful save
+    save_error: Option<String>,
+
+    // Why the last prefab that was opened, reloaded or merged failed to load, cleared by the next
+    // prefab that opens
+    load_error: Option<String>,
+
+    // If the prefab changed on disk in a way that conflicts with the editor's changes, the
+    // conflicts are held here until the user resolves them
+    pending_merge: Option<PendingMerge>,
+
+    // If the user declined to reload the prefab after its source file changed, this holds the
+    // version that was declined so that we don't ask again
+    declined_reload_version: Option<u32>,
+
+    // Notified whenever a new version of a prefab asset is committed. This is created the first
+    // time hot reloading is checked, since the asset storage isn't available in new()
+    prefab_version_changes: Option<Receiver<AssetVersionChanged>>,
+
+    // The most recent version committed for each prefab, as reported by prefab_version_changes
+    latest_prefab_versions: HashMap<LoadHandle, u32>,
+
+    // Every change applied to the opened prefab since it was loaded or saved. This is
+    // periodically written to disk so that unsaved changes can be recovered after a crash
+    edit_journal: EditJournal,
+
+    // If a journal newer than the prefab's source file was found when the prefab was opened, its
+    // steps are held here until the user decides whether to recover them. Like the edit journal,
+    // this belongs to the active document and is parked with it
+    recoverable_journal_steps: Option<Vec<JournalStep>>,
+
+    // Where the camera was for prefabs that aren't open. Restored when the prefab is opened and
+    // persisted with the editor preferences
+    saved_cameras: HashMap<AssetUuid, CameraPreferences>,
 
     // The current transaction for any sort of gizmo interaction (draging to change
     // position, rotation, scaling)
     gizmo_transaction: Option<EditorTransaction>,
 
+    // The shared pivot that the current gizmo transaction rotates/scales around. It's captured when
+    // the drag starts so that it doesn't move along with the selection during the drag
+    gizmo_drag_pivot: Option<glam::Vec2>,
+
+    // How far the current gizmo drag has moved, after applying the input multiplier to each frame's
+    // movement (see ResolvedSnapSettings::scale_drag)
+    gizmo_drag_accumulated: Option<glam::Vec2>,
+
     // Component registries, required for calling into some upstream systems
     component_registry: Arc<HashMap<ComponentTypeId, ComponentRegistration>>,
     component_registry_by_uuid: Arc<HashMap<ComponentTypeUuid, ComponentRegistration>>,
+    component_spawn_mappings: Arc<ComponentSpawnMappings>,
 
     // If a transaction is in progress, the data required to identify it and commit it is
     // stored here. The ID is used to determine if a transaction provided by downstream code
@@ -224,19 +809,45 @@ impl EditorStateResource {
             window_options_running: WindowOptions::new_runtime(),
             window_options_editing: WindowOptions::new_editing(),
             active_editor_tool: EditorTool::Translate,
+            snap_settings: SnapSettings::new(),
+            draw_layer_settings: Default::default(),
+            gizmo_pivot_mode: GizmoPivotMode::IndividualOrigins,
+            gizmo_space: GizmoSpace::World,
             add_component_search_text: ImString::with_capacity(255),
+            command_palette: CommandPaletteState::new(),
             opened_prefab: None,
+            play_session: None,
+            accepting_changes_from_play: false,
+            rejected_edit: None,
+            open_documents: Default::default(),
+            active_document: None,
             pending_editor_ops: Default::default(),
 
             diffs_pending_apply: Default::default(),
 
             undo_chain: Default::default(),
             undo_chain_position: 0,
+            max_undo_steps: DEFAULT_MAX_UNDO_STEPS,
+            saved_undo_chain_position: Some(0),
+            pending_discard_changes_action: None,
+            save_error: None,
+            load_error: None,
+            pending_merge: None,
+            declined_reload_version: None,
+            prefab_version_changes: None,
+            latest_prefab_versions: Default::default(),
+
+            edit_journal: EditJournal::new(),
+            recoverable_journal_steps: None,
+            saved_cameras: Default::default(),
 
             gizmo_transaction: None,
+            gizmo_drag_pivot: None,
+            gizmo_drag_accumulated: None,
 
             component_registry: Arc::new(crate::create_component_registry()),
             component_registry_by_uuid: Arc::new(crate::create_component_registry_by_uuid()),
+            component_spawn_mappings: Arc::new(crate::create_component_spawn_mappings()),
 
             current_transaction_info: None,
         }
@@ -250,6 +861,10 @@ impl EditorStateResource {
         &self.component_registry_by_uuid
     }
 
+    pub fn component_spawn_mappings(&self) -> &ComponentSpawnMappings {
+        &self.component_spawn_mappings
+    }
+
     pub fn opened_prefab(&self) -> Option<Arc<OpenedPrefabState>> {
         self.opened_prefab.clone()
     }
@@ -262,6 +877,17 @@ impl EditorStateResource {
         self.editor_mode
     }
 
+    pub fn is_playing(&self) -> bool {
+        self.play_session.is_some()
+    }
+
+    /// If an edit was rejected in the last few seconds because the editor is playing, returns why
+    pub fn rejected_edit_message(&self) -> Option<&'static str> {
+        self.rejected_edit
+            .filter(|(_, rejected_at)| rejected_at.elapsed() < REJECTED_EDIT_MESSAGE_DURATION)
+            .map(|(message, _)| message)
+    }
+
     pub fn window_options(&self) -> &WindowOptions {
         if self.is_editor_active() {
             &self.window_options_editing
@@ -278,114 +904,659 @@ impl EditorStateResource {
         }
     }
 
-    pub fn gizmo_transaction(&self) -> &Option<EditorTransaction> {
-        &self.gizmo_transaction
+    pub fn command_palette(&self) -> &CommandPaletteState {
+        &self.command_palette
     }
 
-    pub fn gizmo_transaction_mut(&mut self) -> &mut Option<EditorTransaction> {
-        &mut self.gizmo_transaction
+    pub fn command_palette_mut(&mut self) -> &mut CommandPaletteState {
+        &mut self.command_palette
     }
 
-    fn play(
-        &mut self,
-        time_state: &mut TimeResource,
-    ) {
-        self.editor_mode = EditorMode::Inactive;
-        time_state.set_simulation_time_paused(false, SimulationTimePauseReason::Editor);
+    pub fn is_command_palette_open(&self) -> bool {
+        self.command_palette.is_open()
     }
 
-    fn pause(
+    /// Opens the command palette with an empty search, or closes it if it's already open
+    pub fn toggle_command_palette(&mut self) {
+        if self.command_palette.is_open() {
+            self.close_command_palette();
+        } else {
+            self.command_palette.open();
+        }
+    }
+
+    pub fn close_command_palette(&mut self) {
+        self.command_palette.close();
+    }
+
+    pub fn snap_settings(&self) -> &SnapSettings {
+        &self.snap_settings
+    }
+
+    pub fn snap_settings_mut(&mut self) -> &mut SnapSettings {
+        &mut self.snap_settings
+    }
+
+    pub fn draw_layer_settings(&self) -> &DrawLayerSettings {
+        &self.draw_layer_settings
+    }
+
+    pub fn draw_layer_settings_mut(&mut self) -> &mut DrawLayerSettings {
+        &mut self.draw_layer_settings
+    }
+
+    pub fn gizmo_pivot_mode(&self) -> GizmoPivotMode {
+        self.gizmo_pivot_mode
+    }
+
+    pub fn set_gizmo_pivot_mode(
         &mut self,
-        time_state: &mut TimeResource,
+        gizmo_pivot_mode: GizmoPivotMode,
     ) {
-        self.editor_mode = EditorMode::Active;
-        time_state.set_simulation_time_paused(true, SimulationTimePauseReason::Editor);
+        self.gizmo_pivot_mode = gizmo_pivot_mode;
+        log::info!("Gizmo pivot mode changed to {:?}", gizmo_pivot_mode);
+    }
+
+    pub fn gizmo_space(&self) -> GizmoSpace {
+        self.gizmo_space
     }
 
-    pub fn toggle_pause(
+    pub fn set_gizmo_space(
         &mut self,
-        time_state: &mut TimeResource,
+        gizmo_space: GizmoSpace,
     ) {
-        match self.editor_mode {
-            EditorMode::Active => self.play(time_state),
-            EditorMode::Inactive => self.pause(time_state),
-        };
+        self.gizmo_space = gizmo_space;
+        log::info!("Gizmo space changed to {:?}", gizmo_space);
     }
 
-    pub fn open_prefab(
-        world: &mut World,
-        resources: &Resources,
-        prefab_uuid: AssetUuid,
-    ) {
-        {
-            let mut asset_resource = resources.get_mut::<AssetResource>().unwrap();
+    pub fn undo_chain(&self) -> &VecDeque<UndoStep> {
+        &self.undo_chain
+    }
 
-            use atelier_loader::Loader;
-            use atelier_loader::handle::AssetHandle;
+    /// The number of steps in the undo chain that are currently applied. Steps at or beyond this
+    /// index have been undone and can be redone
+    pub fn undo_chain_position(&self) -> usize {
+        self.undo_chain_position
+    }
 
-            let load_handle = asset_resource.loader().add_ref(prefab_uuid);
-            let handle = atelier_loader::handle::Handle::<crate::pipeline::PrefabAsset>::new(
-                asset_resource.tx().clone(),
-                load_handle,
-            );
+    pub fn max_undo_steps(&self) -> usize {
+        self.max_undo_steps
+    }
 
-            let version = loop {
-                asset_resource.update();
-                if let atelier_loader::LoadStatus::Loaded = handle
-                    .load_status::<atelier_loader::rpc_loader::RpcLoader>(
-                    asset_resource.loader(),
-                ) {
-                    break handle
-                        .asset_version::<PrefabAsset, _>(asset_resource.storage())
-                        .unwrap();
-                }
-            };
+    pub fn set_max_undo_steps(
+        &mut self,
+        max_undo_steps: usize,
+    ) {
+        self.max_undo_steps = max_undo_steps.max(1);
+        self.trim_undo_chain();
+    }
 
-            let mut editor_state = resources.get_mut::<EditorStateResource>().unwrap();
+    /// Returns true if the opened prefab has been changed since it was opened or last saved
+    pub fn has_unsaved_changes(&self) -> bool {
+        self.opened_prefab.is_some()
+            && self.saved_undo_chain_position != Some(self.undo_chain_position)
+    }
 
-            // Load the uncooked prefab from disk and cook it. (Eventually this will be handled
-            // during atelier's build step
-            let mut universe = resources.get_mut::<UniverseResource>().unwrap();
-            let cooked_prefab = Arc::new(crate::prefab_cooking::cook_prefab(
-                &*universe,
-                &mut *asset_resource,
-                &editor_state.component_registry,
-                &editor_state.component_registry_by_uuid,
-                prefab_uuid,
-            ));
+    pub fn open_documents(&self) -> &[OpenDocument] {
+        &self.open_documents
+    }
 
-            // Duplicate the prefab data so we can apply diffs to it. This is temporary and will eventually be
-            // done within the daemon. (This is kind of like a clone() on the uncooked prefab asset)
-            let noop_diff = WorldDiff::new(vec![], vec![]);
-            let component_registry = crate::create_component_registry();
-            let uncooked_prefab = Arc::new(legion_transaction::apply_diff_to_prefab(
-                &handle.asset(asset_resource.storage()).unwrap().prefab,
-                &universe.universe,
-                &noop_diff,
-                &crate::create_component_registry_by_uuid(),
-                &CopyCloneImpl::new(&component_registry)
-            ));
-
-            // Store the cooked prefab and relevant metadata in an Arc on the EditorStateResource.
-            // Eventually the cooked prefab data would be held by AssetStorage and we'd just hold
-            // a handle to it.
-            let opened_prefab = OpenedPrefabState {
-                uuid: prefab_uuid,
-                version,
-                prefab_handle: handle,
-                uncooked_prefab,
-                cooked_prefab,
-                prefab_to_world_mappings: Default::default(),
-                world_to_prefab_mappings: Default::default(),
-            };
+    pub fn active_document(&self) -> Option<AssetUuid> {
+        self.active_document
+    }
 
-            editor_state.opened_prefab = Some(Arc::new(opened_prefab));
+    /// Returns true if the given document has been changed since it was opened or last saved
+    pub fn document_has_unsaved_changes(
+        &self,
+        prefab_uuid: AssetUuid,
+    ) -> bool {
+        if self.active_document == Some(prefab_uuid) {
+            return self.has_unsaved_changes();
         }
 
-        Self::reset(world, resources);
+        self.open_documents
+            .iter()
+            .find(|document| document.uuid == prefab_uuid)
+            .and_then(|document| document.parked_state.as_ref())
+            .map(|parked_state| parked_state.has_unsaved_changes())
+            .unwrap_or(false)
     }
 
-    fn reset(
+    /// The names of all open documents with unsaved changes
+    pub fn documents_with_unsaved_changes(&self) -> Vec<&str> {
+        self.open_documents
+            .iter()
+            .filter(|document| self.document_has_unsaved_changes(document.uuid))
+            .map(|document| document.name())
+            .collect()
+    }
+
+    // The uncooked prefabs of inactive documents with unsaved changes. Prefabs are cooked from
+    // these instead of asset storage so that changes to a prefab show up in the documents that
+    // reference it without saving first
+    fn unsaved_document_prefabs(&self) -> HashMap<AssetUuid, Arc<Prefab>> {
+        self.open_documents
+            .iter()
+            .filter_map(|document| {
+                document
+                    .parked_state
+                    .as_ref()
+                    .filter(|parked_state| parked_state.has_unsaved_changes())
+                    .map(|parked_state| {
+                        (document.uuid, parked_state.opened_prefab.uncooked_prefab.clone())
+                    })
+            })
+            .collect()
+    }
+
+    /// An action that is waiting for the user to confirm discarding unsaved changes
+    pub fn pending_discard_changes_action(&self) -> Option<DiscardChangesAction> {
+        self.pending_discard_changes_action
+    }
+
+    /// The error from the last save, if it failed
+    pub fn save_error(&self) -> Option<&str> {
+        self.save_error.as_ref().map(|error| error.as_str())
+    }
+
+    /// The error from the last prefab that failed to load, if the last load failed
+    pub fn load_error(&self) -> Option<&str> {
+        self.load_error.as_ref().map(|error| error.as_str())
+    }
+
+    // Records why a prefab failed to load so that it can be shown in the main menu
+    fn set_load_error(
+        &mut self,
+        error: String,
+    ) {
+        log::error!("{}", error);
+        self.load_error = Some(error);
+    }
+
+    pub fn pending_merge(&self) -> Option<&PendingMerge> {
+        self.pending_merge.as_ref()
+    }
+
+    pub fn pending_merge_mut(&mut self) -> Option<&mut PendingMerge> {
+        self.pending_merge.as_mut()
+    }
+
+    /// The number of steps in a journal that can be recovered for the active document, if one was
+    /// found when its prefab was opened
+    pub fn recoverable_journal_step_count(&self) -> Option<usize> {
+        self.recoverable_journal_steps
+            .as_ref()
+            .map(|steps| steps.len())
+    }
+
+    /// Flushes the edit journals of all open documents to disk if there are changes that haven't
+    /// been written recently
+    pub fn write_edit_journal_if_due(&mut self) {
+        self.edit_journal.write_if_due();
+
+        for document in &mut self.open_documents {
+            if let Some(parked_state) = &mut document.parked_state {
+                parked_state.edit_journal.write_if_due();
+            }
+        }
+    }
+
+    /// Looks for a journal of unsaved changes for the given prefab. If one is found, the user will
+    /// be asked whether to recover it. This should be called after the prefab is opened.
+    pub fn check_for_recoverable_journal(
+        &mut self,
+        prefab_uuid: AssetUuid,
+    ) {
+        self.edit_journal.begin(prefab_uuid);
+        self.recoverable_journal_steps =
+            EditJournal::find_recoverable(&prefab_uuid, &prefab_source_path(&prefab_uuid));
+
+        if let Some(steps) = &self.recoverable_journal_steps {
+            log::info!("Found edit journal with {} unsaved steps", steps.len());
+        }
+    }
+
+    /// Restores the settings that were saved from a previous session
+    pub fn apply_preferences(
+        &mut self,
+        preferences: &EditorPreferences,
+    ) {
+        self.window_options_editing = preferences.window_options_editing.clone();
+        self.window_options_running = preferences.window_options_running.clone();
+        self.active_editor_tool = preferences.active_editor_tool;
+        self.gizmo_pivot_mode = preferences.gizmo_pivot_mode;
+        self.gizmo_space = preferences.gizmo_space;
+        self.snap_settings = preferences.snap_settings.clone();
+        self.draw_layer_settings = preferences.draw_layer_settings.clone();
+        self.saved_cameras = preferences.cameras.clone();
+    }
+
+    /// Writes the current settings into the preferences so they can be saved for the next session
+    pub fn update_preferences(
+        &self,
+        preferences: &mut EditorPreferences,
+        camera: &CameraResource,
+    ) {
+        preferences.window_options_editing = self.window_options_editing.clone();
+        preferences.window_options_running = self.window_options_running.clone();
+        preferences.active_editor_tool = self.active_editor_tool;
+        preferences.gizmo_pivot_mode = self.gizmo_pivot_mode;
+        preferences.gizmo_space = self.gizmo_space;
+        preferences.snap_settings = self.snap_settings.clone();
+        preferences.draw_layer_settings = self.draw_layer_settings.clone();
+        preferences.last_opened_prefab = self.active_document;
+
+        preferences
+            .cameras
+            .extend(self.saved_cameras.iter().map(|(k, v)| (*k, *v)));
+
+        for document in &self.open_documents {
+            let camera_preferences = match &document.parked_state {
+                Some(parked_state) => CameraPreferences {
+                    position: parked_state.camera_position,
+                    x_half_extents: parked_state.camera_x_half_extents,
+                },
+                None => CameraPreferences {
+                    position: camera.position,
+                    x_half_extents: camera.x_half_extents,
+                },
+            };
+
+            preferences.cameras.insert(document.uuid, camera_preferences);
+        }
+    }
+
+    pub fn gizmo_transaction(&self) -> &Option<EditorTransaction> {
+        &self.gizmo_transaction
+    }
+
+    pub fn gizmo_transaction_mut(&mut self) -> &mut Option<EditorTransaction> {
+        &mut self.gizmo_transaction
+    }
+
+    pub fn gizmo_drag_pivot(&self) -> Option<glam::Vec2> {
+        self.gizmo_drag_pivot
+    }
+
+    pub fn gizmo_drag_pivot_mut(&mut self) -> &mut Option<glam::Vec2> {
+        &mut self.gizmo_drag_pivot
+    }
+
+    pub fn gizmo_drag_accumulated(&self) -> Option<glam::Vec2> {
+        self.gizmo_drag_accumulated
+    }
+
+    pub fn gizmo_drag_accumulated_mut(&mut self) -> &mut Option<glam::Vec2> {
+        &mut self.gizmo_drag_accumulated
+    }
+
+    fn play(
+        world: &mut World,
+        resources: &Resources,
+    ) {
+        let is_playing = resources.get::<EditorStateResource>().unwrap().is_playing();
+        if !is_playing {
+            Self::begin_play_session(world, resources);
+        }
+
+        let mut editor_state = resources.get_mut::<EditorStateResource>().unwrap();
+        let mut time_state = resources.get_mut::<TimeResource>().unwrap();
+        editor_state.editor_mode = EditorMode::Inactive;
+        time_state.set_simulation_time_paused(false, SimulationTimePauseReason::Editor);
+    }
+
+    fn pause(
+        &mut self,
+        time_state: &mut TimeResource,
+    ) {
+        self.editor_mode = EditorMode::Active;
+        time_state.set_simulation_time_paused(true, SimulationTimePauseReason::Editor);
+    }
+
+    fn toggle_pause(
+        world: &mut World,
+        resources: &Resources,
+    ) {
+        let editor_mode = resources.get::<EditorStateResource>().unwrap().editor_mode;
+        match editor_mode {
+            EditorMode::Active => Self::play(world, resources),
+            EditorMode::Inactive => {
+                let mut editor_state = resources.get_mut::<EditorStateResource>().unwrap();
+                let mut time_state = resources.get_mut::<TimeResource>().unwrap();
+                editor_state.pause(&mut *time_state)
+            }
+        };
+    }
+
+    // Sets the edit world aside and clones it into a separate play world to be simulated, so that
+    // play starts from exactly what is being edited. The play world gets its own physics, so the
+    // edit world's bodies aren't simulated with it. Rigid bodies are spawned again into the play
+    // physics rather than copied (see create_play_clone_impl_handler_set)
+    fn begin_play_session(
+        world: &mut World,
+        resources: &Resources,
+    ) {
+        log::info!("Starting play session");
+        let selected_uuids = {
+            let mut editor_state = resources.get_mut::<EditorStateResource>().unwrap();
+            let selection_resource = resources.get::<EditorSelectionResource>().unwrap();
+
+            // Commit any in-progress transaction so that it's in the undo chain before it's frozen
+            if let Some(current_transaction_info) = editor_state.current_transaction_info.take() {
+                editor_state.enqueue_diffs(
+                    current_transaction_info.diffs,
+                    current_transaction_info.description,
+                    true,
+                    PostCommitSelection::KeepCurrentSelection,
+                );
+            }
+
+            editor_state.gizmo_transaction = None;
+            editor_state.gizmo_drag_pivot = None;
+            editor_state.gizmo_drag_accumulated = None;
+            editor_state.get_selected_uuids(&*selection_resource, world)
+        };
+
+        // This is scoped so that physics isn't borrowed while spawning bodies into it
+        let (edit_world, edit_physics) = {
+            let mut physics = resources.get_mut::<PhysicsResource>().unwrap();
+            let universe = resources.get::<UniverseResource>().unwrap();
+            let mut time_resource = resources.get_mut::<TimeResource>().unwrap();
+            time_resource.reset_simulation_time();
+
+            (
+                std::mem::replace(world, universe.universe.create_world()),
+                std::mem::replace(
+                    &mut *physics,
+                    PhysicsResource::new(glam::Vec2::unit_y() * crate::GRAVITY),
+                ),
+            )
+        };
+
+        let mut edit_to_play_mappings = HashMap::default();
+        {
+            let spawn_clone_impl_handler_set = crate::create_play_clone_impl_handler_set();
+            let component_registry = crate::create_component_registry();
+            let clone_impl = crate::create_spawn_clone_impl(
+                &spawn_clone_impl_handler_set,
+                &component_registry,
+                resources,
+            );
+            world.clone_from(
+                &edit_world,
+                &clone_impl,
+                &mut legion::world::HashMapCloneImplResult(&mut edit_to_play_mappings),
+                &legion::world::HashMapEntityReplacePolicy(&HashMap::default()),
+            );
+        }
+
+        {
+            let mut editor_state = resources.get_mut::<EditorStateResource>().unwrap();
+
+            // Point the opened prefab at the clones in the play world, and keep the edit world's
+            // mappings to restore when play ends
+            let (prefab_to_world_mappings, world_to_prefab_mappings) =
+                match editor_state.opened_prefab.clone() {
+                    Some(opened_prefab) => {
+                        let play_prefab_to_world_mappings: HashMap<Entity, Entity> = opened_prefab
+                            .prefab_to_world_mappings
+                            .iter()
+                            .filter_map(|(prefab_entity, edit_entity)| {
+                                edit_to_play_mappings
+                                    .get(edit_entity)
+                                    .map(|play_entity| (*prefab_entity, *play_entity))
+                            })
+                            .collect();
+
+                        let play_world_to_prefab_mappings = play_prefab_to_world_mappings
+                            .iter()
+                            .map(|(prefab_entity, play_entity)| (*play_entity, *prefab_entity))
+                            .collect();
+
+                        editor_state.opened_prefab =
+                            Some(Arc::new(opened_prefab.with_world_mappings(
+                                play_prefab_to_world_mappings,
+                                play_world_to_prefab_mappings,
+                            )));
+
+                        (
+                            opened_prefab.prefab_to_world_mappings.clone(),
+                            opened_prefab.world_to_prefab_mappings.clone(),
+                        )
+                    }
+                    None => Default::default(),
+                };
+
+            editor_state.play_session = Some(PlaySession {
+                edit_world,
+                edit_physics,
+                prefab_to_world_mappings,
+                world_to_prefab_mappings,
+            });
+        }
+
+        let mut editor_state = resources.get_mut::<EditorStateResource>().unwrap();
+        let mut selection_resource = resources.get_mut::<EditorSelectionResource>().unwrap();
+        editor_state.restore_selected_uuids(&mut *selection_resource, world, &selected_uuids);
+    }
+
+    // Drops the play world and its physics, and puts the edit world back. The edit world's entities
+    // are respawned in place from the prefab so that any changes kept from play show up
+    fn end_play_session(
+        world: &mut World,
+        resources: &Resources,
+    ) {
+        let selected_uuids = {
+            let mut editor_state = resources.get_mut::<EditorStateResource>().unwrap();
+            if !editor_state.is_playing() {
+                return;
+            }
+
+            let selection_resource = resources.get::<EditorSelectionResource>().unwrap();
+            editor_state.get_selected_uuids(&*selection_resource, world)
+        };
+
+        log::info!("Ending play session");
+        {
+            let mut editor_state = resources.get_mut::<EditorStateResource>().unwrap();
+            let mut physics = resources.get_mut::<PhysicsResource>().unwrap();
+            let play_session = editor_state.play_session.take().unwrap();
+
+            // The play world's bodies belong to the play physics, so drop the world first
+            let play_world = std::mem::replace(world, play_session.edit_world);
+            std::mem::drop(play_world);
+            *physics = play_session.edit_physics;
+
+            if let Some(opened_prefab) = editor_state.opened_prefab.clone() {
+                editor_state.opened_prefab = Some(Arc::new(opened_prefab.with_world_mappings(
+                    play_session.prefab_to_world_mappings,
+                    play_session.world_to_prefab_mappings,
+                )));
+            }
+
+            editor_state.rejected_edit = None;
+        }
+
+        Self::reset(world, resources);
+
+        let mut editor_state = resources.get_mut::<EditorStateResource>().unwrap();
+        let mut selection_resource = resources.get_mut::<EditorSelectionResource>().unwrap();
+        editor_state.restore_selected_uuids(&mut *selection_resource, world, &selected_uuids);
+    }
+
+    // Copies the values of the selected entities' components in the play world back into the
+    // prefab as a committed change. Components that were spawned from a def are turned back into
+    // it. The play world isn't respawned, so play can continue
+    fn keep_changes_from_play(
+        world: &World,
+        resources: &Resources,
+    ) {
+        let mut editor_state = resources.get_mut::<EditorStateResource>().unwrap();
+        if !editor_state.is_playing() {
+            return;
+        }
+
+        let opened_prefab = match editor_state.opened_prefab.clone() {
+            Some(opened_prefab) => opened_prefab,
+            None => return,
+        };
+
+        let selection_resource = resources.get::<EditorSelectionResource>().unwrap();
+        let universe_resource = resources.get::<UniverseResource>().unwrap();
+        let mut tx = match editor_state
+            .create_transaction_from_selected(&*selection_resource, &*universe_resource)
+        {
+            Some(tx) => tx,
+            None => return,
+        };
+
+        // Reverse the keys/values of the opened prefab map so we can efficiently look up the UUID of entities in the prefab
+        use std::iter::FromIterator;
+        let prefab_entity_to_uuid: HashMap<Entity, prefab_format::EntityUuid> = HashMap::from_iter(
+            opened_prefab
+                .cooked_prefab()
+                .entities
+                .iter()
+                .map(|(k, v)| (*v, *k)),
+        );
+
+        let registry = crate::create_keep_changes_registry();
+        let mut entity_count = 0;
+        for play_entity in selection_resource.selected_entities() {
+            let tx_entity = opened_prefab
+                .world_to_prefab_mappings
+                .get(play_entity)
+                .and_then(|prefab_entity| prefab_entity_to_uuid.get(prefab_entity))
+                .and_then(|entity_uuid| tx.transaction.uuid_to_entities().get(entity_uuid))
+                .and_then(|entity_info| entity_info.after_entity());
+
+            if let Some(tx_entity) = tx_entity {
+                registry.copy_components(world, *play_entity, tx.world_mut(), tx_entity);
+                entity_count += 1;
+            }
+        }
+
+        let diffs = tx
+            .transaction
+            .create_transaction_diffs(&*editor_state.component_registry_by_uuid);
+
+        log::info!("Keeping changes from play for {} entities", entity_count);
+        editor_state.enqueue_diffs_kept_from_play(
+            diffs,
+            format!("Keep changes from play ({} entities)", entity_count),
+        );
+    }
+
+    /// Loads, cooks and spawns the given prefab. If it or a prefab it references fails to load,
+    /// nothing is changed and the error is returned
+    pub fn open_prefab(
+        world: &mut World,
+        resources: &Resources,
+        prefab_uuid: AssetUuid,
+    ) -> Result<(), String> {
+        let opened_prefab = Self::load_opened_prefab(resources, prefab_uuid)?;
+
+        {
+            let mut editor_state = resources.get_mut::<EditorStateResource>().unwrap();
+            editor_state.opened_prefab = Some(Arc::new(opened_prefab));
+            editor_state.load_error = None;
+        }
+
+        Self::reset(world, resources);
+        Ok(())
+    }
+
+    // Blocks until the prefab and the prefabs it references are loaded, then cooks it
+    fn load_opened_prefab(
+        resources: &Resources,
+        prefab_uuid: AssetUuid,
+    ) -> Result<OpenedPrefabState, String> {
+        let mut asset_resource = resources.get_mut::<AssetResource>().unwrap();
+
+        use atelier_loader::handle::AssetHandle;
+
+        let load_handle = asset_resource.loader().add_ref(prefab_uuid);
+        let handle = atelier_loader::handle::Handle::<crate::pipeline::PrefabAsset>::new(
+            asset_resource.tx().clone(),
+            load_handle,
+        );
+
+        let version = loop {
+            asset_resource.update();
+            let loaded_version = asset_resource.storage().get_version(&handle);
+            match asset_load_state(&*asset_resource, handle.load_handle()) {
+                (AssetLoadState::Loaded, _) => break loaded_version.unwrap(),
+                // If only a reload failed, the previous version can still be opened
+                (AssetLoadState::Failed, _) if loaded_version.is_some() => {
+                    break loaded_version.unwrap();
+                }
+                (AssetLoadState::Failed, error) => {
+                    return Err(format!(
+                        "Failed to open prefab {}: {}",
+                        prefab_uuid,
+                        error.unwrap_or_default()
+                    ));
+                }
+                (AssetLoadState::Pending, _) => {}
+            }
+        };
+
+        let editor_state = resources.get::<EditorStateResource>().unwrap();
+
+        // Use the cooked prefab if the loader has one (i.e. it was packed ahead of time) and
+        // no other document has unsaved changes that it might reference. Otherwise load the
+        // uncooked prefab from disk and cook it. (Eventually this will be handled during
+        // atelier's build step
+        let mut universe = resources.get_mut::<UniverseResource>().unwrap();
+        let in_memory_prefabs = editor_state.unsaved_document_prefabs();
+        let packed_cooked_prefab = if in_memory_prefabs.is_empty() {
+            asset_resource.loader().load_cooked_prefab(prefab_uuid)
+        } else {
+            None
+        };
+
+        let cooked_prefab = match packed_cooked_prefab {
+            Some(cooked_prefab) => cooked_prefab,
+            None => crate::prefab_cooking::cook_prefab(
+                &*universe,
+                &mut *asset_resource,
+                &editor_state.component_registry,
+                &editor_state.component_registry_by_uuid,
+                &in_memory_prefabs,
+                prefab_uuid,
+            )?,
+        };
+        let cooked_prefab = Arc::new(cooked_prefab);
+
+        // Duplicate the prefab data so we can apply diffs to it. This is temporary and will
+        // eventually be done within the daemon. (This is kind of like a clone() on the uncooked
+        // prefab asset)
+        let noop_diff = WorldDiff::new(vec![], vec![]);
+        let component_registry = crate::create_component_registry();
+        let uncooked_prefab = Arc::new(legion_transaction::apply_diff_to_prefab(
+            &asset_resource.storage().get(&handle).unwrap().prefab,
+            &universe.universe,
+            &noop_diff,
+            &crate::create_component_registry_by_uuid(),
+            &CopyCloneImpl::new(&component_registry)
+        ));
+
+        // open_prefab stores the cooked prefab and relevant metadata in an Arc on the
+        // EditorStateResource. Eventually the cooked prefab data would be held by AssetStorage and
+        // we'd just hold a handle to it.
+        Ok(OpenedPrefabState {
+            uuid: prefab_uuid,
+            version,
+            prefab_handle: handle,
+            base_prefab: uncooked_prefab.clone(),
+            uncooked_prefab,
+            cooked_prefab,
+            prefab_to_world_mappings: Default::default(),
+            world_to_prefab_mappings: Default::default(),
+        })
+    }
+
+    fn reset(
         world: &mut World,
         resources: &Resources,
     ) {
@@ -442,6 +1613,7 @@ impl EditorStateResource {
                 cooked_prefab: opened_prefab.cooked_prefab.clone(),
                 prefab_handle: opened_prefab.prefab_handle.clone(),
                 uncooked_prefab: opened_prefab.uncooked_prefab.clone(),
+                base_prefab: opened_prefab.base_prefab.clone(),
                 version: opened_prefab.version,
                 prefab_to_world_mappings,
                 world_to_prefab_mappings,
@@ -472,6 +1644,10 @@ impl EditorStateResource {
         self.pending_editor_ops.push(EditorOp::Reset);
     }
 
+    pub fn enqueue_keep_changes_from_play(&mut self) {
+        self.pending_editor_ops.push(EditorOp::KeepChangesFromPlay);
+    }
+
     pub fn enqueue_open_prefab(
         &mut self,
         prefab_uuid: AssetUuid,
@@ -480,6 +1656,22 @@ impl EditorStateResource {
             .push(EditorOp::OpenPrefab(prefab_uuid));
     }
 
+    pub fn enqueue_switch_document(
+        &mut self,
+        prefab_uuid: AssetUuid,
+    ) {
+        self.pending_editor_ops
+            .push(EditorOp::SwitchDocument(prefab_uuid));
+    }
+
+    pub fn enqueue_close_document(
+        &mut self,
+        prefab_uuid: AssetUuid,
+    ) {
+        self.pending_editor_ops
+            .push(EditorOp::CloseDocument(prefab_uuid));
+    }
+
     pub fn enqueue_toggle_pause(&mut self) {
         self.pending_editor_ops.push(EditorOp::TogglePause);
     }
@@ -492,6 +1684,47 @@ impl EditorStateResource {
         self.pending_editor_ops.push(EditorOp::Redo);
     }
 
+    pub fn enqueue_jump_to_undo_position(
+        &mut self,
+        undo_chain_position: usize,
+    ) {
+        self.pending_editor_ops
+            .push(EditorOp::JumpToUndoPosition(undo_chain_position));
+    }
+
+    pub fn enqueue_quit(&mut self) {
+        self.pending_editor_ops.push(EditorOp::Quit);
+    }
+
+    pub fn enqueue_confirm_discard_changes(&mut self) {
+        self.pending_editor_ops.push(EditorOp::ConfirmDiscardChanges);
+    }
+
+    pub fn enqueue_cancel_discard_changes(&mut self) {
+        self.pending_editor_ops.push(EditorOp::CancelDiscardChanges);
+    }
+
+    pub fn enqueue_save_and_confirm_discard_changes(&mut self) {
+        self.pending_editor_ops
+            .push(EditorOp::SaveAndConfirmDiscardChanges);
+    }
+
+    pub fn enqueue_apply_merge(&mut self) {
+        self.pending_editor_ops.push(EditorOp::ApplyMerge);
+    }
+
+    pub fn enqueue_abort_merge(&mut self) {
+        self.pending_editor_ops.push(EditorOp::AbortMerge);
+    }
+
+    pub fn enqueue_recover_journal(&mut self) {
+        self.pending_editor_ops.push(EditorOp::RecoverJournal);
+    }
+
+    pub fn enqueue_discard_journal(&mut self) {
+        self.pending_editor_ops.push(EditorOp::DiscardJournal);
+    }
+
     pub fn enqueue_set_active_editor_tool(
         &mut self,
         editor_tool: EditorTool,
@@ -521,37 +1754,57 @@ impl EditorStateResource {
         for editor_op in editor_ops {
             match editor_op {
                 EditorOp::OpenPrefab(asset_uuid) => {
-                    let new_world = {
-                        let mut editor_state = resources.get_mut::<EditorStateResource>().unwrap();
-                        editor_state.clear_undo_history();
+                    Self::end_play_session(world, resources);
+                    Self::open_prefab_document(world, resources, asset_uuid);
+                }
+                EditorOp::SwitchDocument(asset_uuid) => {
+                    Self::end_play_session(world, resources);
+                    Self::switch_document(world, resources, asset_uuid);
+                }
+                EditorOp::CloseDocument(asset_uuid) => {
+                    Self::end_play_session(world, resources);
+                    let action = DiscardChangesAction::CloseDocument(asset_uuid);
+                    if Self::confirm_discard_changes_required(resources, action) {
+                        continue;
+                    }
 
-                        let universe = resources.get::<UniverseResource>().unwrap();
-                        let world = universe.universe.create_world();
-                        world
-                    };
-                    *world = new_world;
-                    Self::open_prefab(world, resources, asset_uuid)
+                    Self::perform_discard_changes_action(world, resources, action);
                 }
                 EditorOp::SavePrefab => {
                     let mut editor_state = resources.get_mut::<EditorStateResource>().unwrap();
                     editor_state.save();
                 }
-                EditorOp::Play => {
-                    let mut editor_state = resources.get_mut::<EditorStateResource>().unwrap();
-                    let mut time_state = resources.get_mut::<TimeResource>().unwrap();
-                    editor_state.play(&mut *time_state)
+                EditorOp::SaveAndConfirmDiscardChanges => {
+                    let action = {
+                        let mut editor_state = resources.get_mut::<EditorStateResource>().unwrap();
+                        if !editor_state.save() {
+                            // Leave the action pending so the modal stays up with the error
+                            continue;
+                        }
+
+                        editor_state.pending_discard_changes_action.take()
+                    };
+
+                    if let Some(action) = action {
+                        Self::perform_discard_changes_action(world, resources, action);
+                    }
                 }
+                EditorOp::Play => Self::play(world, resources),
                 EditorOp::Pause => {
                     let mut editor_state = resources.get_mut::<EditorStateResource>().unwrap();
                     let mut time_state = resources.get_mut::<TimeResource>().unwrap();
                     editor_state.pause(&mut *time_state)
                 }
-                EditorOp::Reset => Self::reset(world, resources),
-                EditorOp::TogglePause => {
-                    let mut editor_state = resources.get_mut::<EditorStateResource>().unwrap();
-                    let mut time_state = resources.get_mut::<TimeResource>().unwrap();
-                    editor_state.toggle_pause(&mut *time_state)
+                EditorOp::Reset => {
+                    let is_playing = resources.get::<EditorStateResource>().unwrap().is_playing();
+                    if is_playing {
+                        Self::end_play_session(world, resources);
+                    } else {
+                        Self::reset(world, resources);
+                    }
                 }
+                EditorOp::KeepChangesFromPlay => Self::keep_changes_from_play(world, resources),
+                EditorOp::TogglePause => Self::toggle_pause(world, resources),
                 EditorOp::SetActiveEditorTool(editor_tool) => {
                     let mut editor_state = resources.get_mut::<EditorStateResource>().unwrap();
                     editor_state.set_active_editor_tool(editor_tool)
@@ -562,6 +1815,77 @@ impl EditorStateResource {
                 EditorOp::Redo => {
                     Self::redo(world, resources);
                 }
+                EditorOp::JumpToUndoPosition(undo_chain_position) => {
+                    Self::jump_to_undo_position(world, resources, undo_chain_position);
+                }
+                EditorOp::Quit => {
+                    let action = DiscardChangesAction::Quit;
+                    if Self::confirm_discard_changes_required(resources, action) {
+                        continue;
+                    }
+
+                    Self::perform_discard_changes_action(world, resources, action);
+                }
+                EditorOp::ConfirmDiscardChanges => {
+                    let action = resources
+                        .get_mut::<EditorStateResource>()
+                        .unwrap()
+                        .pending_discard_changes_action
+                        .take();
+
+                    if let Some(action) = action {
+                        Self::perform_discard_changes_action(world, resources, action);
+                    }
+                }
+                EditorOp::CancelDiscardChanges => {
+                    let mut editor_state = resources.get_mut::<EditorStateResource>().unwrap();
+                    let action = editor_state.pending_discard_changes_action.take();
+                    editor_state.save_error = None;
+
+                    // Don't ask again about the same version of the file
+                    if let Some(DiscardChangesAction::HotReload(version)) = action {
+                        editor_state.declined_reload_version = Some(version);
+                    }
+                }
+                EditorOp::ApplyMerge => {
+                    let pending_merge = resources
+                        .get_mut::<EditorStateResource>()
+                        .unwrap()
+                        .pending_merge
+                        .take();
+
+                    if let Some(pending_merge) = pending_merge {
+                        let take_theirs = pending_merge
+                            .conflicts
+                            .iter()
+                            .filter(|conflict| conflict.resolution == MergeResolution::TakeTheirs)
+                            .map(|conflict| (conflict.entity_uuid, conflict.component_type))
+                            .collect();
+
+                        Self::merge_external_changes(
+                            world,
+                            resources,
+                            pending_merge.version,
+                            &take_theirs,
+                        );
+                    }
+                }
+                EditorOp::AbortMerge => {
+                    let mut editor_state = resources.get_mut::<EditorStateResource>().unwrap();
+                    if let Some(pending_merge) = editor_state.pending_merge.take() {
+                        // Don't ask again about the same version of the file
+                        editor_state.declined_reload_version = Some(pending_merge.version);
+                    }
+                }
+                EditorOp::RecoverJournal => {
+                    let mut editor_state = resources.get_mut::<EditorStateResource>().unwrap();
+                    editor_state.recover_journal();
+                }
+                EditorOp::DiscardJournal => {
+                    let mut editor_state = resources.get_mut::<EditorStateResource>().unwrap();
+                    editor_state.recoverable_journal_steps = None;
+                    editor_state.edit_journal.clear();
+                }
             }
         }
     }
@@ -586,77 +1910,711 @@ impl EditorStateResource {
                         .map(|(k, v)| (*v, *k)),
                 );
 
-            // Iterate all selected prefab entities
-            for selected_entity in selection_resource.selected_entities() {
-                if let Some(prefab_entity) =
-                    opened_prefab.world_to_prefab_mappings.get(selected_entity)
-                {
-                    let entity_uuid = prefab_entity_to_uuid.get(prefab_entity);
-                    // Insert the UUID into selected_uuids
-                    if let Some(uuid) = entity_uuid {
-                        log::info!(
-                            "Selected entity {:?} corresponds to prefab entity {:?} uuid {:?}",
-                            selected_entity,
-                            prefab_entity,
-                            uuid::Uuid::from_bytes(*uuid).to_string()
-                        );
-                        selected_uuids.insert(*uuid);
-                    } else {
-                        //TODO: For now this is a panic because it really shouldn't happen and we want to make sure it's visible if it does, but
-                        // losing selection info shouldn't be fatal
-                        panic!("Could not find prefab entity {:?} which should have corresponded with selected entity {:?}", prefab_entity, selected_entity);
+            // Iterate all selected prefab entities
+            for selected_entity in selection_resource.selected_entities() {
+                if let Some(prefab_entity) =
+                    opened_prefab.world_to_prefab_mappings.get(selected_entity)
+                {
+                    let entity_uuid = prefab_entity_to_uuid.get(prefab_entity);
+                    // Insert the UUID into selected_uuids
+                    if let Some(uuid) = entity_uuid {
+                        log::info!(
+                            "Selected entity {:?} corresponds to prefab entity {:?} uuid {:?}",
+                            selected_entity,
+                            prefab_entity,
+                            uuid::Uuid::from_bytes(*uuid).to_string()
+                        );
+                        selected_uuids.insert(*uuid);
+                    } else {
+                        //TODO: For now this is a panic because it really shouldn't happen and we want to make sure it's visible if it does, but
+                        // losing selection info shouldn't be fatal
+                        panic!("Could not find prefab entity {:?} which should have corresponded with selected entity {:?}", prefab_entity, selected_entity);
+                    }
+                }
+            }
+        }
+        selected_uuids
+    }
+
+    fn restore_selected_uuids(
+        &mut self,
+        selection_resource: &mut EditorSelectionResource,
+        world: &World,
+        selected_uuids: &HashSet<EntityUuid>,
+    ) {
+        let mut selected_entities: HashSet<Entity> = HashSet::default();
+        for selected_uuid in selected_uuids {
+            if let Some(opened_prefab) = self.opened_prefab.as_ref() {
+                if let Some(prefab_entity) =
+                    &opened_prefab.cooked_prefab.entities.get(selected_uuid)
+                {
+                    let world_entity = opened_prefab.prefab_to_world_mappings[prefab_entity];
+                    selected_entities.insert(world_entity);
+                }
+            }
+        }
+
+        selection_resource.enqueue_set_selection(selected_entities.into_iter().collect());
+    }
+
+    /// Opens the given prefab in a new document and makes it active. If the prefab is already open,
+    /// its document is made active instead.
+    pub fn open_prefab_document(
+        world: &mut World,
+        resources: &Resources,
+        prefab_uuid: AssetUuid,
+    ) {
+        let already_open = resources
+            .get::<EditorStateResource>()
+            .unwrap()
+            .open_documents
+            .iter()
+            .any(|document| document.uuid == prefab_uuid);
+
+        if already_open {
+            Self::switch_document(world, resources, prefab_uuid);
+            return;
+        }
+
+        let previous_document = resources
+            .get::<EditorStateResource>()
+            .unwrap()
+            .active_document;
+
+        Self::park_active_document(world, resources);
+
+        let new_world = {
+            let universe = resources.get::<UniverseResource>().unwrap();
+            let world = universe.universe.create_world();
+            world
+        };
+        *world = new_world;
+
+        if let Err(error) = Self::open_prefab(world, resources, prefab_uuid) {
+            // Go back to the document that was open before
+            if let Some(previous_document) = previous_document {
+                Self::activate_document(world, resources, previous_document);
+            }
+
+            resources
+                .get_mut::<EditorStateResource>()
+                .unwrap()
+                .set_load_error(error);
+            return;
+        }
+
+        let mut editor_state = resources.get_mut::<EditorStateResource>().unwrap();
+        editor_state.open_documents.push(OpenDocument {
+            uuid: prefab_uuid,
+            name: prefab_source_files::prefab_name(&prefab_source_path(&prefab_uuid)),
+            parked_state: None,
+        });
+        editor_state.active_document = Some(prefab_uuid);
+        editor_state.check_for_recoverable_journal(prefab_uuid);
+
+        // Look at the same place as the last time this prefab was open
+        if let Some(camera_preferences) = editor_state.saved_cameras.get(&prefab_uuid) {
+            let mut camera = resources.get_mut::<CameraResource>().unwrap();
+            camera.position = camera_preferences.position;
+            camera.x_half_extents = camera_preferences.x_half_extents;
+        }
+    }
+
+    fn switch_document(
+        world: &mut World,
+        resources: &Resources,
+        prefab_uuid: AssetUuid,
+    ) {
+        if resources
+            .get::<EditorStateResource>()
+            .unwrap()
+            .active_document
+            == Some(prefab_uuid)
+        {
+            return;
+        }
+
+        Self::park_active_document(world, resources);
+        Self::activate_document(world, resources, prefab_uuid);
+    }
+
+    fn close_document(
+        world: &mut World,
+        resources: &Resources,
+        prefab_uuid: AssetUuid,
+    ) {
+        let was_active = resources
+            .get::<EditorStateResource>()
+            .unwrap()
+            .active_document
+            == Some(prefab_uuid);
+
+        if was_active {
+            Self::park_active_document(world, resources);
+        }
+
+        let next_document = {
+            let mut editor_state = resources.get_mut::<EditorStateResource>().unwrap();
+            let index = editor_state
+                .open_documents
+                .iter()
+                .position(|document| document.uuid == prefab_uuid);
+
+            if let Some(index) = index {
+                // The changes are being discarded, so the journal isn't needed anymore
+                let document = editor_state.open_documents.remove(index);
+                if let Some(mut parked_state) = document.parked_state {
+                    parked_state.edit_journal.clear();
+                    editor_state.saved_cameras.insert(
+                        document.uuid,
+                        CameraPreferences {
+                            position: parked_state.camera_position,
+                            x_half_extents: parked_state.camera_x_half_extents,
+                        },
+                    );
+                }
+            }
+
+            editor_state
+                .open_documents
+                .last()
+                .map(|document| document.uuid)
+        };
+
+        // If the active document was closed, show another one
+        if was_active {
+            if let Some(next_document) = next_document {
+                Self::activate_document(world, resources, next_document);
+            }
+        }
+    }
+
+    // Moves the state of the active document out of EditorStateResource and removes its entities
+    // from the world
+    fn park_active_document(
+        world: &mut World,
+        resources: &Resources,
+    ) {
+        let mut editor_state = resources.get_mut::<EditorStateResource>().unwrap();
+        let mut selection_resource = resources.get_mut::<EditorSelectionResource>().unwrap();
+        let camera = resources.get::<CameraResource>().unwrap();
+
+        let active_document = match editor_state.active_document.take() {
+            Some(active_document) => active_document,
+            None => return,
+        };
+
+        let opened_prefab = match editor_state.opened_prefab.take() {
+            Some(opened_prefab) => opened_prefab,
+            None => return,
+        };
+
+        // Selection is stored by UUID since world entities will be different when the document is
+        // spawned again
+        editor_state.opened_prefab = Some(opened_prefab.clone());
+        let selected_uuids = editor_state.get_selected_uuids(&*selection_resource, world);
+        editor_state.opened_prefab = None;
+        selection_resource.enqueue_clear_selection();
+
+        for x in opened_prefab.prefab_to_world_mappings.values() {
+            world.delete(*x);
+        }
+
+        // Any in-progress transaction belongs to this document
+        editor_state.current_transaction_info = None;
+        editor_state.gizmo_transaction = None;
+        editor_state.gizmo_drag_pivot = None;
+        editor_state.gizmo_drag_accumulated = None;
+        editor_state.pending_merge = None;
+
+        let parked_state = DocumentState {
+            opened_prefab,
+            undo_chain: std::mem::replace(&mut editor_state.undo_chain, Default::default()),
+            undo_chain_position: editor_state.undo_chain_position,
+            saved_undo_chain_position: editor_state.saved_undo_chain_position,
+            edit_journal: std::mem::replace(&mut editor_state.edit_journal, EditJournal::new()),
+            recoverable_journal_steps: editor_state.recoverable_journal_steps.take(),
+            declined_reload_version: editor_state.declined_reload_version.take(),
+            selected_uuids,
+            camera_position: camera.position,
+            camera_x_half_extents: camera.x_half_extents,
+        };
+
+        editor_state.clear_undo_history();
+
+        if let Some(document) = editor_state
+            .open_documents
+            .iter_mut()
+            .find(|document| document.uuid == active_document)
+        {
+            document.parked_state = Some(parked_state);
+        }
+    }
+
+    // Moves the state of the given document back into EditorStateResource and spawns it. The
+    // prefab is re-cooked so that changes to prefabs it references (possibly made in another
+    // document) are picked up.
+    fn activate_document(
+        world: &mut World,
+        resources: &Resources,
+        prefab_uuid: AssetUuid,
+    ) {
+        let (selected_uuids, has_unsaved_changes, can_rebase) = {
+            let mut editor_state = resources.get_mut::<EditorStateResource>().unwrap();
+            let parked_state = editor_state
+                .open_documents
+                .iter_mut()
+                .find(|document| document.uuid == prefab_uuid)
+                .and_then(|document| document.parked_state.take());
+
+            let parked_state = match parked_state {
+                Some(parked_state) => parked_state,
+                None => return,
+            };
+
+            let mut camera = resources.get_mut::<CameraResource>().unwrap();
+            camera.position = parked_state.camera_position;
+            camera.x_half_extents = parked_state.camera_x_half_extents;
+
+            // The entities this document was spawned as have been deleted
+            let opened_prefab = &parked_state.opened_prefab;
+            editor_state.opened_prefab = Some(Arc::new(OpenedPrefabState {
+                uuid: opened_prefab.uuid,
+                version: opened_prefab.version,
+                prefab_handle: opened_prefab.prefab_handle.clone(),
+                uncooked_prefab: opened_prefab.uncooked_prefab.clone(),
+                cooked_prefab: opened_prefab.cooked_prefab.clone(),
+                base_prefab: opened_prefab.base_prefab.clone(),
+                prefab_to_world_mappings: Default::default(),
+                world_to_prefab_mappings: Default::default(),
+            }));
+
+            editor_state.undo_chain = parked_state.undo_chain;
+            editor_state.undo_chain_position = parked_state.undo_chain_position;
+            editor_state.saved_undo_chain_position = parked_state.saved_undo_chain_position;
+            editor_state.edit_journal = parked_state.edit_journal;
+            editor_state.recoverable_journal_steps = parked_state.recoverable_journal_steps;
+            editor_state.declined_reload_version = parked_state.declined_reload_version;
+            editor_state.active_document = Some(prefab_uuid);
+
+            (
+                parked_state.selected_uuids,
+                editor_state.has_unsaved_changes(),
+                editor_state.unsaved_undo_steps().is_some(),
+            )
+        };
+
+        if !has_unsaved_changes {
+            Self::reload_opened_prefab(world, resources);
+        } else {
+            // Spawn the document as it was parked, then bring in anything that changed on disk
+            // while it was parked the same way hot reloading does
+            Self::reset(world, resources);
+
+            let changed_version = {
+                let editor_state = resources.get::<EditorStateResource>().unwrap();
+                let asset_resource = resources.get::<AssetResource>().unwrap();
+                let opened_prefab = editor_state.opened_prefab.as_ref().unwrap();
+                asset_resource
+                    .storage()
+                    .get_version(&opened_prefab.prefab_handle)
+                    .filter(|version| *version != opened_prefab.version)
+                    .filter(|version| editor_state.declined_reload_version != Some(*version))
+            };
+
+            // If the changes can't be rebased, hot reloading asks whether to discard them instead
+            if let Some(version) = changed_version {
+                if can_rebase {
+                    Self::merge_or_resolve_external_changes(world, resources, version);
+                }
+            }
+        }
+
+        let mut editor_state = resources.get_mut::<EditorStateResource>().unwrap();
+        let mut selection_resource = resources.get_mut::<EditorSelectionResource>().unwrap();
+        editor_state.restore_selected_uuids(&mut *selection_resource, world, &selected_uuids);
+    }
+
+    // If there are unsaved changes, hold the action until the user confirms it and return true.
+    // Otherwise return false and let the caller perform it immediately
+    fn confirm_discard_changes_required(
+        resources: &Resources,
+        action: DiscardChangesAction,
+    ) -> bool {
+        let mut editor_state = resources.get_mut::<EditorStateResource>().unwrap();
+        let has_unsaved_changes = match action {
+            DiscardChangesAction::CloseDocument(prefab_uuid) => {
+                editor_state.document_has_unsaved_changes(prefab_uuid)
+            }
+            DiscardChangesAction::HotReload(_) => editor_state.has_unsaved_changes(),
+            DiscardChangesAction::Quit => {
+                !editor_state.documents_with_unsaved_changes().is_empty()
+            }
+        };
+
+        if has_unsaved_changes {
+            log::info!("Unsaved changes exist, asking for confirmation before discarding them");
+            editor_state.pending_discard_changes_action = Some(action);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn perform_discard_changes_action(
+        world: &mut World,
+        resources: &Resources,
+        action: DiscardChangesAction,
+    ) {
+        match action {
+            DiscardChangesAction::CloseDocument(asset_uuid) => {
+                Self::close_document(world, resources, asset_uuid);
+            }
+            DiscardChangesAction::HotReload(_) => {
+                {
+                    // The edits are being thrown away, so drop the undo history and journal too
+                    let mut editor_state = resources.get_mut::<EditorStateResource>().unwrap();
+                    editor_state.clear_undo_history();
+                    editor_state.edit_journal.clear();
+                }
+
+                Self::reload_opened_prefab(world, resources);
+            }
+            DiscardChangesAction::Quit => {
+                let mut app_control = resources.get_mut::<AppControlResource>().unwrap();
+                app_control.enqueue_terminate_process();
+            }
+        }
+    }
+
+    pub fn hot_reload_if_asset_changed(
+        world: &mut World,
+        resources: &Resources,
+    ) {
+        // Detect if we need to reload. Do this comparing the version the cooked prefab was made
+        // from with the latest version the asset storage has told us about
+        let mut new_version = None;
+        {
+            let mut editor_state = resources.get_mut::<EditorStateResource>().unwrap();
+            let editor_state = &mut *editor_state;
+
+            // Drain the notifications every frame (even while playing) so that they don't pile up
+            let prefab_version_changes =
+                editor_state.prefab_version_changes.get_or_insert_with(|| {
+                    let asset_resource = resources.get::<AssetResource>().unwrap();
+                    asset_resource.storage().subscribe::<PrefabAsset>()
+                });
+
+            for change in prefab_version_changes.try_iter() {
+                log::info!(
+                    "Prefab {:?} changed from version {:?} to {}",
+                    change.load_handle,
+                    change.previous_version,
+                    change.version
+                );
+                editor_state
+                    .latest_prefab_versions
+                    .insert(change.load_handle, change.version);
+            }
+
+            // The prefab is frozen while playing. The change is picked up when play ends
+            if editor_state.is_playing() {
+                return;
+            }
+
+            if let Some(opened_prefab) = &editor_state.opened_prefab {
+                let load_handle = opened_prefab.prefab_handle.load_handle();
+                if let Some(version) = editor_state.latest_prefab_versions.get(&load_handle) {
+                    if opened_prefab.version != *version {
+                        new_version = Some(*version);
                     }
                 }
             }
         }
-        selected_uuids
+
+        if let Some(version) = new_version {
+            {
+                let mut editor_state = resources.get_mut::<EditorStateResource>().unwrap();
+                if editor_state.has_unsaved_changes() {
+                    // Don't do anything if the user already declined this version or is being
+                    // asked about it
+                    if editor_state.declined_reload_version == Some(version)
+                        || editor_state.pending_discard_changes_action.is_some()
+                        || editor_state.pending_merge.is_some()
+                    {
+                        return;
+                    }
+
+                    // The editor's changes can only be rebased if the steps since the last save
+                    // are all applied. If the saved state was undone past or dropped from the
+                    // undo chain, the only options are to discard the changes or ignore the file
+                    if editor_state.unsaved_undo_steps().is_none() {
+                        log::info!("Source file change detected, but there are unsaved changes");
+                        editor_state.pending_discard_changes_action =
+                            Some(DiscardChangesAction::HotReload(version));
+                        return;
+                    }
+                }
+            }
+
+            let has_unsaved_changes = resources
+                .get::<EditorStateResource>()
+                .unwrap()
+                .has_unsaved_changes();
+
+            if has_unsaved_changes {
+                log::info!("Source file change detected, merging with unsaved changes");
+                Self::merge_or_resolve_external_changes(world, resources, version);
+            } else {
+                log::info!("Source file change detected, reloading");
+                Self::reload_opened_prefab(world, resources);
+            }
+        }
     }
 
-    fn restore_selected_uuids(
-        &mut self,
-        selection_resource: &mut EditorSelectionResource,
-        world: &World,
-        selected_uuids: &HashSet<EntityUuid>,
+    // Merges the given version of the file on disk with the editor's unsaved changes if nothing
+    // conflicts. Otherwise the conflicts are held for the user to resolve
+    fn merge_or_resolve_external_changes(
+        world: &mut World,
+        resources: &Resources,
+        version: u32,
     ) {
-        let mut selected_entities: HashSet<Entity> = HashSet::default();
-        for selected_uuid in selected_uuids {
-            if let Some(opened_prefab) = self.opened_prefab.as_ref() {
-                if let Some(prefab_entity) =
-                    &opened_prefab.cooked_prefab.entities.get(selected_uuid)
-                {
-                    let world_entity = opened_prefab.prefab_to_world_mappings[prefab_entity];
-                    selected_entities.insert(world_entity);
-                }
+        match Self::find_merge_conflicts(resources) {
+            Ok(conflicts) if conflicts.is_empty() => {
+                let take_theirs = HashSet::default();
+                Self::merge_external_changes(world, resources, version, &take_theirs);
+            }
+            Ok(conflicts) => {
+                log::info!("Found {} conflicting components", conflicts.len());
+                let mut editor_state = resources.get_mut::<EditorStateResource>().unwrap();
+                editor_state.pending_merge = Some(PendingMerge { version, conflicts });
+            }
+            // Without knowing what conflicts, the changes can't be merged safely. Let the user
+            // decide whether to discard their changes or ignore the file
+            Err(e) => {
+                log::error!("Could not merge source file change: {}", e);
+                let mut editor_state = resources.get_mut::<EditorStateResource>().unwrap();
+                editor_state.pending_discard_changes_action =
+                    Some(DiscardChangesAction::HotReload(version));
             }
         }
+    }
 
-        selection_resource.enqueue_set_selection(selected_entities.into_iter().collect());
+    // The undo steps that have been applied since the prefab was last saved. Returns None if the
+    // current state isn't the saved state plus some number of applied steps
+    fn unsaved_undo_steps(&self) -> Option<Vec<Arc<TransactionDiffs>>> {
+        let saved_undo_chain_position = self.saved_undo_chain_position?;
+        if saved_undo_chain_position > self.undo_chain_position {
+            return None;
+        }
+
+        Some(
+            self.undo_chain
+                .range(saved_undo_chain_position..self.undo_chain_position)
+                .map(|undo_step| undo_step.diffs.clone())
+                .collect(),
+        )
     }
 
-    pub fn hot_reload_if_asset_changed(
+    // Copies the latest version of the opened prefab out of asset storage
+    fn load_latest_prefab(
+        resources: &Resources,
+        opened_prefab: &OpenedPrefabState,
+    ) -> Arc<Prefab> {
+        let asset_resource = resources.get::<AssetResource>().unwrap();
+        let universe = resources.get::<UniverseResource>().unwrap();
+
+        // Duplicate the prefab data so we can apply diffs to it (see open_prefab)
+        let noop_diff = WorldDiff::new(vec![], vec![]);
+        let component_registry = crate::create_component_registry();
+        Arc::new(legion_transaction::apply_diff_to_prefab(
+            &asset_resource
+                .storage()
+                .get(&opened_prefab.prefab_handle)
+                .unwrap()
+                .prefab,
+            &universe.universe,
+            &noop_diff,
+            &crate::create_component_registry_by_uuid(),
+            &CopyCloneImpl::new(&component_registry),
+        ))
+    }
+
+    // Does a three-way comparison between the prefab when it was last loaded, the prefab with the
+    // editor's changes, and the prefab on disk
+    fn find_merge_conflicts(resources: &Resources) -> Result<Vec<ComponentConflict>, String> {
+        let opened_prefab = resources
+            .get::<EditorStateResource>()
+            .unwrap()
+            .opened_prefab
+            .clone();
+
+        if let Some(opened_prefab) = opened_prefab {
+            let theirs = Self::load_latest_prefab(resources, &*opened_prefab);
+            crate::create_prefab_merge_registry().find_conflicts(
+                &opened_prefab.base_prefab,
+                &opened_prefab.uncooked_prefab,
+                &theirs,
+            )
+        } else {
+            Ok(vec![])
+        }
+    }
+
+    // Loads the prefab from disk and replays the editor's unsaved changes on top of it. Changes to
+    // components in take_theirs are dropped so that the values on disk are kept
+    fn merge_external_changes(
         world: &mut World,
         resources: &Resources,
+        version: u32,
+        take_theirs: &HashSet<(EntityUuid, ComponentTypeUuid)>,
     ) {
-        // Detect if we need to reload. Do this comparing the prefab asset's version with the cooked prefab's version
-        let mut prefab_to_reload = None;
-        {
+        let (opened_prefab, unsaved_undo_steps) = {
             let editor_state = resources.get::<EditorStateResource>().unwrap();
-            if let Some(opened_prefab) = &editor_state.opened_prefab {
-                let mut asset_resource = resources.get_mut::<AssetResource>().unwrap();
-                let version = opened_prefab
-                    .prefab_handle
-                    .asset_version::<PrefabAsset, _>(asset_resource.storage())
-                    .unwrap();
-                if opened_prefab.version != version {
-                    prefab_to_reload = Some(opened_prefab.clone());
+            match (&editor_state.opened_prefab, editor_state.unsaved_undo_steps()) {
+                (Some(opened_prefab), Some(unsaved_undo_steps)) => {
+                    (opened_prefab.clone(), unsaved_undo_steps)
+                }
+                _ => return,
+            }
+        };
+
+        let selected_uuids = {
+            let mut editor_state = resources.get_mut::<EditorStateResource>().unwrap();
+            let selection_resource = resources.get::<EditorSelectionResource>().unwrap();
+            editor_state.get_selected_uuids(&*selection_resource, world)
+        };
+
+        // Load and cook the version on disk. Referenced prefabs with unsaved changes in other
+        // documents are cooked from memory
+        let theirs = Self::load_latest_prefab(resources, &*opened_prefab);
+        let cooked_prefab = {
+            let in_memory_prefabs = resources
+                .get::<EditorStateResource>()
+                .unwrap()
+                .unsaved_document_prefabs();
+
+            let mut asset_resource = resources.get_mut::<AssetResource>().unwrap();
+            let universe = resources.get::<UniverseResource>().unwrap();
+            crate::prefab_cooking::cook_prefab(
+                &*universe,
+                &mut *asset_resource,
+                &crate::create_component_registry(),
+                &crate::create_component_registry_by_uuid(),
+                &in_memory_prefabs,
+                opened_prefab.uuid,
+            )
+        };
+
+        // If a prefab it references fails to load, keep the editor's version as it is
+        let mut cooked_prefab = match cooked_prefab {
+            Ok(cooked_prefab) => Arc::new(cooked_prefab),
+            Err(error) => {
+                resources
+                    .get_mut::<EditorStateResource>()
+                    .unwrap()
+                    .set_load_error(error);
+                return;
+            }
+        };
+
+        // Replay the unsaved changes on top of it
+        let mut uncooked_prefab = theirs.clone();
+        {
+            let universe = resources.get::<UniverseResource>().unwrap();
+            let component_registry = crate::create_component_registry();
+            let component_registry_by_uuid = crate::create_component_registry_by_uuid();
+            let copy_clone_impl = CopyCloneImpl::new(&component_registry);
+
+            for diffs in &unsaved_undo_steps {
+                let diff = prefab_merge::filter_world_diff(&diffs.apply_diff(), take_theirs);
+
+                cooked_prefab = Arc::new(legion_transaction::apply_diff_to_cooked_prefab(
+                    &cooked_prefab,
+                    &universe.universe,
+                    &diff,
+                    &component_registry_by_uuid,
+                    &copy_clone_impl,
+                ));
+
+                uncooked_prefab = Arc::new(legion_transaction::apply_diff_to_prefab(
+                    &uncooked_prefab,
+                    &universe.universe,
+                    &diff,
+                    &component_registry_by_uuid,
+                    &copy_clone_impl,
+                ));
+            }
+        }
+
+        // Delete the old stuff from the world
+        for x in opened_prefab.prefab_to_world_mappings.values() {
+            world.delete(*x);
+        }
+
+        {
+            let mut editor_state = resources.get_mut::<EditorStateResource>().unwrap();
+            editor_state.opened_prefab = Some(Arc::new(OpenedPrefabState {
+                uuid: opened_prefab.uuid,
+                version,
+                prefab_handle: opened_prefab.prefab_handle.clone(),
+                uncooked_prefab,
+                cooked_prefab,
+                base_prefab: theirs,
+                prefab_to_world_mappings: Default::default(), // These will get populated by reset()
+                world_to_prefab_mappings: Default::default(), // These will get populated by reset()
+            }));
+
+            // Rebase the undo chain onto the merged prefab. The editor's changes to components
+            // taken from disk are dropped from every step so that undo and redo leave the values
+            // from disk alone, and the rest of each step is kept. Undoing back to the saved
+            // position produces what is on disk
+            let editor_state = &mut *editor_state;
+            if !take_theirs.is_empty() {
+                for undo_step in &mut editor_state.undo_chain {
+                    undo_step.diffs = Arc::new(prefab_merge::filter_transaction_diffs(
+                        &undo_step.diffs,
+                        take_theirs,
+                    ));
                 }
             }
+
+            // The journal is replayed on top of the file on disk, so restart it from the rebased
+            // unsaved steps
+            editor_state.edit_journal.begin(opened_prefab.uuid);
+            if let Some(saved_undo_chain_position) = editor_state.saved_undo_chain_position {
+                let unsaved_steps = editor_state
+                    .undo_chain
+                    .range(saved_undo_chain_position..editor_state.undo_chain_position);
+                for undo_step in unsaved_steps {
+                    editor_state
+                        .edit_journal
+                        .record(undo_step.description.clone(), (*undo_step.diffs).clone());
+                }
+            }
+            editor_state.edit_journal.write();
         }
 
-        // If prefab_to_reload is not none, do the reload
-        if let Some(opened_prefab) = prefab_to_reload {
-            log::info!("Source file change detected, reloading");
+        // Spawn everything
+        Self::reset(world, resources);
+
+        let mut editor_state = resources.get_mut::<EditorStateResource>().unwrap();
+        let mut selection_resource = resources.get_mut::<EditorSelectionResource>().unwrap();
+        editor_state.restore_selected_uuids(&mut *selection_resource, world, &selected_uuids);
+    }
+
+    // Re-cooks and reloads the opened prefab, keeping the current selection
+    fn reload_opened_prefab(
+        world: &mut World,
+        resources: &Resources,
+    ) {
+        let opened_prefab = resources
+            .get::<EditorStateResource>()
+            .unwrap()
+            .opened_prefab
+            .clone();
 
+        if let Some(opened_prefab) = opened_prefab {
             // Save the selected entity UUIDs
             let selected_uuids = {
                 let mut editor_state = resources.get_mut::<EditorStateResource>().unwrap();
@@ -664,36 +2622,87 @@ impl EditorStateResource {
                 editor_state.get_selected_uuids(&*selection_resource, world)
             };
 
+            // re-cook and load the prefab. If it fails, the version that was loaded before stays
+            // open. A document that was just activated has nothing spawned yet, so spawn it
+            if let Err(error) = Self::open_prefab(world, resources, opened_prefab.uuid) {
+                if opened_prefab.prefab_to_world_mappings.is_empty() {
+                    Self::reset(world, resources);
+                }
+
+                resources
+                    .get_mut::<EditorStateResource>()
+                    .unwrap()
+                    .set_load_error(error);
+                return;
+            }
+
             // Delete the old stuff from the world
             for x in opened_prefab.prefab_to_world_mappings.values() {
                 world.delete(*x);
             }
 
-            // re-cook and load the prefab
-            Self::open_prefab(world, resources, opened_prefab.uuid);
+            let mut editor_state = resources.get_mut::<EditorStateResource>().unwrap();
+
+            // Journaled steps were made against the previous version of the prefab and can't be
+            // replayed on the reloaded one, so start a new journal from here
+            editor_state.edit_journal.begin(opened_prefab.uuid);
 
             // Restore selection
-            let mut editor_state = resources.get_mut::<EditorStateResource>().unwrap();
             let mut selection_resource = resources.get_mut::<EditorSelectionResource>().unwrap();
             editor_state.restore_selected_uuids(&mut *selection_resource, world, &selected_uuids);
         }
     }
 
+    // The prefab is frozen while playing, so edits are rejected. The reason is shown in the main
+    // menu for a few seconds (see rejected_edit_message)
+    fn reject_edit_while_playing(
+        &mut self,
+        message: &'static str,
+    ) {
+        log::warn!("{}", message);
+        self.rejected_edit = Some((message, Instant::now()));
+    }
+
     pub fn enqueue_diffs(
         &mut self,
         diffs: TransactionDiffs,
+        description: String,
         commit_changes: bool,
         post_commit_selection: PostCommitSelection,
     ) {
+        if self.is_playing() && !self.accepting_changes_from_play {
+            self.reject_edit_while_playing("Can't edit the prefab while playing");
+            return;
+        }
+
         if diffs.apply_diff().has_changes() {
             self.diffs_pending_apply.push(TransactionDiffsPendingApply {
                 diffs,
+                description,
                 commit_changes,
                 post_commit_selection,
             });
         }
     }
 
+    // Queues changes kept from play through enqueue_diffs as a committed change, so they get an
+    // undo step and are journaled like any other edit. These are the only edits accepted while
+    // playing
+    fn enqueue_diffs_kept_from_play(
+        &mut self,
+        diffs: TransactionDiffs,
+        description: String,
+    ) {
+        self.accepting_changes_from_play = true;
+        self.enqueue_diffs(
+            diffs,
+            description,
+            true,
+            PostCommitSelection::KeepCurrentSelection,
+        );
+        self.accepting_changes_from_play = false;
+    }
+
     pub fn process_diffs(
         world: &mut World,
         resources: &mut Resources,
@@ -732,7 +2741,23 @@ impl EditorStateResource {
             // If commit is flagged, add an undo step will be added
             if queued_diff.commit_changes {
                 let mut editor_state = resources.get_mut::<EditorStateResource>().unwrap();
-                editor_state.push_to_undo_queue(queued_diff.diffs);
+                editor_state.push_to_undo_queue(queued_diff.diffs, queued_diff.description);
+            }
+        }
+    }
+
+    // Queues the recovered journal steps to be applied. Each one is committed, so they are
+    // added to the undo chain (and re-recorded in the journal) as if the user had just made them
+    fn recover_journal(&mut self) {
+        if let Some(steps) = self.recoverable_journal_steps.take() {
+            log::info!("Recovering {} steps from edit journal", steps.len());
+            for step in steps {
+                self.enqueue_diffs(
+                    step.diffs,
+                    step.description,
+                    true,
+                    PostCommitSelection::KeepCurrentSelection,
+                );
             }
         }
     }
@@ -740,21 +2765,37 @@ impl EditorStateResource {
     fn clear_undo_history(&mut self) {
         self.undo_chain.clear();
         self.undo_chain_position = 0;
+        self.saved_undo_chain_position = Some(0);
     }
 
     fn push_to_undo_queue(
         &mut self,
         diffs: TransactionDiffs,
+        description: String,
     ) {
-        // Drop everything that follows the current undo chain index
+        // Drop everything that follows the current undo chain index. If the saved state is in the
+        // part of the chain being dropped, it can't be returned to anymore
+        if let Some(saved_undo_chain_position) = self.saved_undo_chain_position {
+            if saved_undo_chain_position > self.undo_chain_position {
+                self.saved_undo_chain_position = None;
+            }
+        }
+
         self.undo_chain.truncate(self.undo_chain_position);
 
+        self.edit_journal.record(description.clone(), diffs.clone());
+
         // Push the given data onto the chain
-        self.undo_chain.push_back(Arc::new(diffs));
+        self.undo_chain.push_back(UndoStep {
+            description,
+            diffs: Arc::new(diffs),
+        });
 
         // We assume the caller has done whatever was needed
         self.undo_chain_position += 1;
 
+        self.trim_undo_chain();
+
         log::info!(
             "Pushed to undo queue, undo chain length: {} position: {}",
             self.undo_chain.len(),
@@ -762,6 +2803,49 @@ impl EditorStateResource {
         );
     }
 
+    fn trim_undo_chain(&mut self) {
+        trim_undo_steps(
+            &mut self.undo_chain,
+            &mut self.undo_chain_position,
+            &mut self.saved_undo_chain_position,
+            self.max_undo_steps,
+        );
+    }
+
+    fn jump_to_undo_position(
+        world: &mut World,
+        resources: &Resources,
+        undo_chain_position: usize,
+    ) {
+        let (current_position, undo_chain_len) = {
+            let mut editor_state = resources.get_mut::<EditorStateResource>().unwrap();
+            if editor_state.is_playing() {
+                editor_state
+                    .reject_edit_while_playing("Can't change the undo position while playing");
+                return;
+            }
+
+            (editor_state.undo_chain_position, editor_state.undo_chain.len())
+        };
+
+        let undo_chain_position = undo_chain_position.min(undo_chain_len);
+        log::info!(
+            "Jumping from undo chain position {} to {}",
+            current_position,
+            undo_chain_position
+        );
+
+        // Step through the chain one entry at a time so that each diff is applied against the
+        // state it was created from
+        for _ in undo_chain_position..current_position {
+            Self::undo(world, resources);
+        }
+
+        for _ in current_position..undo_chain_position {
+            Self::redo(world, resources);
+        }
+    }
+
     fn undo(
         world: &mut World,
         resources: &Resources,
@@ -775,12 +2859,24 @@ impl EditorStateResource {
                 editor_state.undo_chain_position
             );
 
-            if editor_state.undo_chain_position > 0 {
+            if editor_state.is_playing() {
+                editor_state.reject_edit_while_playing("Can't undo while playing");
+                None
+            } else if editor_state.undo_chain_position > 0 {
                 // reduce undo_index
                 editor_state.undo_chain_position -= 1;
 
                 // undo whatever is at self.undo_chain[self.undo_chain_index]
-                Some(editor_state.undo_chain[editor_state.undo_chain_position].clone())
+                let undo_step = &editor_state.undo_chain[editor_state.undo_chain_position];
+                let diffs = undo_step.diffs.clone();
+
+                // Journal the undo as a step that reverts the change
+                let mut journal_diffs = (*diffs).clone();
+                journal_diffs.reverse();
+                let description = format!("Undo {}", undo_step.description);
+                editor_state.edit_journal.record(description, journal_diffs);
+
+                Some(diffs)
             } else {
                 None
             }
@@ -809,9 +2905,17 @@ impl EditorStateResource {
                 editor_state.undo_chain_position
             );
 
-            if editor_state.undo_chain_position < editor_state.undo_chain.len() {
+            if editor_state.is_playing() {
+                editor_state.reject_edit_while_playing("Can't redo while playing");
+                None
+            } else if editor_state.undo_chain_position < editor_state.undo_chain.len() {
                 // redo whatever is at self.undo_chain[self.undo_chain_index]
-                let diffs = editor_state.undo_chain[editor_state.undo_chain_position].clone();
+                let undo_step = &editor_state.undo_chain[editor_state.undo_chain_position];
+                let diffs = undo_step.diffs.clone();
+                let description = format!("Redo {}", undo_step.description);
+                editor_state
+                    .edit_journal
+                    .record(description, (*diffs).clone());
 
                 // increase undo_index
                 editor_state.undo_chain_position += 1;
@@ -838,9 +2942,10 @@ impl EditorStateResource {
         diffs: &WorldDiff,
         post_commit_selection: PostCommitSelection,
     ) {
-        let selected_uuids = {
+        let (selected_uuids, is_playing) = {
             let mut selection_resource = resources.get_mut::<EditorSelectionResource>().unwrap();
             let mut editor_state = resources.get_mut::<EditorStateResource>().unwrap();
+            let is_playing = editor_state.is_playing();
 
             // Clone the currently opened prefab Arc so we can refer back to it
             let mut opened_prefab = {
@@ -854,9 +2959,12 @@ impl EditorStateResource {
             // Get the UUIDs of all selected entities
             let selected_uuids = editor_state.get_selected_uuids(&mut *selection_resource, world);
 
-            // Delete the old stuff from the world
-            for x in opened_prefab.prefab_to_world_mappings.values() {
-                world.delete(*x);
+            // Delete the old stuff from the world. While playing, only the prefab is changed and
+            // the play world is left alone
+            if !is_playing {
+                for x in opened_prefab.prefab_to_world_mappings.values() {
+                    world.delete(*x);
+                }
             }
 
             {
@@ -883,24 +2991,40 @@ impl EditorStateResource {
                     &copy_clone_impl
                 ));
 
-                // Update the opened prefab state
+                // Update the opened prefab state. While playing, the world isn't respawned so the
+                // mappings to the play world are kept
+                let (prefab_to_world_mappings, world_to_prefab_mappings) = if is_playing {
+                    (
+                        opened_prefab.prefab_to_world_mappings.clone(),
+                        opened_prefab.world_to_prefab_mappings.clone(),
+                    )
+                } else {
+                    // These will get populated by reset()
+                    (Default::default(), Default::default())
+                };
+
                 let new_opened_prefab = OpenedPrefabState {
                     uuid: opened_prefab.uuid,
                     cooked_prefab: new_cooked_prefab,
                     prefab_handle: opened_prefab.prefab_handle.clone(),
                     uncooked_prefab: new_uncooked_prefab,
+                    base_prefab: opened_prefab.base_prefab.clone(),
                     version: opened_prefab.version,
-                    prefab_to_world_mappings: Default::default(), // These will get populated by reset()
-                    world_to_prefab_mappings: Default::default(), // These will get populated by reset()
+                    prefab_to_world_mappings,
+                    world_to_prefab_mappings,
                 };
 
                 // Set opened_prefab (TODO: Probably better to pass new_opened_prefab in and let reset() assign to opened_prefab)
                 editor_state.opened_prefab = Some(Arc::new(new_opened_prefab));
             }
 
-            selected_uuids
+            (selected_uuids, is_playing)
         };
 
+        if is_playing {
+            return;
+        }
+
         // Spawn everything
         Self::reset(world, resources);
 
@@ -933,40 +3057,50 @@ impl EditorStateResource {
         }
     }
 
-    fn save(&mut self) {
+    // Returns false if the prefab couldn't be written, in which case save_error says why
+    fn save(&mut self) -> bool {
         //
         // Check that a prefab is opened
         //
         if self.opened_prefab.is_none() {
-            return;
+            return false;
         }
 
-        let opened_prefab = self.opened_prefab.as_ref().unwrap();
+        let opened_prefab = self.opened_prefab.as_ref().unwrap().clone();
 
         //
         // Persist the uncooked prefab to disk
         //
-        let registered_components = crate::create_component_registry_by_uuid();
-        let prefab_serde_context = legion_prefab::PrefabSerdeContext {
-            registered_components,
-        };
-
-        let mut ron_ser = ron::ser::Serializer::new(Some(ron::ser::PrettyConfig::default()), true);
-        let prefab_ser = legion_prefab::PrefabFormatSerializer::new(
-            &prefab_serde_context,
-            &opened_prefab.uncooked_prefab,
-        );
-        prefab_format::serialize(
-            &mut ron_ser,
-            &prefab_ser,
-            opened_prefab.uncooked_prefab.prefab_id(),
-        )
-        .expect("failed to round-trip prefab");
-        let output = ron_ser.into_output_string();
+        let output = prefab_merge::serialize_prefab_to_ron(&opened_prefab.uncooked_prefab);
         log::trace!("Exporting prefab:");
         log::trace!("{}", output);
 
-        std::fs::write("assets/demo_level.prefab", output).unwrap();
+        let path = prefab_source_path(&opened_prefab.uuid);
+        if let Err(e) = std::fs::write(&path, output) {
+            let error = format!("Failed to save {}: {}", path.display(), e);
+            log::error!("{}", error);
+            self.save_error = Some(error);
+            return false;
+        }
+
+        self.save_error = None;
+
+        // The saved prefab is now the common ancestor for merging any changes made on disk
+        self.opened_prefab = Some(Arc::new(OpenedPrefabState {
+            uuid: opened_prefab.uuid,
+            version: opened_prefab.version,
+            prefab_handle: opened_prefab.prefab_handle.clone(),
+            uncooked_prefab: opened_prefab.uncooked_prefab.clone(),
+            cooked_prefab: opened_prefab.cooked_prefab.clone(),
+            base_prefab: opened_prefab.uncooked_prefab.clone(),
+            prefab_to_world_mappings: opened_prefab.prefab_to_world_mappings.clone(),
+            world_to_prefab_mappings: opened_prefab.world_to_prefab_mappings.clone(),
+        }));
+
+        // Everything in the journal is now persisted in the prefab itself
+        self.edit_journal.clear();
+        self.saved_undo_chain_position = Some(self.undo_chain_position);
+        true
     }
 
     pub fn create_empty_transaction(
@@ -1027,6 +3161,74 @@ impl EditorStateResource {
             None
         }
     }
+
+    /// Returns true if the component type can be added to the entity. It must not already have the
+    /// component, or any other component that would spawn into the same type. (For example, an
+    /// entity can't have both RigidBodyBallComponentDef and RigidBodyBoxComponentDef)
+    pub fn can_add_component(
+        &self,
+        world: &World,
+        entity: Entity,
+        component_type: &ComponentRegistration,
+    ) -> bool {
+        let component_type_id = component_type.component_type_id();
+        if world.has_component_by_id(entity, component_type_id) {
+            return false;
+        }
+
+        for existing_type_id in self.component_registry.keys() {
+            if world.has_component_by_id(entity, *existing_type_id)
+                && self
+                    .component_spawn_mappings
+                    .spawns_into_same_type(*existing_type_id, component_type_id)
+            {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// Adds a default-valued component of the given type to every selected entity that can accept
+    /// it, as a single undo step
+    pub fn add_component_to_selected(
+        &mut self,
+        selection_resources: &EditorSelectionResource,
+        universe_resource: &UniverseResource,
+        component_type: &ComponentRegistration,
+    ) {
+        let tx = self.create_transaction_from_selected(selection_resources, universe_resource);
+        if let Some(mut tx) = tx {
+            let all_entities: Vec<Entity> = tx.world().iter_entities().collect();
+
+            let mut added_count = 0;
+            for e in &all_entities {
+                if self.can_add_component(tx.world(), *e, component_type) {
+                    // e guaranteed to be Some, this is a new transaction and we aren't deleting entities
+                    component_type
+                        .add_default_to_entity(tx.world_mut(), *e)
+                        .unwrap();
+                    added_count += 1;
+                }
+            }
+
+            if added_count > 0 {
+                tx.set_description(format!("Add {}", component_type.type_name()));
+                tx.commit(self, PostCommitSelection::KeepCurrentSelection);
+            }
+        }
+    }
+}
+
+// The path of the source file for the given prefab. If the prefab hasn't been imported from a
+// source file, it's saved to a new file named after its UUID
+fn prefab_source_path(prefab_uuid: &AssetUuid) -> PathBuf {
+    prefab_source_files::find_prefab_source_path(prefab_uuid).unwrap_or_else(|| {
+        PathBuf::from(format!(
+            "assets/{}.prefab",
+            uuid::Uuid::from_bytes(prefab_uuid.0)
+        ))
+    })
 }
 
 #[derive(Clone, Copy, PartialEq)]
@@ -1038,6 +3240,7 @@ pub struct EditorTransactionId(uuid::Uuid);
 pub struct EditorTransaction {
     id: EditorTransactionId,
     transaction: legion_transaction::Transaction,
+    description: String,
 }
 
 impl EditorTransaction {
@@ -1050,7 +3253,23 @@ impl EditorTransaction {
         let component_registry = crate::create_component_registry();
         let transaction = builder.begin(universe, world, &CopyCloneImpl::new(&component_registry));
 
-        EditorTransaction { id, transaction }
+        EditorTransaction {
+            id,
+            transaction,
+            description: "Edit".to_string(),
+        }
+    }
+
+    /// Sets the human-readable description that will be shown for this transaction's undo step
+    pub fn set_description<T: Into<String>>(
+        &mut self,
+        description: T,
+    ) {
+        self.description = description.into();
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
     }
 
     pub fn world(&self) -> &World {
@@ -1102,7 +3321,12 @@ impl EditorTransaction {
         diffs.reverse();
 
         // Apply the diffs, this is not a commit since we don't want this in the undo queue
-        editor_state.enqueue_diffs(diffs, false, PostCommitSelection::KeepCurrentSelection);
+        editor_state.enqueue_diffs(
+            diffs,
+            self.description.clone(),
+            false,
+            PostCommitSelection::KeepCurrentSelection,
+        );
     }
 
     fn do_update(
@@ -1124,8 +3348,10 @@ impl EditorTransaction {
                 &mut current_transaction_info,
                 &mut editor_state.current_transaction_info,
             );
+            let current_transaction_info = current_transaction_info.unwrap();
             editor_state.enqueue_diffs(
-                current_transaction_info.unwrap().diffs,
+                current_transaction_info.diffs,
+                current_transaction_info.description,
                 true,
                 post_commit_selection,
             );
@@ -1145,10 +3371,85 @@ impl EditorTransaction {
             editor_state.current_transaction_info = Some(CurrentTransactionInfo {
                 id: self.id,
                 diffs: diffs.clone(),
+                description: self.description.clone(),
             });
         }
 
         // Apply the diffs, if commit_changes is true, an undo step will be added
-        editor_state.enqueue_diffs(diffs, commit_changes, post_commit_selection);
+        editor_state.enqueue_diffs(
+            diffs,
+            self.description.clone(),
+            commit_changes,
+            post_commit_selection,
+        );
+    }
+}
+
+// Shortens the chain to at most max_steps. Steps that have already been applied are dropped
+// oldest first, and only once none of those are left are undone steps dropped from the redo
+// end, so the position always keeps pointing at the same step.
+fn trim_undo_steps<T>(
+    undo_chain: &mut VecDeque<T>,
+    undo_chain_position: &mut usize,
+    saved_undo_chain_position: &mut Option<usize>,
+    max_steps: usize,
+) {
+    let excess = undo_chain.len().saturating_sub(max_steps);
+    let front_count = excess.min(*undo_chain_position);
+    undo_chain.drain(..front_count);
+    *undo_chain_position -= front_count;
+    *saved_undo_chain_position =
+        saved_undo_chain_position.and_then(|position| position.checked_sub(front_count));
+
+    if undo_chain.len() > max_steps {
+        undo_chain.truncate(max_steps);
+        if saved_undo_chain_position.map_or(false, |position| position > max_steps) {
+            *saved_undo_chain_position = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trim_drops_applied_steps_first() {
+        let mut chain: VecDeque<_> = (0..5).collect();
+        let mut position = 4;
+        let mut saved = Some(2);
+        trim_undo_steps(&mut chain, &mut position, &mut saved, 3);
+
+        assert_eq!(chain, [2, 3, 4]);
+        assert_eq!(position, 2);
+        assert_eq!(saved, Some(0));
+    }
+
+    #[test]
+    fn trim_keeps_redo_steps_after_an_early_position() {
+        let mut chain: VecDeque<_> = (0..5).collect();
+        let mut position = 1;
+        let mut saved = Some(4);
+        trim_undo_steps(&mut chain, &mut position, &mut saved, 2);
+
+        // Only step 0 was applied, so it goes first and the redo tail is cut after that
+        assert_eq!(chain, [1, 2]);
+        assert_eq!(position, 0);
+        assert_eq!(saved, None);
+
+        // Redoing still replays the step that followed the position before trimming
+        assert_eq!(chain[position], 1);
+    }
+
+    #[test]
+    fn trim_at_the_start_of_the_chain_only_drops_redo_steps() {
+        let mut chain: VecDeque<_> = (0..4).collect();
+        let mut position = 0;
+        let mut saved = Some(0);
+        trim_undo_steps(&mut chain, &mut position, &mut saved, 1);
+
+        assert_eq!(chain, [0]);
+        assert_eq!(position, 0);
+        assert_eq!(saved, Some(0));
     }
 }
@@ -2,10 +2,48 @@ mod editor_state;
 pub use editor_state::PostCommitSelection;
 pub use editor_state::EditorStateResource;
 pub use editor_state::EditorTool;
+pub use editor_state::GizmoPivotMode;
+pub use editor_state::GizmoSpace;
 pub use editor_state::EditorMode;
 pub use editor_state::EditorTransactionId;
 pub use editor_state::EditorTransaction;
 pub use editor_state::OpenedPrefabState;
+pub use editor_state::SnapSettings;
+pub use editor_state::DrawLayerSettings;
+pub use editor_state::WindowOptions;
+pub use editor_state::CommandPaletteState;
+pub use editor_state::ResolvedSnapSettings;
+pub use editor_state::UndoStep;
+pub use editor_state::DiscardChangesAction;
+pub use editor_state::PendingMerge;
+pub use editor_state::OpenDocument;
+
+mod edit_journal;
+
+mod prefab_source_files;
+pub use prefab_source_files::PrefabSourceFile;
+pub use prefab_source_files::find_prefab_source_files;
+pub use prefab_source_files::AssetSourceFile;
+pub use prefab_source_files::find_asset_source_files;
+pub use prefab_source_files::find_all_asset_source_files;
+pub use prefab_source_files::find_asset_source_file;
+pub use prefab_source_files::invalidate_asset_source_files;
+
+mod prefab_merge;
+pub use prefab_merge::ComponentConflict;
+pub use prefab_merge::FieldConflict;
+pub use prefab_merge::MergeResolution;
+pub use prefab_merge::PrefabMergeRegistry;
+
+mod editor_preferences;
+pub use editor_preferences::EditorPreferences;
+pub use editor_preferences::CameraPreferences;
+
+mod editor_keybindings;
+pub use editor_keybindings::EditorKeybindingsResource;
+pub use editor_keybindings::EditorAction;
+pub use editor_keybindings::KeyChord;
+pub use editor_keybindings::KeyModifiers;
 
 mod editor_selection;
 pub use editor_selection::EditorSelectionResource;
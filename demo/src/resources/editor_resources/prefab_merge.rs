@@ -0,0 +1,311 @@
+use std::collections::{BTreeSet, HashSet};
+use std::marker::PhantomData;
+
+use legion::storage::Component;
+use legion_prefab::Prefab;
+use legion_transaction::{TransactionDiffs, WorldDiff};
+use prefab_format::{ComponentTypeUuid, EntityUuid};
+use serde::Serialize;
+use serde_json::Value;
+use type_uuid::TypeUuid;
+
+/// How a conflicting component should be merged
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum MergeResolution {
+    /// Keep the changes made in the editor
+    KeepMine,
+
+    /// Take the changes made to the file on disk, discarding the editor's changes to the
+    /// component
+    TakeTheirs,
+}
+
+/// A field that was changed both in the editor and on disk, to different values
+pub struct FieldConflict {
+    pub field_path: String,
+    pub base: String,
+    pub mine: String,
+    pub theirs: String,
+}
+
+/// All the conflicting fields within a single component on an entity. Conflicts are resolved per
+/// component because the editor's changes are stored as diffs per component.
+pub struct ComponentConflict {
+    pub entity_uuid: EntityUuid,
+    pub component_type: ComponentTypeUuid,
+    pub component_name: String,
+    pub fields: Vec<FieldConflict>,
+    pub resolution: MergeResolution,
+}
+
+/// Serializes the prefab in the same format it is stored on disk
+pub fn serialize_prefab_to_ron(prefab: &Prefab) -> String {
+    let registered_components = crate::create_component_registry_by_uuid();
+    let prefab_serde_context = legion_prefab::PrefabSerdeContext {
+        registered_components,
+    };
+
+    let mut ron_ser = ron::ser::Serializer::new(Some(ron::ser::PrettyConfig::default()), true);
+    let prefab_ser = legion_prefab::PrefabFormatSerializer::new(&prefab_serde_context, prefab);
+    prefab_format::serialize(&mut ron_ser, &prefab_ser, prefab.prefab_id())
+        .expect("failed to round-trip prefab");
+    ron_ser.into_output_string()
+}
+
+/// A trait object which allows dynamic dispatch into comparing a component across prefabs
+trait RegisteredPrefabMergeT: Send + Sync {
+    fn component_type(&self) -> ComponentTypeUuid;
+
+    fn component_name(&self) -> &'static str;
+
+    fn find_conflicts(
+        &self,
+        base: &Prefab,
+        mine: &Prefab,
+        theirs: &Prefab,
+        entity_uuid: &EntityUuid,
+    ) -> Result<Vec<FieldConflict>, String>;
+}
+
+/// Implements the RegisteredPrefabMergeT trait object with code that reads T out of each prefab
+#[derive(Default)]
+struct RegisteredPrefabMerge<T> {
+    phantom_data: PhantomData<T>,
+}
+
+impl<T> RegisteredPrefabMerge<T>
+where
+    T: Component + Serialize + TypeUuid,
+{
+    fn new() -> Self {
+        RegisteredPrefabMerge {
+            phantom_data: Default::default(),
+        }
+    }
+
+    // Serializes the component on the entity in the prefab, if it has one
+    fn component_value(
+        prefab: &Prefab,
+        entity_uuid: &EntityUuid,
+    ) -> Result<Option<Value>, String> {
+        let entity = match prefab.prefab_meta.entities.get(entity_uuid) {
+            Some(entity) => *entity,
+            None => return Ok(None),
+        };
+
+        match prefab.world.get_component::<T>(entity) {
+            Some(component) => serde_json::to_value(&*component).map(Some).map_err(|e| {
+                format!(
+                    "Failed to read {} for merging: {}",
+                    std::any::type_name::<T>(),
+                    e
+                )
+            }),
+            None => Ok(None),
+        }
+    }
+}
+
+impl<T> RegisteredPrefabMergeT for RegisteredPrefabMerge<T>
+where
+    T: Component + Serialize + TypeUuid,
+{
+    fn component_type(&self) -> ComponentTypeUuid {
+        T::UUID
+    }
+
+    fn component_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+
+    fn find_conflicts(
+        &self,
+        base: &Prefab,
+        mine: &Prefab,
+        theirs: &Prefab,
+        entity_uuid: &EntityUuid,
+    ) -> Result<Vec<FieldConflict>, String> {
+        let base = Self::component_value(base, entity_uuid)?;
+        let mine = Self::component_value(mine, entity_uuid)?;
+        let theirs = Self::component_value(theirs, entity_uuid)?;
+
+        let mut conflicts = vec![];
+        find_field_conflicts(
+            &mut vec![],
+            base.as_ref(),
+            mine.as_ref(),
+            theirs.as_ref(),
+            &mut conflicts,
+        );
+        Ok(conflicts)
+    }
+}
+
+/// Tracks which components are compared when merging changes made on disk with the editor's
+/// changes. Changes to components that aren't registered never conflict, so the editor's changes
+/// to them are kept.
+#[derive(Default)]
+pub struct PrefabMergeRegistry {
+    registered: Vec<Box<dyn RegisteredPrefabMergeT>>,
+}
+
+impl PrefabMergeRegistry {
+    /// Adds a type to the registry, which allows conflicting changes to it to be detected
+    pub fn register<T: Component + Serialize + TypeUuid>(&mut self) {
+        self.registered
+            .push(Box::new(RegisteredPrefabMerge::<T>::new()));
+    }
+
+    /// Compares the prefab as it was last loaded (base), the prefab with the editor's changes
+    /// (mine), and the prefab that is now on disk (theirs). Any field of a registered component
+    /// that was changed to different values on both sides is returned as a conflict, grouped by
+    /// component. Fails if a component can't be serialized for comparison.
+    pub fn find_conflicts(
+        &self,
+        base: &Prefab,
+        mine: &Prefab,
+        theirs: &Prefab,
+    ) -> Result<Vec<ComponentConflict>, String> {
+        let entity_uuids: BTreeSet<EntityUuid> = base
+            .prefab_meta
+            .entities
+            .keys()
+            .chain(mine.prefab_meta.entities.keys())
+            .chain(theirs.prefab_meta.entities.keys())
+            .cloned()
+            .collect();
+
+        let mut conflicts = vec![];
+        for entity_uuid in entity_uuids {
+            for r in &self.registered {
+                let fields = r.find_conflicts(base, mine, theirs, &entity_uuid)?;
+                if !fields.is_empty() {
+                    conflicts.push(ComponentConflict {
+                        entity_uuid,
+                        component_type: r.component_type(),
+                        component_name: r.component_name().to_string(),
+                        fields,
+                        resolution: MergeResolution::KeepMine,
+                    });
+                }
+            }
+        }
+
+        Ok(conflicts)
+    }
+}
+
+/// Returns a copy of the diff without any changes to the given entity components. This is used to
+/// drop the editor's changes to components that are being taken from disk.
+pub fn filter_world_diff(
+    diff: &WorldDiff,
+    excluded_components: &HashSet<(EntityUuid, ComponentTypeUuid)>,
+) -> WorldDiff {
+    let component_diffs = diff
+        .component_diffs()
+        .iter()
+        .filter(|component_diff| {
+            !excluded_components.contains(&(
+                *component_diff.entity_uuid(),
+                *component_diff.component_type(),
+            ))
+        })
+        .cloned()
+        .collect();
+
+    WorldDiff::new(diff.entity_diffs().to_vec(), component_diffs)
+}
+
+/// Filters both directions of an undo step (see filter_world_diff), so that neither applying nor
+/// reverting it touches the excluded components
+pub fn filter_transaction_diffs(
+    diffs: &TransactionDiffs,
+    excluded_components: &HashSet<(EntityUuid, ComponentTypeUuid)>,
+) -> TransactionDiffs {
+    TransactionDiffs::new(
+        filter_world_diff(diffs.apply_diff(), excluded_components),
+        filter_world_diff(diffs.revert_diff(), excluded_components),
+    )
+}
+
+// Walks the three values together. Where only one side changed a value, or both changed it the
+// same way, there's no conflict. Otherwise structs and equal length collections are compared
+// field by field, and anything else is reported as a conflict at the current path. A missing
+// component is None.
+fn find_field_conflicts(
+    path: &mut Vec<String>,
+    base: Option<&Value>,
+    mine: Option<&Value>,
+    theirs: Option<&Value>,
+    conflicts: &mut Vec<FieldConflict>,
+) {
+    if mine == theirs || mine == base || theirs == base {
+        return;
+    }
+
+    match (base, mine, theirs) {
+        (Some(Value::Object(base)), Some(Value::Object(mine)), Some(Value::Object(theirs))) => {
+            let keys: BTreeSet<&String> = mine.keys().chain(theirs.keys()).collect();
+            for key in keys {
+                path.push(key.clone());
+                find_field_conflicts(
+                    path,
+                    base.get(key),
+                    mine.get(key),
+                    theirs.get(key),
+                    conflicts,
+                );
+                path.pop();
+            }
+        }
+        (Some(Value::Array(base)), Some(Value::Array(mine)), Some(Value::Array(theirs)))
+            if base.len() == mine.len() && mine.len() == theirs.len() =>
+        {
+            for index in 0..base.len() {
+                path.push(format!("[{}]", index));
+                find_field_conflicts(
+                    path,
+                    base.get(index),
+                    mine.get(index),
+                    theirs.get(index),
+                    conflicts,
+                );
+                path.pop();
+            }
+        }
+        _ => conflicts.push(FieldConflict {
+            field_path: path_to_string(path),
+            base: value_to_string(base),
+            mine: value_to_string(mine),
+            theirs: value_to_string(theirs),
+        }),
+    }
+}
+
+// Dot-separated for nested structs (i.e. "paint.color"), with collection indices appended
+// directly (i.e. "points[2]")
+fn path_to_string(path: &[String]) -> String {
+    let mut path_string = String::new();
+    for element in path {
+        if !path_string.is_empty() && !element.starts_with('[') {
+            path_string.push('.');
+        }
+        path_string.push_str(element);
+    }
+
+    path_string
+}
+
+fn value_to_string(value: Option<&Value>) -> String {
+    match value {
+        None => "<none>".to_string(),
+        // Components store f32s, which read better without the digits added by widening to f64
+        Some(Value::Number(n)) if n.is_f64() => (n.as_f64().unwrap() as f32).to_string(),
+        Some(Value::String(s)) => s.clone(),
+        Some(Value::Array(values)) => {
+            let values: Vec<String> = values.iter().map(|v| value_to_string(Some(v))).collect();
+            format!("({})", values.join(", "))
+        }
+        Some(other) => other.to_string(),
+    }
+}
@@ -0,0 +1,224 @@
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use atelier_core::AssetUuid;
+use serde::Deserialize;
+
+/// The directory that prefab source files are located in, including its subdirectories
+const ASSET_DIRECTORY: &str = "assets";
+
+// Incremented by invalidate_asset_source_files. A thread's cached list is rebuilt the next time
+// it's used after this changes
+static ASSET_SOURCE_FILES_GENERATION: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    // Every imported asset, and the generation it was found in. See asset_source_files
+    static ASSET_SOURCE_FILES: RefCell<Option<(usize, Rc<Vec<AssetSourceFile>>)>> =
+        RefCell::new(None);
+}
+
+/// A prefab source file on disk and the UUID it was imported as
+pub struct PrefabSourceFile {
+    pub uuid: AssetUuid,
+    pub path: PathBuf,
+}
+
+impl PrefabSourceFile {
+    /// A short name for the prefab, suitable for showing in the UI
+    pub fn name(&self) -> String {
+        prefab_name(&self.path)
+    }
+}
+
+pub fn prefab_name(path: &Path) -> String {
+    path.file_name()
+        .map(|file_name| file_name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string_lossy().to_string())
+}
+
+/// Finds all prefab source files that have been imported. The asset daemon writes a .meta file
+/// next to every source file it imports, and the prefab's UUID is read from it.
+pub fn find_prefab_source_files() -> Vec<PrefabSourceFile> {
+    // Prefab source files contain a single asset
+    let mut prefab_source_files: Vec<_> = asset_source_files()
+        .iter()
+        .filter(|asset_source_file| {
+            asset_source_file
+                .path
+                .extension()
+                .map(|x| x == "prefab")
+                .unwrap_or(false)
+        })
+        .map(|asset_source_file| PrefabSourceFile {
+            uuid: asset_source_file.uuid,
+            path: asset_source_file.path.clone(),
+        })
+        .collect();
+
+    prefab_source_files.dedup_by(|a, b| a.path == b.path);
+    prefab_source_files
+}
+
+/// Finds the source file for the given prefab
+pub fn find_prefab_source_path(prefab_uuid: &AssetUuid) -> Option<PathBuf> {
+    find_prefab_source_files()
+        .into_iter()
+        .find(|prefab_source_file| prefab_source_file.uuid == *prefab_uuid)
+        .map(|prefab_source_file| prefab_source_file.path)
+}
+
+/// An asset that was imported from a source file on disk
+#[derive(Clone)]
+pub struct AssetSourceFile {
+    pub uuid: AssetUuid,
+    pub path: PathBuf,
+
+    /// The asset's TypeUuid
+    pub asset_type: [u8; 16],
+}
+
+impl AssetSourceFile {
+    /// A short name for the asset, suitable for showing in the UI
+    pub fn name(&self) -> String {
+        prefab_name(&self.path)
+    }
+}
+
+/// Finds all imported assets of the given type (the asset's TypeUuid). Like prefabs, these are
+/// found by reading the .meta files the asset daemon writes next to each source file.
+pub fn find_asset_source_files(asset_type: [u8; 16]) -> Vec<AssetSourceFile> {
+    asset_source_files()
+        .iter()
+        .filter(|asset_source_file| asset_source_file.asset_type == asset_type)
+        .cloned()
+        .collect()
+}
+
+/// Finds all imported assets, regardless of type
+pub fn find_all_asset_source_files() -> Vec<AssetSourceFile> {
+    asset_source_files().to_vec()
+}
+
+/// Finds the source file that the given asset was imported from
+pub fn find_asset_source_file(asset_uuid: &AssetUuid) -> Option<AssetSourceFile> {
+    asset_source_files()
+        .iter()
+        .find(|asset_source_file| asset_source_file.uuid == *asset_uuid)
+        .cloned()
+}
+
+/// Marks the imported assets found so far as out of date, so that the .meta files are read again
+/// the next time they're needed. Called when the state of the loaded assets changes (see
+/// AssetStatusResource), which is when the asset daemon has imported something
+pub fn invalidate_asset_source_files() {
+    ASSET_SOURCE_FILES_GENERATION.fetch_add(1, Ordering::Relaxed);
+}
+
+// Reading every .meta file is too slow to do every frame (asset pickers and names are drawn each
+// frame), so the result is kept until invalidate_asset_source_files is called
+fn asset_source_files() -> Rc<Vec<AssetSourceFile>> {
+    let generation = ASSET_SOURCE_FILES_GENERATION.load(Ordering::Relaxed);
+    ASSET_SOURCE_FILES.with(|asset_source_files| {
+        let mut asset_source_files = asset_source_files.borrow_mut();
+        if let Some((cached_generation, cached)) = &*asset_source_files {
+            if *cached_generation == generation {
+                return cached.clone();
+            }
+        }
+
+        let found = Rc::new(read_asset_source_files());
+        *asset_source_files = Some((generation, found.clone()));
+        found
+    })
+}
+
+fn read_asset_source_files() -> Vec<AssetSourceFile> {
+    let mut asset_source_files = vec![];
+
+    // The asset daemon imports files in subdirectories too
+    let mut directories = vec![PathBuf::from(ASSET_DIRECTORY)];
+    while let Some(directory) = directories.pop() {
+        let entries = match std::fs::read_dir(&directory) {
+            Ok(entries) => entries,
+            Err(e) => {
+                log::warn!("Failed to read asset directory {:?}: {}", directory, e);
+                continue;
+            }
+        };
+
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            let path = entry.path();
+            if entry.file_type().map(|x| x.is_dir()).unwrap_or(false) {
+                directories.push(path);
+                continue;
+            }
+
+            if path.extension().map(|x| x != "meta").unwrap_or(true) {
+                continue;
+            }
+
+            for asset in read_meta_file(&path) {
+                asset_source_files.push(AssetSourceFile {
+                    uuid: asset.id,
+                    path: path.with_extension(""),
+                    asset_type: asset.asset_type().0,
+                });
+            }
+        }
+    }
+
+    asset_source_files.sort_by(|a, b| a.path.cmp(&b.path));
+    asset_source_files
+}
+
+// The parts of a .meta file that are needed to find assets. Other fields are ignored
+#[derive(Deserialize)]
+struct SourceMetadata {
+    assets: Vec<AssetMetadata>,
+}
+
+#[derive(Deserialize)]
+struct AssetMetadata {
+    id: AssetUuid,
+
+    // Older .meta files store the asset's type here, newer ones store it with the artifact
+    #[serde(default)]
+    asset_type: AssetUuid,
+    #[serde(default)]
+    artifact: Option<ArtifactMetadata>,
+}
+
+impl AssetMetadata {
+    fn asset_type(&self) -> AssetUuid {
+        match &self.artifact {
+            Some(artifact) => artifact.type_id,
+            None => self.asset_type,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ArtifactMetadata {
+    type_id: AssetUuid,
+}
+
+// Reads the assets in a .meta file. Files that can't be read are logged and treated as empty
+fn read_meta_file(meta_path: &Path) -> Vec<AssetMetadata> {
+    let contents = match std::fs::read_to_string(meta_path) {
+        Ok(contents) => contents,
+        Err(e) => {
+            log::warn!("Failed to read {:?}: {}", meta_path, e);
+            return vec![];
+        }
+    };
+
+    match ron::de::from_str::<SourceMetadata>(&contents) {
+        Ok(source_metadata) => source_metadata.assets,
+        Err(e) => {
+            log::warn!("Failed to parse {:?}: {}", meta_path, e);
+            vec![]
+        }
+    }
+}
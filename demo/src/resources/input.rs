@@ -28,6 +28,9 @@ pub struct InputResource {
     input_state: InputState,
     mouse_drag_in_progress: [Option<MouseDragState>; InputState::MOUSE_BUTTON_COUNT],
     mouse_drag_just_finished: [Option<MouseDragState>; InputState::MOUSE_BUTTON_COUNT],
+
+    // Keys that went down this frame, in the order they were pressed
+    keys_just_down: Vec<VirtualKeyCode>,
 }
 
 impl InputResource {
@@ -36,6 +39,7 @@ impl InputResource {
             input_state,
             mouse_drag_in_progress: Default::default(),
             mouse_drag_just_finished: Default::default(),
+            keys_just_down: Default::default(),
         }
     }
 
@@ -67,6 +71,22 @@ impl InputResource {
         self.input_state.is_key_just_down(key)
     }
 
+    /// Returns all keys that went down during this frame. This is useful for waiting on any key
+    /// (i.e. when rebinding keys)
+    pub fn keys_just_down(&self) -> &[VirtualKeyCode] {
+        &self.keys_just_down
+    }
+
+    /// Records that a key went down. Key repeats are ignored
+    pub fn record_key_pressed(
+        &mut self,
+        key: VirtualKeyCode,
+    ) {
+        if !self.input_state.is_key_down(key) {
+            self.keys_just_down.push(key);
+        }
+    }
+
     /// Returns true if the key went up during this frame
     pub fn is_key_just_up(
         &self,
@@ -322,5 +342,6 @@ impl InputResource {
 
     pub fn end_frame(&mut self) {
         self.input_state.end_frame();
+        self.keys_just_down.clear();
     }
 }
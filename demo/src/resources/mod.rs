@@ -4,6 +4,12 @@ pub use fps_text::FpsTextResource;
 mod asset;
 pub use asset::AssetResource;
 
+mod asset_status;
+pub use asset_status::AssetStatusResource;
+pub use asset_status::AssetStatus;
+pub use asset_status::AssetLoadState;
+pub use asset_status::asset_load_state;
+
 mod canvas_draw;
 pub use canvas_draw::CanvasDrawResource;
 
@@ -3,7 +3,7 @@ use legion::prelude::*;
 use legion::storage::Component;
 use std::marker::PhantomData;
 use std::sync::Arc;
-use crate::resources::{OpenedPrefabState, EditorStateResource};
+use crate::resources::{OpenedPrefabState, EditorStateResource, DrawLayerSettings};
 
 const EDITOR_SELECTION_WORLD_MARGIN: f32 = 0.02;
 
@@ -43,6 +43,7 @@ trait RegisteredEditorSelectableT: Send + Sync {
         collision_world: &mut CollisionWorld<f32, Entity>,
         resources: &Resources,
         opened_prefab: &OpenedPrefabState,
+        draw_layer_settings: &DrawLayerSettings,
         world: &World,
     );
 }
@@ -74,10 +75,15 @@ where
         collision_world: &mut CollisionWorld<f32, Entity>,
         resources: &Resources,
         opened_prefab: &OpenedPrefabState,
+        draw_layer_settings: &DrawLayerSettings,
         world: &World,
     ) {
         let query = <Read<T>>::query();
         for (entity, t) in query.iter_entities(world) {
+            if !draw_layer_settings.is_entity_selectable(world, entity) {
+                continue;
+            }
+
             t.create_editor_selection_world(
                 collision_world,
                 resources,
@@ -117,10 +123,15 @@ where
         collision_world: &mut CollisionWorld<f32, Entity>,
         resources: &Resources,
         opened_prefab: &OpenedPrefabState,
+        draw_layer_settings: &DrawLayerSettings,
         world: &World,
     ) {
         let query = <Read<U>>::query();
         for (world_entity, world_component) in query.iter_entities(world) {
+            if !draw_layer_settings.is_entity_selectable(world, world_entity) {
+                continue;
+            }
+
             if let Some(prefab_entity) = opened_prefab.world_to_prefab_mappings().get(&world_entity)
             {
                 if let Some(prefab_component) = opened_prefab
@@ -163,7 +174,8 @@ impl EditorSelectableRegistry {
         ));
     }
 
-    /// Produces a collision world that includes shapes associated with entities
+    /// Produces a collision world that includes shapes associated with entities. Entities on
+    /// hidden or locked draw layers are left out so that they can't be selected
     pub fn create_editor_selection_world(
         &self,
         resources: &Resources,
@@ -171,6 +183,12 @@ impl EditorSelectableRegistry {
     ) -> CollisionWorld<f32, Entity> {
         let mut collision_world = CollisionWorld::<f32, Entity>::new(EDITOR_SELECTION_WORLD_MARGIN);
 
+        let draw_layer_settings = resources
+            .get::<EditorStateResource>()
+            .unwrap()
+            .draw_layer_settings()
+            .clone();
+
         if let Some(opened_prefab) = resources
             .get::<EditorStateResource>()
             .unwrap()
@@ -182,6 +200,7 @@ impl EditorSelectableRegistry {
                     &mut collision_world,
                     resources,
                     &*opened_prefab,
+                    &draw_layer_settings,
                     &world,
                 );
             }
@@ -0,0 +1,89 @@
+use legion::storage::{Component, ComponentTypeId};
+use legion_transaction::{SpawnCloneImplHandlerSet, SpawnFrom};
+use std::collections::{HashMap, HashSet};
+
+/// Registers each def component's mapping with both the SpawnCloneImplHandlerSet that does the
+/// spawning and the ComponentSpawnMappings that the editor uses, so the two always agree
+pub struct SpawnMappingsBuilder {
+    handler_set: SpawnCloneImplHandlerSet,
+    mappings: ComponentSpawnMappings,
+}
+
+impl SpawnMappingsBuilder {
+    pub fn new() -> Self {
+        SpawnMappingsBuilder {
+            handler_set: SpawnCloneImplHandlerSet::new(),
+            mappings: ComponentSpawnMappings::default(),
+        }
+    }
+
+    /// FromT is spawned as IntoT using its Into implementation
+    pub fn add_mapping_into<FromT: Component + Clone + Into<IntoT>, IntoT: Component>(&mut self) {
+        self.handler_set.add_mapping_into::<FromT, IntoT>();
+        self.mappings.add_mapping::<FromT, IntoT>();
+    }
+
+    /// FromT is spawned as IntoT using IntoT's SpawnFrom implementation, which has access to
+    /// resources
+    pub fn add_mapping<FromT: Component, IntoT: Component + SpawnFrom<FromT>>(&mut self) {
+        self.handler_set.add_mapping::<FromT, IntoT>();
+        self.mappings.add_mapping::<FromT, IntoT>();
+    }
+
+    pub fn build(self) -> (SpawnCloneImplHandlerSet, ComponentSpawnMappings) {
+        (self.handler_set, self.mappings)
+    }
+}
+
+/// Tracks which component type each def component is transformed into when it is spawned. This
+/// is built alongside the SpawnCloneImplHandlerSet by SpawnMappingsBuilder, and lets the editor
+/// reason about components in terms of what they will become at runtime
+#[derive(Default)]
+pub struct ComponentSpawnMappings {
+    // def component type -> runtime component type
+    spawned_types: HashMap<ComponentTypeId, ComponentTypeId>,
+
+    // Types that only ever exist as the result of spawning from a def
+    runtime_only_types: HashSet<ComponentTypeId>,
+}
+
+impl ComponentSpawnMappings {
+    /// Records that FromT is spawned as IntoT
+    fn add_mapping<FromT: Component, IntoT: Component>(&mut self) {
+        let from_type = ComponentTypeId::of::<FromT>();
+        let into_type = ComponentTypeId::of::<IntoT>();
+        self.spawned_types.insert(from_type, into_type);
+        self.runtime_only_types.insert(into_type);
+    }
+
+    /// Returns the type the given component will be spawned as. Types without a mapping are
+    /// spawned as themselves
+    pub fn spawned_type(
+        &self,
+        component_type: ComponentTypeId,
+    ) -> ComponentTypeId {
+        *self
+            .spawned_types
+            .get(&component_type)
+            .unwrap_or(&component_type)
+    }
+
+    /// Returns true if this type is only produced by spawning a def component, and so should not
+    /// be directly added to a prefab
+    pub fn is_runtime_only(
+        &self,
+        component_type: ComponentTypeId,
+    ) -> bool {
+        self.runtime_only_types.contains(&component_type)
+    }
+
+    /// Returns true if the two component types would collide once spawned (i.e. they both become
+    /// the same runtime type)
+    pub fn spawns_into_same_type(
+        &self,
+        lhs: ComponentTypeId,
+        rhs: ComponentTypeId,
+    ) -> bool {
+        self.spawned_type(lhs) == self.spawned_type(rhs)
+    }
+}
@@ -1,10 +1,12 @@
 use legion::prelude::*;
-use crate::resources::AssetResource;
+use crate::resources::{AssetResource, AssetStatusResource};
 
 pub fn update_asset_manager() -> Box<dyn Schedulable> {
     SystemBuilder::new("update asset manager")
         .write_resource::<AssetResource>()
-        .build(|_, _, asset_manager, _| {
+        .write_resource::<AssetStatusResource>()
+        .build(|_, _, (asset_manager, asset_status), _| {
             asset_manager.update();
+            asset_status.update(&*asset_manager);
         })
 }
@@ -1,4 +1,5 @@
 use legion::prelude::*;
+use legion::systems::SubWorld;
 
 use imgui;
 use skulpin::skia_safe;
@@ -8,18 +9,176 @@ use crate::components::{
 };
 use crate::components::DrawSkiaBoxComponent;
 use crate::components::DrawSkiaCircleComponent;
+use crate::components::DrawSkiaRoundedRectComponent;
+use crate::components::DrawSkiaPolygonComponent;
+use crate::components::DrawSkiaLineStripComponent;
+use crate::components::DrawSkiaSpriteComponent;
+use crate::components::DrawSkiaVectorShapeComponent;
+use crate::components::DrawOrderComponent;
 
 use crate::resources::{
     CanvasDrawResource, CameraResource, InputResource, ViewportResource, DebugDrawResource,
 };
 use crate::resources::ImguiResource;
 use crate::resources::FpsTextResource;
+use crate::resources::AssetResource;
+use crate::resources::EditorStateResource;
+use crate::asset_storage::AssetVersionChanged;
+use crate::pipeline::{ImageAsset, VectorShapeAsset};
+
+use atelier_loader::crossbeam_channel::Receiver;
+use atelier_loader::handle::AssetHandle;
+use atelier_loader::LoadHandle;
+use std::collections::HashSet;
 
 use skulpin::winit;
 use skulpin::LogicalSize;
 
+pub fn update_sprite_images() -> Box<dyn Schedulable> {
+    // Create skia images for sprites whose image asset has finished loading or was hot-reloaded.
+    // The first run creates images for every sprite. After that, only sprites using an image that
+    // the asset storage reports as changed are updated
+    let mut image_version_changes: Option<Receiver<AssetVersionChanged>> = None;
+
+    SystemBuilder::new("update_sprite_images")
+        .read_resource::<AssetResource>()
+        .with_query(<Write<DrawSkiaSpriteComponent>>::query())
+        .build(move |_, world, asset_resource, sprites_query| {
+            let changed_images: Option<HashSet<LoadHandle>> = match &image_version_changes {
+                Some(image_version_changes) => Some(
+                    image_version_changes
+                        .try_iter()
+                        .map(|x| x.load_handle)
+                        .collect(),
+                ),
+                None => {
+                    image_version_changes =
+                        Some(asset_resource.storage().subscribe::<ImageAsset>());
+                    None
+                }
+            };
+
+            if let Some(changed_images) = &changed_images {
+                if changed_images.is_empty() {
+                    return;
+                }
+            }
+
+            for mut sprite in sprites_query.iter_mut(world) {
+                let needs_update = changed_images
+                    .as_ref()
+                    .map(|changed_images| changed_images.contains(&sprite.image.load_handle()))
+                    .unwrap_or(true);
+
+                if needs_update {
+                    sprite.update_skia_image(&*asset_resource);
+                }
+            }
+        })
+}
+
+pub fn update_vector_shapes() -> Box<dyn Schedulable> {
+    // Create skia paths for vector shapes whose asset has finished loading or was hot-reloaded.
+    // Works the same way as update_sprite_images
+    let mut shape_version_changes: Option<Receiver<AssetVersionChanged>> = None;
+
+    SystemBuilder::new("update_vector_shapes")
+        .read_resource::<AssetResource>()
+        .with_query(<Write<DrawSkiaVectorShapeComponent>>::query())
+        .build(move |_, world, asset_resource, vector_shapes_query| {
+            let changed_shapes: Option<HashSet<LoadHandle>> = match &shape_version_changes {
+                Some(shape_version_changes) => Some(
+                    shape_version_changes
+                        .try_iter()
+                        .map(|x| x.load_handle)
+                        .collect(),
+                ),
+                None => {
+                    shape_version_changes =
+                        Some(asset_resource.storage().subscribe::<VectorShapeAsset>());
+                    None
+                }
+            };
+
+            if let Some(changed_shapes) = &changed_shapes {
+                if changed_shapes.is_empty() {
+                    return;
+                }
+            }
+
+            for mut vector_shape in vector_shapes_query.iter_mut(world) {
+                let needs_update = changed_shapes
+                    .as_ref()
+                    .map(|changed_shapes| {
+                        changed_shapes.contains(&vector_shape.shape.load_handle())
+                    })
+                    .unwrap_or(true);
+
+                if needs_update {
+                    vector_shape.update_skia_shape(&*asset_resource);
+                }
+            }
+        })
+}
+
+/// The kinds of components that the draw system draws. Entities are sorted by their draw order
+/// before drawing, so that the different kinds can be interleaved
+#[derive(Copy, Clone)]
+enum Drawable {
+    Sprite,
+    VectorShape,
+    Box,
+    RoundedRect,
+    Circle,
+    Polygon,
+    LineStrip,
+}
+
+/// The position, scale and rotation that an entity is drawn with
+struct DrawTransform {
+    position: glam::Vec2,
+    uniform_scale: f32,
+    non_uniform_scale: glam::Vec2,
+    rotation_in_degrees: f32,
+}
+
+impl DrawTransform {
+    fn new(
+        world: &SubWorld,
+        entity: Entity,
+    ) -> Option<Self> {
+        let position = *world.get_component::<Position2DComponent>(entity)?.position;
+
+        let uniform_scale = world
+            .get_component::<UniformScale2DComponent>(entity)
+            .map(|uniform_scale| uniform_scale.uniform_scale)
+            .unwrap_or(1.0);
+
+        let non_uniform_scale = world
+            .get_component::<NonUniformScale2DComponent>(entity)
+            .map(|non_uniform_scale| *non_uniform_scale.non_uniform_scale)
+            .unwrap_or_else(|| glam::Vec2::new(1.0, 1.0));
+
+        let rotation_in_degrees = world
+            .get_component::<Rotation2DComponent>(entity)
+            .map(|rotation| rotation.rotation * 180.0 / std::f32::consts::PI)
+            .unwrap_or(0.0);
+
+        Some(DrawTransform {
+            position,
+            uniform_scale,
+            non_uniform_scale,
+            rotation_in_degrees,
+        })
+    }
+
+    fn scale(&self) -> glam::Vec2 {
+        self.non_uniform_scale * self.uniform_scale
+    }
+}
+
 pub fn draw() -> Box<dyn Schedulable> {
-    // Copy the data from physics rigid bodies into position components
+    // Draw every drawable entity, sorted by draw layer and order
     SystemBuilder::new("draw")
         .write_resource::<CanvasDrawResource>()
         .read_resource::<FpsTextResource>()
@@ -27,24 +186,114 @@ pub fn draw() -> Box<dyn Schedulable> {
         .write_resource::<ViewportResource>()
         .read_resource::<InputResource>()
         .write_resource::<DebugDrawResource>()
+        .read_resource::<EditorStateResource>()
+        .read_component::<UniformScale2DComponent>()
+        .read_component::<NonUniformScale2DComponent>()
+        .read_component::<Rotation2DComponent>()
+        .with_query(<(
+            Read<Position2DComponent>,
+            Read<DrawSkiaSpriteComponent>,
+            TryRead<DrawOrderComponent>,
+        )>::query())
+        .with_query(<(
+            Read<Position2DComponent>,
+            Read<DrawSkiaVectorShapeComponent>,
+            TryRead<DrawOrderComponent>,
+        )>::query())
         .with_query(<(
             Read<Position2DComponent>,
             Read<DrawSkiaBoxComponent>,
-            TryRead<UniformScale2DComponent>,
-            TryRead<NonUniformScale2DComponent>,
-            TryRead<Rotation2DComponent>,
+            TryRead<DrawOrderComponent>,
+        )>::query())
+        .with_query(<(
+            Read<Position2DComponent>,
+            Read<DrawSkiaRoundedRectComponent>,
+            TryRead<DrawOrderComponent>,
         )>::query())
         .with_query(<(
             Read<Position2DComponent>,
             Read<DrawSkiaCircleComponent>,
-            TryRead<UniformScale2DComponent>,
-            TryRead<Rotation2DComponent>,
+            TryRead<DrawOrderComponent>,
+        )>::query())
+        .with_query(<(
+            Read<Position2DComponent>,
+            Read<DrawSkiaPolygonComponent>,
+            TryRead<DrawOrderComponent>,
+        )>::query())
+        .with_query(<(
+            Read<Position2DComponent>,
+            Read<DrawSkiaLineStripComponent>,
+            TryRead<DrawOrderComponent>,
         )>::query())
         .build(
             |_,
              world,
-             (draw_context, fps_text, camera_state, viewport_state, input_resource, debug_draw),
-             (draw_boxes_query, draw_circles_query)| {
+             (
+                draw_context,
+                fps_text,
+                camera_state,
+                viewport_state,
+                input_resource,
+                debug_draw,
+                editor_state,
+            ),
+             (
+                draw_sprites_query,
+                draw_vector_shapes_query,
+                draw_boxes_query,
+                draw_rounded_rects_query,
+                draw_circles_query,
+                draw_polygons_query,
+                draw_line_strips_query,
+            )| {
+                // Gather everything that will be drawn. Each kind is gathered in the order they
+                // used to be drawn, and the sort is stable, so entities with the same layer and
+                // order keep that order
+                let mut drawables = vec![];
+                for (entity, (_, _, draw_order)) in draw_sprites_query.iter_entities(world) {
+                    drawables.push((draw_order.map(|x| *x), Drawable::Sprite, entity));
+                }
+
+                for (entity, (_, _, draw_order)) in draw_vector_shapes_query.iter_entities(world) {
+                    drawables.push((draw_order.map(|x| *x), Drawable::VectorShape, entity));
+                }
+
+                for (entity, (_, _, draw_order)) in draw_boxes_query.iter_entities(world) {
+                    drawables.push((draw_order.map(|x| *x), Drawable::Box, entity));
+                }
+
+                for (entity, (_, _, draw_order)) in draw_rounded_rects_query.iter_entities(world) {
+                    drawables.push((draw_order.map(|x| *x), Drawable::RoundedRect, entity));
+                }
+
+                for (entity, (_, _, draw_order)) in draw_circles_query.iter_entities(world) {
+                    drawables.push((draw_order.map(|x| *x), Drawable::Circle, entity));
+                }
+
+                for (entity, (_, _, draw_order)) in draw_polygons_query.iter_entities(world) {
+                    drawables.push((draw_order.map(|x| *x), Drawable::Polygon, entity));
+                }
+
+                for (entity, (_, _, draw_order)) in draw_line_strips_query.iter_entities(world) {
+                    drawables.push((draw_order.map(|x| *x), Drawable::LineStrip, entity));
+                }
+
+                // Layers hidden in the editor are only hidden while editing
+                let draw_layer_settings = editor_state.draw_layer_settings();
+                let hide_layers = editor_state.is_editor_active();
+
+                let mut drawables: Vec<_> = drawables
+                    .into_iter()
+                    .map(|(draw_order, drawable, entity)| {
+                        (draw_order.unwrap_or_default(), drawable, entity)
+                    })
+                    .filter(|(draw_order, _, _)| {
+                        !hide_layers || draw_layer_settings.is_visible(draw_order.layer)
+                    })
+                    .collect();
+
+                drawables.sort_by_key(|(draw_order, _, _)| (draw_order.layer, draw_order.order));
+
                 draw_context.with_canvas(|canvas, coordinate_system_helper| {
                     let window_size = input_resource.window_size();
                     let camera_position = camera_state.position;
@@ -72,61 +321,70 @@ pub fn draw() -> Box<dyn Schedulable> {
                     // Generally would want to clear data every time we draw
                     canvas.clear(skia_safe::Color::from_argb(0, 0, 0, 255));
 
-                    // Draw all the boxes
-                    for (pos, skia_box, uniform_scale, non_uniform_scale, rotation) in
-                        draw_boxes_query.iter(world)
-                    {
-                        let mut half_extents = *skia_box.half_extents;
-                        if let Some(uniform_scale) = uniform_scale {
-                            half_extents *= uniform_scale.uniform_scale;
-                        }
-
-                        if let Some(non_uniform_scale) = non_uniform_scale {
-                            half_extents *= *non_uniform_scale.non_uniform_scale;
-                        }
-
-                        let paint = skia_box.paint.0.lock().unwrap();
-
-                        let rotation_in_degrees = if let Some(rotation) = rotation {
-                            rotation.rotation * 180.0 / std::f32::consts::PI
-                        } else {
-                            0.0
+                    // Shapes are drawn around the origin so that gradients are relative to the
+                    // entity's position
+                    for (_, drawable, entity) in &drawables {
+                        let transform = match DrawTransform::new(world, *entity) {
+                            Some(transform) => transform,
+                            None => continue,
                         };
 
                         canvas.save();
-                        canvas.rotate(
-                            rotation_in_degrees,
-                            Some(skia_safe::Point::new(pos.position.x(), pos.position.y())),
-                        );
+                        canvas.translate((transform.position.x(), transform.position.y()));
+                        canvas.rotate(transform.rotation_in_degrees, None);
 
-                        canvas.draw_rect(
-                            skia_safe::Rect {
-                                left: pos.position.x() - half_extents.x(),
-                                right: pos.position.x() + half_extents.x(),
-                                top: pos.position.y() - half_extents.y(),
-                                bottom: pos.position.y() + half_extents.y(),
-                            },
-                            &paint,
-                        );
-
-                        canvas.restore();
-                    }
-
-                    // Draw all the circles
-                    for (pos, skia_circle, uniform_scale, rotation) in
-                        draw_circles_query.iter(world)
-                    {
-                        let mut scale = 1.0;
-                        if let Some(uniform_scale) = uniform_scale {
-                            scale *= uniform_scale.uniform_scale;
+                        match drawable {
+                            Drawable::Sprite => {
+                                let sprite =
+                                    world.get_component::<DrawSkiaSpriteComponent>(*entity);
+                                if let Some(sprite) = sprite {
+                                    draw_sprite(canvas, &transform, &*sprite);
+                                }
+                            }
+                            Drawable::VectorShape => {
+                                let vector_shape =
+                                    world.get_component::<DrawSkiaVectorShapeComponent>(*entity);
+                                if let Some(vector_shape) = vector_shape {
+                                    draw_vector_shape(canvas, &transform, &*vector_shape);
+                                }
+                            }
+                            Drawable::Box => {
+                                let skia_box = world.get_component::<DrawSkiaBoxComponent>(*entity);
+                                if let Some(skia_box) = skia_box {
+                                    draw_box(canvas, &transform, &*skia_box);
+                                }
+                            }
+                            Drawable::RoundedRect => {
+                                let skia_rounded_rect =
+                                    world.get_component::<DrawSkiaRoundedRectComponent>(*entity);
+                                if let Some(skia_rounded_rect) = skia_rounded_rect {
+                                    draw_rounded_rect(canvas, &transform, &*skia_rounded_rect);
+                                }
+                            }
+                            Drawable::Circle => {
+                                let skia_circle =
+                                    world.get_component::<DrawSkiaCircleComponent>(*entity);
+                                if let Some(skia_circle) = skia_circle {
+                                    draw_circle(canvas, &transform, &*skia_circle);
+                                }
+                            }
+                            Drawable::Polygon => {
+                                let skia_polygon =
+                                    world.get_component::<DrawSkiaPolygonComponent>(*entity);
+                                if let Some(skia_polygon) = skia_polygon {
+                                    draw_polygon(canvas, &transform, &*skia_polygon);
+                                }
+                            }
+                            Drawable::LineStrip => {
+                                let skia_line_strip =
+                                    world.get_component::<DrawSkiaLineStripComponent>(*entity);
+                                if let Some(skia_line_strip) = skia_line_strip {
+                                    draw_line_strip(canvas, &transform, &*skia_line_strip);
+                                }
+                            }
                         }
 
-                        let paint = skia_circle.paint.0.lock().unwrap();
-                        canvas.draw_circle(
-                            skia_safe::Point::new(pos.position.x(), pos.position.y()),
-                            skia_circle.radius * scale,
-                            &paint,
-                        );
+                        canvas.restore();
                     }
 
                     // Debug draw
@@ -177,3 +435,144 @@ pub fn draw() -> Box<dyn Schedulable> {
             },
         )
 }
+
+fn draw_sprite(
+    canvas: &mut skia_safe::Canvas,
+    transform: &DrawTransform,
+    sprite: &DrawSkiaSpriteComponent,
+) {
+    // Skip sprites whose image hasn't loaded yet
+    let skia_image = match &sprite.skia_image {
+        Some((skia_image, _)) => skia_image,
+        None => return,
+    };
+
+    let half_extents = *sprite.half_extents * transform.scale();
+    let paint = sprite.paint.0.lock().unwrap();
+
+    // Image rows are stored top to bottom, but y points up in the world
+    canvas.save();
+    canvas.scale((1.0, -1.0));
+
+    canvas.draw_image_rect(
+        &skia_image.0,
+        None,
+        skia_safe::Rect {
+            left: -half_extents.x(),
+            right: half_extents.x(),
+            top: -half_extents.y(),
+            bottom: half_extents.y(),
+        },
+        &paint,
+    );
+
+    canvas.restore();
+}
+
+fn draw_vector_shape(
+    canvas: &mut skia_safe::Canvas,
+    transform: &DrawTransform,
+    vector_shape: &DrawSkiaVectorShapeComponent,
+) {
+    // Skip shapes whose asset hasn't loaded yet
+    let skia_shape = match &vector_shape.skia_shape {
+        Some((skia_shape, _)) => skia_shape,
+        None => return,
+    };
+
+    let scale = transform.scale();
+
+    canvas.save();
+    canvas.scale((scale.x(), scale.y()));
+
+    for (path, paints) in &skia_shape.paths {
+        for paint in paints {
+            canvas.draw_path(path, &paint.0.lock().unwrap());
+        }
+    }
+
+    canvas.restore();
+}
+
+fn draw_box(
+    canvas: &mut skia_safe::Canvas,
+    transform: &DrawTransform,
+    skia_box: &DrawSkiaBoxComponent,
+) {
+    let half_extents = *skia_box.half_extents * transform.scale();
+    let paint = skia_box.paint.0.lock().unwrap();
+
+    canvas.draw_rect(
+        skia_safe::Rect {
+            left: -half_extents.x(),
+            right: half_extents.x(),
+            top: -half_extents.y(),
+            bottom: half_extents.y(),
+        },
+        &paint,
+    );
+}
+
+fn draw_rounded_rect(
+    canvas: &mut skia_safe::Canvas,
+    transform: &DrawTransform,
+    skia_rounded_rect: &DrawSkiaRoundedRectComponent,
+) {
+    let scale = transform.scale();
+    let half_extents = *skia_rounded_rect.half_extents * scale;
+
+    // The corners are stretched along with the rest of the rect, and can be at most half as wide
+    // and tall as the rect
+    let corner_radius_x = (skia_rounded_rect.corner_radius * scale.x())
+        .abs()
+        .min(half_extents.x().abs());
+    let corner_radius_y = (skia_rounded_rect.corner_radius * scale.y())
+        .abs()
+        .min(half_extents.y().abs());
+
+    let paint = skia_rounded_rect.paint.0.lock().unwrap();
+
+    canvas.draw_round_rect(
+        skia_safe::Rect {
+            left: -half_extents.x(),
+            right: half_extents.x(),
+            top: -half_extents.y(),
+            bottom: half_extents.y(),
+        },
+        corner_radius_x,
+        corner_radius_y,
+        &paint,
+    );
+}
+
+fn draw_circle(
+    canvas: &mut skia_safe::Canvas,
+    transform: &DrawTransform,
+    skia_circle: &DrawSkiaCircleComponent,
+) {
+    // Circles stay round, so only the uniform scale affects them
+    let paint = skia_circle.paint.0.lock().unwrap();
+    canvas.draw_circle(
+        skia_safe::Point::new(0.0, 0.0),
+        skia_circle.radius * transform.uniform_scale,
+        &paint,
+    );
+}
+
+fn draw_polygon(
+    canvas: &mut skia_safe::Canvas,
+    transform: &DrawTransform,
+    skia_polygon: &DrawSkiaPolygonComponent,
+) {
+    let paint = skia_polygon.paint.0.lock().unwrap();
+    canvas.draw_path(&skia_polygon.create_skia_path(transform.scale()), &paint);
+}
+
+fn draw_line_strip(
+    canvas: &mut skia_safe::Canvas,
+    transform: &DrawTransform,
+    skia_line_strip: &DrawSkiaLineStripComponent,
+) {
+    let paint = skia_line_strip.paint.0.lock().unwrap();
+    canvas.draw_path(&skia_line_strip.create_skia_path(transform.scale()), &paint);
+}
@@ -0,0 +1,105 @@
+use legion::prelude::*;
+
+use crate::resources::EditorStateResource;
+use crate::resources::ImguiResource;
+use crate::resources::{AssetLoadState, AssetStatusResource};
+
+use imgui;
+use imgui::im_str;
+
+pub fn editor_assets_window() -> Box<dyn Schedulable> {
+    SystemBuilder::new("editor_assets_window")
+        .write_resource::<ImguiResource>()
+        .read_resource::<EditorStateResource>()
+        .read_resource::<AssetStatusResource>()
+        .build(|_, _, (imgui_manager, editor_state, asset_status), _| {
+            imgui_manager.with_ui(|ui: &mut imgui::Ui| {
+                if !editor_state.window_options().show_assets {
+                    return;
+                }
+
+                imgui::Window::new(im_str!("Assets"))
+                    .position([350.0, 600.0], imgui::Condition::FirstUseEver)
+                    .size([500.0, 200.0], imgui::Condition::FirstUseEver)
+                    .build(ui, || {
+                        ui.text(&im_str!(
+                            "Loaded: {}  Pending: {}  Failed: {}",
+                            asset_status.count(AssetLoadState::Loaded),
+                            asset_status.count(AssetLoadState::Pending),
+                            asset_status.count(AssetLoadState::Failed)
+                        ));
+
+                        ui.separator();
+
+                        // Failed assets are listed first since they're the ones that need
+                        // attention
+                        let mut assets: Vec<_> = asset_status.assets().iter().collect();
+                        assets.sort_by_key(|(asset_id, status)| {
+                            (
+                                status.state != AssetLoadState::Failed,
+                                status.source_file_name.clone(),
+                                asset_id.0,
+                            )
+                        });
+
+                        ui.columns(3, im_str!("assets"), true);
+                        ui.text(im_str!("Asset"));
+                        ui.next_column();
+                        ui.text(im_str!("State"));
+                        ui.next_column();
+                        ui.text(im_str!("Refs"));
+                        ui.next_column();
+                        ui.separator();
+
+                        for (asset_id, status) in assets {
+                            let asset_name = status
+                                .source_file_name
+                                .clone()
+                                .unwrap_or_else(|| uuid::Uuid::from_bytes(asset_id.0).to_string());
+                            ui.text(&im_str!("{}", asset_name));
+                            if ui.is_item_hovered() {
+                                ui.tooltip_text(&im_str!("{}", uuid::Uuid::from_bytes(asset_id.0)));
+                            }
+                            ui.next_column();
+
+                            match status.state {
+                                AssetLoadState::Loaded => ui.text(im_str!("Loaded")),
+                                AssetLoadState::Pending => ui.text_disabled(im_str!("Pending")),
+                                AssetLoadState::Failed => {
+                                    ui.text_colored([1.0, 0.3, 0.3, 1.0], im_str!("Failed"));
+                                    if let Some(error) = &status.error {
+                                        if ui.is_item_hovered() {
+                                            ui.tooltip_text(&im_str!("{}", error));
+                                        }
+                                    }
+                                }
+                            }
+                            ui.next_column();
+
+                            ui.text(&im_str!("{}", status.ref_count));
+                            ui.next_column();
+                        }
+
+                        ui.columns(1, im_str!(""), false);
+
+                        // Show the full error messages below the list so they can be read without
+                        // hovering
+                        let failed_assets = asset_status
+                            .assets()
+                            .values()
+                            .filter(|status| status.error.is_some());
+                        for status in failed_assets {
+                            ui.separator();
+                            ui.text_wrapped(&im_str!(
+                                "{}: {}",
+                                status
+                                    .source_file_name
+                                    .as_deref()
+                                    .unwrap_or("(unknown file)"),
+                                status.error.as_ref().unwrap()
+                            ));
+                        }
+                    });
+            });
+        })
+}
@@ -0,0 +1,172 @@
+use legion::prelude::*;
+
+use crate::resources::EditorStateResource;
+use crate::resources::ImguiResource;
+use crate::resources::EditorKeybindingsResource;
+use crate::editor_commands::{EditorCommand, EditorCommandRegistry, fuzzy_match_score};
+
+use imgui;
+use imgui::im_str;
+
+// Returns the commands that match the search text, best match first
+fn filtered_commands(
+    commands: Vec<EditorCommand>,
+    search_text: &str,
+) -> Vec<EditorCommand> {
+    let mut scored_commands: Vec<_> = commands
+        .into_iter()
+        .filter_map(|command| {
+            fuzzy_match_score(search_text, command.name()).map(|score| (score, command))
+        })
+        .collect();
+
+    scored_commands.sort_by(|(score1, command1), (score2, command2)| {
+        score2
+            .cmp(score1)
+            .then_with(|| command1.name().cmp(command2.name()))
+    });
+
+    scored_commands
+        .into_iter()
+        .map(|(_, command)| command)
+        .collect()
+}
+
+pub fn editor_command_palette_window(
+    world: &mut World,
+    resources: &mut Resources,
+) {
+    if !resources
+        .get::<EditorStateResource>()
+        .unwrap()
+        .is_command_palette_open()
+    {
+        return;
+    }
+
+    // The commands are gathered once when the palette opens and reused until it closes
+    let cached_commands = resources
+        .get::<EditorStateResource>()
+        .unwrap()
+        .command_palette()
+        .commands()
+        .cloned();
+
+    let commands = match cached_commands {
+        Some(commands) => commands,
+        None => {
+            let commands = resources
+                .get::<EditorCommandRegistry>()
+                .unwrap()
+                .commands(world, resources);
+
+            resources
+                .get_mut::<EditorStateResource>()
+                .unwrap()
+                .command_palette_mut()
+                .set_commands(commands.clone());
+            commands
+        }
+    };
+
+    let mut command_to_run = None;
+
+    {
+        let imgui_manager = resources.get::<ImguiResource>().unwrap();
+        let mut editor_state = resources.get_mut::<EditorStateResource>().unwrap();
+        let keybindings = resources.get::<EditorKeybindingsResource>().unwrap();
+
+        imgui_manager.with_ui(|ui: &mut imgui::Ui| {
+            let mut keep_open = true;
+            imgui::Window::new(im_str!("Command Palette"))
+                .position([350.0, 60.0], imgui::Condition::FirstUseEver)
+                .size([500.0, 300.0], imgui::Condition::FirstUseEver)
+                .collapsible(false)
+                .opened(&mut keep_open)
+                .build(ui, || {
+                    // Keep the search box focused so the user can start typing right away
+                    if !ui.is_any_item_active() {
+                        ui.set_keyboard_focus_here(imgui::FocusedWidget::Next);
+                    }
+
+                    let enter_pressed = ui
+                        .input_text(
+                            im_str!("Search"),
+                            editor_state.command_palette_mut().search_text_mut(),
+                        )
+                        .resize_buffer(true)
+                        .enter_returns_true(true)
+                        .build();
+
+                    let commands = filtered_commands(
+                        commands,
+                        editor_state.command_palette().search_text().to_str(),
+                    );
+
+                    // Arrow keys move the highlighted command, enter runs it
+                    let mut selected_index = editor_state.command_palette().selected_index();
+                    if ui.is_key_pressed(ui.key_index(imgui::Key::DownArrow)) {
+                        selected_index += 1;
+                    }
+
+                    if ui.is_key_pressed(ui.key_index(imgui::Key::UpArrow)) {
+                        selected_index = selected_index.saturating_sub(1);
+                    }
+
+                    selected_index = selected_index.min(commands.len().saturating_sub(1));
+                    editor_state
+                        .command_palette_mut()
+                        .set_selected_index(selected_index);
+
+                    if enter_pressed {
+                        command_to_run = commands.get(selected_index).cloned();
+                    }
+
+                    if ui.is_key_pressed(ui.key_index(imgui::Key::Escape)) {
+                        editor_state.close_command_palette();
+                    }
+
+                    ui.separator();
+                    ui.columns(2, im_str!("commands"), false);
+
+                    for (index, command) in commands.iter().enumerate() {
+                        let id_token = ui.push_id(index as i32);
+
+                        if imgui::Selectable::new(&im_str!("{}", command.name()))
+                            .selected(index == selected_index)
+                            .build(ui)
+                        {
+                            command_to_run = Some(command.clone());
+                        }
+                        ui.next_column();
+
+                        if let Some(key_chord) = command
+                            .action()
+                            .and_then(|action| keybindings.binding(action))
+                        {
+                            ui.text_disabled(&im_str!("{}", key_chord));
+                        }
+                        ui.next_column();
+
+                        id_token.pop(ui);
+                    }
+
+                    ui.columns(1, im_str!(""), false);
+                });
+
+            if !keep_open {
+                editor_state.close_command_palette();
+            }
+        });
+
+        if command_to_run.is_some() {
+            editor_state.close_command_palette();
+        }
+    }
+
+    // Run the command after the UI is done so that it can access any resource
+    if let Some(command_to_run) = command_to_run {
+        log::info!("Running command {}", command_to_run.name());
+        command_to_run.run(world, resources);
+    }
+}
@@ -0,0 +1,86 @@
+use legion::prelude::*;
+
+use crate::resources::EditorStateResource;
+use crate::resources::ImguiResource;
+
+use imgui;
+use imgui::im_str;
+
+pub fn editor_documents_window() -> Box<dyn Schedulable> {
+    SystemBuilder::new("editor_documents_window")
+        .write_resource::<ImguiResource>()
+        .write_resource::<EditorStateResource>()
+        .build(|_, _, (imgui_manager, editor_state), _| {
+            imgui_manager.with_ui(|ui: &mut imgui::Ui| {
+                if !editor_state.window_options().show_documents {
+                    return;
+                }
+
+                imgui::Window::new(im_str!("Documents"))
+                    .position([350.0, 20.0], imgui::Condition::FirstUseEver)
+                    .size([500.0, 50.0], imgui::Condition::FirstUseEver)
+                    .build(ui, || {
+                        let mut document_to_switch = None;
+                        let mut document_to_close = None;
+
+                        let active_document = editor_state.active_document();
+                        for (index, document) in editor_state.open_documents().iter().enumerate() {
+                            let id_token = ui.push_id(index as i32);
+                            if index > 0 {
+                                ui.same_line(0.0);
+                            }
+
+                            // Documents are drawn as a row of tabs, with the active one highlighted
+                            let is_active = active_document == Some(document.uuid());
+                            let color_stack_token = if is_active {
+                                Some(ui.push_style_color(
+                                    imgui::StyleColor::Button,
+                                    [0.8, 0.0, 0.0, 1.0],
+                                ))
+                            } else {
+                                None
+                            };
+
+                            let unsaved_marker =
+                                if editor_state.document_has_unsaved_changes(document.uuid()) {
+                                    "*"
+                                } else {
+                                    ""
+                                };
+
+                            if ui.button(
+                                &im_str!("{}{}", document.name(), unsaved_marker),
+                                [0.0, 0.0],
+                            ) {
+                                document_to_switch = Some(document.uuid());
+                            }
+
+                            if let Some(color_stack_token) = color_stack_token {
+                                color_stack_token.pop(ui);
+                            }
+
+                            ui.same_line(0.0);
+                            if ui.small_button(im_str!("x")) {
+                                document_to_close = Some(document.uuid());
+                            }
+
+                            id_token.pop(ui);
+                        }
+
+                        if let Some(document_to_switch) = document_to_switch {
+                            editor_state.enqueue_switch_document(document_to_switch);
+                        }
+
+                        if let Some(document_to_close) = document_to_close {
+                            // Make the document active first so that if the user is asked to save
+                            // it, they can see what is being saved
+                            if editor_state.document_has_unsaved_changes(document_to_close) {
+                                editor_state.enqueue_switch_document(document_to_close);
+                            }
+
+                            editor_state.enqueue_close_document(document_to_close);
+                        }
+                    });
+            });
+        })
+}
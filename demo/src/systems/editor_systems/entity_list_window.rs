@@ -52,8 +52,8 @@ pub fn editor_entity_list_window() -> Box<dyn Schedulable> {
 
                     if window_options.show_entity_list {
                         imgui::Window::new(im_str!("Entity List"))
-                            .position([0.0, 50.0], imgui::Condition::Once)
-                            .size([350.0, 250.0], imgui::Condition::Once)
+                            .position([0.0, 50.0], imgui::Condition::FirstUseEver)
+                            .size([350.0, 250.0], imgui::Condition::FirstUseEver)
                             .build(ui, || {
                                 let add_entity = ui.button(im_str!("\u{e8b1} Add"), [80.0, 0.0]);
                                 ui.same_line_with_spacing(80.0, 10.0);
@@ -66,6 +66,7 @@ pub fn editor_entity_list_window() -> Box<dyn Schedulable> {
                                         .create_empty_transaction(&*universe_resource)
                                     {
                                         tx.world_mut().insert((), vec![()]);
+                                        tx.set_description("Add entity");
                                         tx.commit(
                                             &mut *editor_ui_state,
                                             PostCommitSelection::SelectAllInTransaction,
@@ -80,7 +81,12 @@ pub fn editor_entity_list_window() -> Box<dyn Schedulable> {
                                             &*universe_resource,
                                         )
                                     {
+                                        let entity_count = tx.world().iter_entities().count();
                                         tx.world_mut().delete_all();
+                                        tx.set_description(format!(
+                                            "Delete {} entities",
+                                            entity_count
+                                        ));
                                         tx.commit(
                                             &mut *editor_ui_state,
                                             PostCommitSelection::KeepCurrentSelection,
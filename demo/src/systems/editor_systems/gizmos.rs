@@ -3,7 +3,7 @@ use legion::prelude::*;
 use crate::resources::{
     EditorStateResource, InputResource, TimeResource, EditorSelectionResource, ViewportResource,
     DebugDrawResource, UniverseResource, EditorDrawResource, EditorTransaction,
-    PostCommitSelection,
+    PostCommitSelection, ResolvedSnapSettings, EditorShapeDragState, GizmoPivotMode, GizmoSpace,
 };
 use crate::resources::ImguiResource;
 use crate::resources::EditorTool;
@@ -52,7 +52,7 @@ pub fn editor_gizmos() -> Box<dyn Schedulable> {
         .write_resource::<DebugDrawResource>()
         .write_resource::<EditorDrawResource>()
         .read_resource::<UniverseResource>()
-        .with_query(<(Read<Position2DComponent>)>::query())
+        .with_query(<(Read<Position2DComponent>, TryRead<Rotation2DComponent>)>::query())
         .with_query(<(
             Read<Position2DComponent>,
             TryRead<UniformScale2DComponent>,
@@ -75,20 +75,78 @@ pub fn editor_gizmos() -> Box<dyn Schedulable> {
                 let mut gizmo_tx = None;
                 std::mem::swap(&mut gizmo_tx, editor_state.gizmo_transaction_mut());
 
+                // A transaction is only kept between frames while a gizmo is being dragged
+                let is_drag_in_progress = gizmo_tx.is_some();
+
                 if gizmo_tx.is_none() {
                     gizmo_tx = editor_state
                         .create_transaction_from_selected(&*editor_selection, &*universe_resource);
                 }
 
+                let snap_settings = editor_state.snap_settings().resolve(&*input_state);
+                let gizmo_space = editor_state.gizmo_space();
+
+                // Determine the shared pivot for rotate/scale, if one is being used. The selection
+                // moves while it's dragged, so the pivot from the start of the drag is reused
+                // until the drag ends. Otherwise the selection would drift
+                let pivot = if is_drag_in_progress {
+                    editor_state.gizmo_drag_pivot()
+                } else {
+                    determine_pivot(
+                        editor_state.gizmo_pivot_mode(),
+                        &mut *editor_selection,
+                        subworld,
+                    )
+                };
+
+                // Apply fine adjustment to this frame's movement, and add it to the drag's total
+                let previous_accumulated = if is_drag_in_progress {
+                    editor_state.gizmo_drag_accumulated()
+                } else {
+                    None
+                };
+
+                let drag = editor_draw
+                    .shape_drag_in_progress_or_just_finished(MouseButton::Left)
+                    .map(|drag| snap_settings.scale_drag(drag, previous_accumulated));
+
                 if let Some(mut gizmo_tx) = gizmo_tx {
                     let mut result = GizmoResult::NoChange;
                     result = result.max(handle_translate_gizmo_input(
                         &mut *editor_draw,
+                        drag.as_ref(),
+                        &mut gizmo_tx,
+                        &snap_settings,
+                        gizmo_space,
+                    ));
+                    result = result.max(handle_scale_gizmo_input(
+                        &mut *editor_draw,
+                        drag.as_ref(),
                         &mut gizmo_tx,
+                        &snap_settings,
+                        pivot,
                     ));
-                    result = result.max(handle_scale_gizmo_input(&mut *editor_draw, &mut gizmo_tx));
-                    result =
-                        result.max(handle_rotate_gizmo_input(&mut *editor_draw, &mut gizmo_tx));
+                    result = result.max(handle_rotate_gizmo_input(
+                        &mut *editor_draw,
+                        drag.as_ref(),
+                        &mut gizmo_tx,
+                        &snap_settings,
+                        pivot,
+                    ));
+
+                    if result != GizmoResult::NoChange {
+                        let verb = match editor_state.active_editor_tool() {
+                            EditorTool::Translate => "Translate",
+                            EditorTool::Scale => "Scale",
+                            EditorTool::Rotate => "Rotate",
+                        };
+
+                        gizmo_tx.set_description(format!(
+                            "{} {} entities",
+                            verb,
+                            editor_selection.selected_entities().len()
+                        ));
+                    }
 
                     match result {
                         GizmoResult::NoChange => {}
@@ -96,14 +154,30 @@ pub fn editor_gizmos() -> Box<dyn Schedulable> {
                             gizmo_tx
                                 .update(editor_state, PostCommitSelection::KeepCurrentSelection);
                             *editor_state.gizmo_transaction_mut() = Some(gizmo_tx);
+                            *editor_state.gizmo_drag_pivot_mut() = pivot;
+                            *editor_state.gizmo_drag_accumulated_mut() = drag
+                                .as_ref()
+                                .map(|drag| drag.world_space_accumulated_frame_delta);
                         }
                         GizmoResult::Commit => {
                             gizmo_tx
                                 .commit(editor_state, PostCommitSelection::KeepCurrentSelection);
+                            *editor_state.gizmo_drag_pivot_mut() = None;
+                            *editor_state.gizmo_drag_accumulated_mut() = None;
                         }
                     }
                 }
 
+                // Show where the pivot is when it's shared by the selection
+                if let Some(pivot) = pivot {
+                    let active_editor_tool = editor_state.active_editor_tool();
+                    if active_editor_tool == EditorTool::Rotate
+                        || active_editor_tool == EditorTool::Scale
+                    {
+                        debug_draw.add_circle(pivot, 0.05, glam::vec4(1.0, 1.0, 1.0, 1.0));
+                    }
+                }
+
                 match editor_state.active_editor_tool() {
                     EditorTool::Translate => draw_translate_gizmo(
                         &mut *debug_draw,
@@ -111,6 +185,7 @@ pub fn editor_gizmos() -> Box<dyn Schedulable> {
                         &mut *editor_selection,
                         subworld,
                         translate_query,
+                        gizmo_space,
                     ),
                     EditorTool::Scale => draw_scale_gizmo(
                         &mut *debug_draw,
@@ -139,13 +214,52 @@ enum GizmoResult {
     Commit,
 }
 
+fn rotate_vec2(
+    v: glam::Vec2,
+    angle: f32,
+) -> glam::Vec2 {
+    let (sin, cos) = angle.sin_cos();
+    glam::Vec2::new(v.x() * cos - v.y() * sin, v.x() * sin + v.y() * cos)
+}
+
+// Returns the world-space point that rotation/scaling should happen around, or None if each entity
+// should use its own position
+fn determine_pivot(
+    pivot_mode: GizmoPivotMode,
+    editor_selection: &mut EditorSelectionResource,
+    subworld: &SubWorld,
+) -> Option<glam::Vec2> {
+    let selection_center = |editor_selection: &mut EditorSelectionResource| {
+        editor_selection.selection_aabb().map(|aabb| {
+            let center = aabb.center();
+            glam::Vec2::new(center.x, center.y)
+        })
+    };
+
+    match pivot_mode {
+        GizmoPivotMode::IndividualOrigins => None,
+        GizmoPivotMode::SelectionCenter => selection_center(editor_selection),
+        GizmoPivotMode::ActiveEntity => {
+            let active_entity_position = editor_selection.active_entity().and_then(|entity| {
+                subworld
+                    .get_component::<Position2DComponent>(entity)
+                    .map(|position| *position.position)
+            });
+
+            // Fall back to the selection center if the active entity doesn't have a position
+            active_entity_position.or_else(|| selection_center(editor_selection))
+        }
+    }
+}
+
 fn handle_translate_gizmo_input(
     editor_draw: &mut EditorDrawResource,
+    drag_in_progress: Option<&EditorShapeDragState>,
     tx: &mut EditorTransaction,
+    snap_settings: &ResolvedSnapSettings,
+    gizmo_space: GizmoSpace,
 ) -> GizmoResult {
-    if let Some(drag_in_progress) =
-        editor_draw.shape_drag_in_progress_or_just_finished(MouseButton::Left)
-    {
+    if let Some(drag_in_progress) = drag_in_progress {
         // See what if any axis we will operate on
         let mut translate_x = false;
         let mut translate_y = false;
@@ -163,24 +277,81 @@ fn handle_translate_gizmo_input(
             return GizmoResult::NoChange;
         }
 
-        // Determine the drag distance in ui_space
-        let mut world_space_previous_frame_delta =
-            drag_in_progress.world_space_previous_frame_delta;
+        // Determine how far to move this frame, taking snapping and fine adjustment into account
+        let accumulated = drag_in_progress.world_space_accumulated_frame_delta;
+        let previous_frame_delta = drag_in_progress.world_space_previous_frame_delta;
 
-        if !translate_x {
-            world_space_previous_frame_delta.set_x(0.0);
-        }
+        let increment_x = if translate_x {
+            snap_settings.drag_increment(
+                accumulated.x(),
+                previous_frame_delta.x(),
+                snap_settings.grid_step,
+            )
+        } else {
+            0.0
+        };
+
+        let increment_y = if translate_y {
+            snap_settings.drag_increment(
+                accumulated.y(),
+                previous_frame_delta.y(),
+                snap_settings.grid_step,
+            )
+        } else {
+            0.0
+        };
+
+        let query = <(Write<Position2DComponent>, TryRead<Rotation2DComponent>)>::query();
+
+        for (entity_handle, (mut position, rotation)) in query.iter_entities_mut(tx.world_mut()) {
+            let local_rotation = match (gizmo_space, rotation) {
+                (GizmoSpace::Local, Some(rotation)) => rotation.rotation,
+                _ => 0.0,
+            };
+
+            if local_rotation != 0.0 {
+                // Project the drag onto the entity's rotated axes. Positions aren't aligned to the
+                // world grid in this case as the axes don't line up with it
+                let x_axis = rotate_vec2(glam::Vec2::unit_x(), local_rotation);
+                let y_axis = rotate_vec2(glam::Vec2::unit_y(), local_rotation);
+
+                let mut offset = glam::Vec2::zero();
+                if translate_x {
+                    offset += x_axis
+                        * snap_settings.drag_increment(
+                            accumulated.dot(x_axis),
+                            previous_frame_delta.dot(x_axis),
+                            snap_settings.grid_step,
+                        );
+                }
 
-        if !translate_y {
-            world_space_previous_frame_delta.set_y(0.0);
-        }
+                if translate_y {
+                    offset += y_axis
+                        * snap_settings.drag_increment(
+                            accumulated.dot(y_axis),
+                            previous_frame_delta.dot(y_axis),
+                            snap_settings.grid_step,
+                        );
+                }
 
-        let query = <(Write<Position2DComponent>)>::query();
+                *position.position += offset;
+                continue;
+            }
 
-        for (entity_handle, mut position) in query.iter_entities_mut(tx.world_mut()) {
             // Can use editor_draw.is_shape_drag_just_finished(MouseButton::Left) to see if this is the final drag,
             // in which case we might want to save an undo step
-            *position.position += world_space_previous_frame_delta;
+            let x = snap_settings.apply_increment(
+                position.position.x(),
+                increment_x,
+                snap_settings.grid_step,
+            );
+            let y = snap_settings.apply_increment(
+                position.position.y(),
+                increment_y,
+                snap_settings.grid_step,
+            );
+            position.position.set_x(x);
+            position.position.set_y(y);
         }
 
         if editor_draw.is_shape_drag_just_finished(MouseButton::Left) {
@@ -199,11 +370,16 @@ fn draw_translate_gizmo(
     selection_world: &mut EditorSelectionResource,
     subworld: &SubWorld,
     translate_query: &mut legion::systems::SystemQuery<
-        Read<Position2DComponent>,
-        EntityFilterTuple<ComponentFilter<Position2DComponent>, Passthrough, Passthrough>,
+        (Read<Position2DComponent>, TryRead<Rotation2DComponent>),
+        EntityFilterTuple<
+            And<(ComponentFilter<Position2DComponent>, Passthrough)>,
+            And<(Passthrough, Passthrough)>,
+            And<(Passthrough, Passthrough)>,
+        >,
     >,
+    gizmo_space: GizmoSpace,
 ) {
-    for (entity, position) in translate_query.iter_entities(subworld) {
+    for (entity, (position, rotation)) in translate_query.iter_entities(subworld) {
         if !selection_world.is_entity_selected(entity) {
             continue;
         }
@@ -214,32 +390,39 @@ fn draw_translate_gizmo(
 
         let xy_position = glam::Vec2::new(position.position.x(), position.position.y());
 
+        // In local space, the gizmo is rotated to match the entity
+        let local_rotation = match (gizmo_space, rotation) {
+            (GizmoSpace::Local, Some(rotation)) => rotation.rotation,
+            _ => 0.0,
+        };
+
         //TODO: Make this resolution independent. Need a UI multiplier?
 
         let ui_multiplier = 0.01;
+        let offset = |x: f32, y: f32| rotate_vec2(glam::vec2(x, y) * ui_multiplier, local_rotation);
 
         // x axis line
         editor_draw.add_line(
             "x_axis_translate",
             debug_draw,
             xy_position,
-            xy_position + (glam::vec2(100.0, 0.0) * ui_multiplier),
+            xy_position + offset(100.0, 0.0),
             x_color,
         );
 
         editor_draw.add_line(
             "x_axis_translate",
             debug_draw,
-            xy_position + (glam::vec2(85.0, 15.0) * ui_multiplier),
-            xy_position + (glam::vec2(100.0, 0.0) * ui_multiplier),
+            xy_position + offset(85.0, 15.0),
+            xy_position + offset(100.0, 0.0),
             x_color,
         );
 
         editor_draw.add_line(
             "x_axis_translate",
             debug_draw,
-            xy_position + (glam::vec2(85.0, -15.0) * ui_multiplier),
-            xy_position + (glam::vec2(100.0, 0.0) * ui_multiplier),
+            xy_position + offset(85.0, -15.0),
+            xy_position + offset(100.0, 0.0),
             x_color,
         );
 
@@ -248,23 +431,23 @@ fn draw_translate_gizmo(
             "y_axis_translate",
             debug_draw,
             xy_position,
-            xy_position + (glam::vec2(0.0, 100.0) * ui_multiplier),
+            xy_position + offset(0.0, 100.0),
             y_color,
         );
 
         editor_draw.add_line(
             "y_axis_translate",
             debug_draw,
-            xy_position + (glam::vec2(-15.0, 85.0) * ui_multiplier),
-            xy_position + (glam::vec2(0.0, 100.0) * ui_multiplier),
+            xy_position + offset(-15.0, 85.0),
+            xy_position + offset(0.0, 100.0),
             y_color,
         );
 
         editor_draw.add_line(
             "y_axis_translate",
             debug_draw,
-            xy_position + (glam::vec2(15.0, 85.0) * ui_multiplier),
-            xy_position + (glam::vec2(0.0, 100.0) * ui_multiplier),
+            xy_position + offset(15.0, 85.0),
+            xy_position + offset(0.0, 100.0),
             y_color,
         );
 
@@ -272,8 +455,8 @@ fn draw_translate_gizmo(
         editor_draw.add_line(
             "xy_axis_translate",
             debug_draw,
-            xy_position + (glam::vec2(0.0, 25.0) * ui_multiplier),
-            xy_position + (glam::vec2(25.0, 25.0) * ui_multiplier),
+            xy_position + offset(0.0, 25.0),
+            xy_position + offset(25.0, 25.0),
             xy_color,
         );
 
@@ -281,8 +464,8 @@ fn draw_translate_gizmo(
         editor_draw.add_line(
             "xy_axis_translate",
             debug_draw,
-            xy_position + (glam::vec2(25.0, 0.0) * ui_multiplier),
-            xy_position + (glam::vec2(25.0, 25.0) * ui_multiplier),
+            xy_position + offset(25.0, 0.0),
+            xy_position + offset(25.0, 25.0),
             xy_color,
         );
     }
@@ -309,13 +492,50 @@ fn sign_aware_magnitude(v: glam::Vec2) -> f32 {
     }
 }
 
+// Returns (accumulated, previous_frame_delta) for a drag, collapsed to a single signed value. This
+// keeps the per-frame delta consistent with the accumulated value so that snapping works
+fn sign_aware_drag_magnitude(drag: &EditorShapeDragState) -> (f32, f32) {
+    let accumulated = sign_aware_magnitude(drag.world_space_accumulated_frame_delta);
+    let previous_accumulated = sign_aware_magnitude(
+        drag.world_space_accumulated_frame_delta - drag.world_space_previous_frame_delta,
+    );
+    (accumulated, accumulated - previous_accumulated)
+}
+
+// When scaling around a shared pivot, entities also move away from/toward the pivot by the same ratio
+// that their scale changed
+fn scale_position_around_pivot(
+    position: Option<&mut Position2DComponent>,
+    pivot: Option<glam::Vec2>,
+    old_scale: glam::Vec2,
+    new_scale: glam::Vec2,
+) {
+    if let (Some(position), Some(pivot)) = (position, pivot) {
+        let ratio = |old: f32, new: f32| {
+            if old.abs() > std::f32::EPSILON {
+                new / old
+            } else {
+                1.0
+            }
+        };
+
+        let ratio = glam::Vec2::new(
+            ratio(old_scale.x(), new_scale.x()),
+            ratio(old_scale.y(), new_scale.y()),
+        );
+
+        *position.position = pivot + (*position.position - pivot) * ratio;
+    }
+}
+
 fn handle_scale_gizmo_input(
     editor_draw: &mut EditorDrawResource,
+    drag_in_progress: Option<&EditorShapeDragState>,
     tx: &mut EditorTransaction,
+    snap_settings: &ResolvedSnapSettings,
+    pivot: Option<glam::Vec2>,
 ) -> GizmoResult {
-    if let Some(drag_in_progress) =
-        editor_draw.shape_drag_in_progress_or_just_finished(MouseButton::Left)
-    {
+    if let Some(drag_in_progress) = drag_in_progress {
         // See what if any axis we will operate on
         let mut scale_x = false;
         let mut scale_y = false;
@@ -336,33 +556,75 @@ fn handle_scale_gizmo_input(
         // Determine the drag distance in ui_space
         //TODO: I was intending this to use ui space but the values during drag are not lining up
         // with values on end drag. This is likely an fp precision issue.
-        let mut ui_space_previous_frame_delta = drag_in_progress.world_space_previous_frame_delta;
-
-        if !scale_x && !scale_uniform {
-            ui_space_previous_frame_delta.set_x(0.0);
-        }
-
-        if !scale_y && !scale_uniform {
-            ui_space_previous_frame_delta.set_y(0.0);
-        }
-
-        if scale_uniform {
-            ui_space_previous_frame_delta
-                .set_x(sign_aware_magnitude(ui_space_previous_frame_delta));
-            ui_space_previous_frame_delta.set_y(ui_space_previous_frame_delta.x());
-        }
-
+        let scale_step = snap_settings.scale_step;
         if scale_uniform {
-            let query = <(Write<UniformScale2DComponent>)>::query();
-
-            for (entity_handle, mut uniform_scale) in query.iter_entities_mut(tx.world_mut()) {
-                uniform_scale.uniform_scale += ui_space_previous_frame_delta.x()
+            let (accumulated, previous_frame_delta) = sign_aware_drag_magnitude(drag_in_progress);
+            let increment =
+                snap_settings.drag_increment(accumulated, previous_frame_delta, scale_step);
+
+            let query = <(
+                Write<UniformScale2DComponent>,
+                TryWrite<Position2DComponent>,
+            )>::query();
+
+            for (entity_handle, (mut uniform_scale, mut position)) in
+                query.iter_entities_mut(tx.world_mut())
+            {
+                let old_scale = uniform_scale.uniform_scale;
+                uniform_scale.uniform_scale =
+                    snap_settings.apply_increment(old_scale, increment, scale_step);
+
+                scale_position_around_pivot(
+                    position.as_deref_mut(),
+                    pivot,
+                    glam::Vec2::splat(old_scale),
+                    glam::Vec2::splat(uniform_scale.uniform_scale),
+                );
             }
         } else {
-            let query = <(Write<NonUniformScale2DComponent>)>::query();
-
-            for (entity_handle, mut non_uniform_scale) in query.iter_entities_mut(tx.world_mut()) {
-                *non_uniform_scale.non_uniform_scale += ui_space_previous_frame_delta
+            let accumulated = drag_in_progress.world_space_accumulated_frame_delta;
+            let previous_frame_delta = drag_in_progress.world_space_previous_frame_delta;
+
+            let increment_x = if scale_x {
+                snap_settings.drag_increment(accumulated.x(), previous_frame_delta.x(), scale_step)
+            } else {
+                0.0
+            };
+
+            let increment_y = if scale_y {
+                snap_settings.drag_increment(accumulated.y(), previous_frame_delta.y(), scale_step)
+            } else {
+                0.0
+            };
+
+            let query = <(
+                Write<NonUniformScale2DComponent>,
+                TryWrite<Position2DComponent>,
+            )>::query();
+
+            for (entity_handle, (mut non_uniform_scale, mut position)) in
+                query.iter_entities_mut(tx.world_mut())
+            {
+                let old_scale = *non_uniform_scale.non_uniform_scale;
+                let x = snap_settings.apply_increment(
+                    non_uniform_scale.non_uniform_scale.x(),
+                    increment_x,
+                    scale_step,
+                );
+                let y = snap_settings.apply_increment(
+                    non_uniform_scale.non_uniform_scale.y(),
+                    increment_y,
+                    scale_step,
+                );
+                non_uniform_scale.non_uniform_scale.set_x(x);
+                non_uniform_scale.non_uniform_scale.set_y(y);
+
+                scale_position_around_pivot(
+                    position.as_deref_mut(),
+                    pivot,
+                    old_scale,
+                    *non_uniform_scale.non_uniform_scale,
+                );
             }
         }
 
@@ -477,11 +739,12 @@ fn draw_scale_gizmo(
 
 fn handle_rotate_gizmo_input(
     editor_draw: &mut EditorDrawResource,
+    drag_in_progress: Option<&EditorShapeDragState>,
     tx: &mut EditorTransaction,
+    snap_settings: &ResolvedSnapSettings,
+    pivot: Option<glam::Vec2>,
 ) -> GizmoResult {
-    if let Some(drag_in_progress) =
-        editor_draw.shape_drag_in_progress_or_just_finished(MouseButton::Left)
-    {
+    if let Some(drag_in_progress) = drag_in_progress {
         // See what if any axis we will operate on
         let mut rotate_z = false;
         if drag_in_progress.shape_id == "z_axis_rotate" {
@@ -499,12 +762,29 @@ fn handle_rotate_gizmo_input(
         // Determine the drag distance in ui_space
         //TODO: I was intending this to use ui space but the values during drag are not lining up
         // with values on end drag. This is likely an fp precision issue.
-        let ui_space_previous_frame_delta =
-            sign_aware_magnitude(drag_in_progress.world_space_previous_frame_delta);
-
-        let query = <(Write<Rotation2DComponent>)>::query();
-        for (entity_handle, mut rotation) in query.iter_entities_mut(tx.world_mut()) {
-            rotation.rotation += ui_space_previous_frame_delta
+        let (accumulated, previous_frame_delta) = sign_aware_drag_magnitude(drag_in_progress);
+        let rotate_step = snap_settings.rotate_step;
+        let increment =
+            snap_settings.drag_increment(accumulated, previous_frame_delta, rotate_step);
+
+        if let Some(pivot) = pivot {
+            // Orbit all entities around the shared pivot. Every entity is rotated by the same
+            // amount (rather than aligning each to the snap step) so that the group stays rigid
+            let query = <(Write<Position2DComponent>, TryWrite<Rotation2DComponent>)>::query();
+            for (entity_handle, (mut position, rotation)) in query.iter_entities_mut(tx.world_mut())
+            {
+                *position.position = pivot + rotate_vec2(*position.position - pivot, increment);
+
+                if let Some(mut rotation) = rotation {
+                    rotation.rotation += increment;
+                }
+            }
+        } else {
+            let query = <(Write<Rotation2DComponent>)>::query();
+            for (entity_handle, mut rotation) in query.iter_entities_mut(tx.world_mut()) {
+                rotation.rotation =
+                    snap_settings.apply_increment(rotation.rotation, increment, rotate_step);
+            }
         }
 
         if editor_draw.is_shape_drag_just_finished(MouseButton::Left) {
@@ -0,0 +1,76 @@
+use legion::prelude::*;
+
+use crate::resources::{EditorStateResource, ViewportResource, DebugDrawResource};
+
+// If zoomed out far enough that we would draw more than this many lines in either direction, the
+// grid step is doubled until we are under the limit
+const MAX_GRID_LINES_PER_AXIS: f32 = 200.0;
+
+pub fn editor_draw_grid() -> Box<dyn Schedulable> {
+    SystemBuilder::new("editor_draw_grid")
+        .read_resource::<EditorStateResource>()
+        .read_resource::<ViewportResource>()
+        .write_resource::<DebugDrawResource>()
+        .build(|_, _, (editor_state, viewport, debug_draw), _| {
+            let snap_settings = editor_state.snap_settings();
+            if !snap_settings.show_grid || snap_settings.grid_step <= 0.0 {
+                return;
+            }
+
+            let camera_position = viewport.world_space_camera_position();
+            let half_extents = viewport.view_half_extents();
+
+            let mins = glam::Vec2::new(
+                camera_position.x() - half_extents.x(),
+                camera_position.y() - half_extents.y(),
+            );
+            let maxs = glam::Vec2::new(
+                camera_position.x() + half_extents.x(),
+                camera_position.y() + half_extents.y(),
+            );
+
+            // Avoid drawing an excessive number of lines when zoomed out
+            let mut step = snap_settings.grid_step;
+            while (half_extents.x().max(half_extents.y()) * 2.0) / step > MAX_GRID_LINES_PER_AXIS {
+                step *= 2.0;
+            }
+
+            let line_color = glam::vec4(0.25, 0.25, 0.25, 1.0);
+            let x_axis_color = glam::vec4(0.0, 0.5, 0.0, 1.0);
+            let y_axis_color = glam::vec4(0.5, 0.3, 0.0, 1.0);
+
+            // Vertical lines
+            let mut x = (mins.x() / step).floor() * step;
+            while x <= maxs.x() {
+                let color = if x.abs() < step * 0.5 {
+                    y_axis_color
+                } else {
+                    line_color
+                };
+
+                debug_draw.add_line(
+                    glam::Vec2::new(x, mins.y()),
+                    glam::Vec2::new(x, maxs.y()),
+                    color,
+                );
+                x += step;
+            }
+
+            // Horizontal lines
+            let mut y = (mins.y() / step).floor() * step;
+            while y <= maxs.y() {
+                let color = if y.abs() < step * 0.5 {
+                    x_axis_color
+                } else {
+                    line_color
+                };
+
+                debug_draw.add_line(
+                    glam::Vec2::new(mins.x(), y),
+                    glam::Vec2::new(maxs.x(), y),
+                    color,
+                );
+                y += step;
+            }
+        })
+}
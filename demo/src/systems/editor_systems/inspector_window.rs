@@ -31,6 +31,46 @@ use legion_transaction::ComponentDiff;
 use std::sync::Arc;
 use crate::components::Position2DComponent;
 use atelier_core::asset_uuid;
+use legion_prefab::ComponentRegistration;
+
+// Draws the filter text box and returns the component types that match it, sorted by name. Types
+// that only exist as the result of spawning (i.e. RigidBodyComponent) are never returned since
+// they can't be saved in a prefab
+fn filtered_component_types(
+    ui: &imgui::Ui,
+    editor_ui_state: &mut EditorStateResource,
+) -> Vec<ComponentRegistration> {
+    ui.input_text(
+        im_str!("Filter"),
+        &mut editor_ui_state.add_component_search_text,
+    )
+    .resize_buffer(true)
+    .build();
+
+    // Lowercase the text to do a case-insensitive compare. Every whitespace-separated term must be
+    // found in the name
+    let filter_string = editor_ui_state
+        .add_component_search_text
+        .to_str()
+        .to_lowercase();
+    let filter_terms: Vec<_> = filter_string.split_whitespace().collect();
+
+    let spawn_mappings = editor_ui_state.component_spawn_mappings();
+    let mut component_types: Vec<_> = editor_ui_state
+        .component_registry()
+        .iter()
+        .filter(|(component_type_id, _)| !spawn_mappings.is_runtime_only(**component_type_id))
+        .filter(|(_, t)| {
+            let type_name = t.type_name().to_lowercase();
+            filter_terms.iter().all(|term| type_name.contains(term))
+        })
+        .map(|(_, t)| t.clone())
+        .collect();
+
+    // Sort components alphabetically
+    component_types.sort_by(|t1, t2| t1.type_name().cmp(t2.type_name()));
+    component_types
+}
 
 pub fn editor_inspector_window(
     world: &mut World,
@@ -69,8 +109,8 @@ pub fn editor_inspector_window(
 
             if window_options.show_inspector {
                 imgui::Window::new(im_str!("Inspector"))
-                    .position([0.0, 300.0], imgui::Condition::Once)
-                    .size([350.0, 300.0], imgui::Condition::Once)
+                    .position([0.0, 300.0], imgui::Condition::FirstUseEver)
+                    .size([350.0, 300.0], imgui::Condition::FirstUseEver)
                     .build(ui, || {
                         let mut tx = editor_ui_state.create_transaction_from_selected(
                             &*selection_world,
@@ -78,92 +118,118 @@ pub fn editor_inspector_window(
                         );
 
                         if let Some(mut tx) = tx {
-                            // Draw a button to bring up the add component menu
+                            // Make a list of all entities, this is necessary because we can't take an &-borrow for uuid_to_entities at the
+                            // same time as an &mut-borrow for world_mut()
+                            let all_entities: Vec<Entity> = tx.world().iter_entities().collect();
+
+                            if all_entities.len() > 1 {
+                                ui.text(im_str!(
+                                    "Editing {} entities (only shared components are shown)",
+                                    all_entities.len()
+                                ));
+                            }
+
+                            // Draw buttons to bring up the add/remove component menus
                             if ui.button(im_str!("\u{e8b1} Add"), [80.0, 0.0]) {
                                 ui.open_popup(im_str!("Add Component"));
                             }
 
+                            ui.same_line_with_spacing(80.0, 10.0);
+                            if ui.button(im_str!("\u{e897} Remove"), [80.0, 0.0]) {
+                                ui.open_popup(im_str!("Remove Component"));
+                            }
+
                             let mut component_type_to_add = None;
+                            let mut component_type_to_remove = None;
 
                             // Render the add component pop-up. It has a filtering text box and lists
                             // component types that can be clicked
                             ui.popup(im_str!("Add Component"), || {
-                                // Draw the filter text box
-                                ui.input_text(
-                                    im_str!("Filter"),
-                                    &mut editor_ui_state.add_component_search_text,
-                                )
-                                .resize_buffer(true)
-                                .build();
-
-                                // Lowercase the text to do a case-insensitive compare
-                                let filter_string = editor_ui_state
-                                    .add_component_search_text
-                                    .to_str()
-                                    .to_lowercase();
-
-                                // Get a list of all component types that match the filter (or don't filter if the string is empty)
-                                let mut component_types: Vec<_> = editor_ui_state
-                                    .component_registry()
-                                    .iter()
-                                    .filter(|(_, t)| {
-                                        filter_string.is_empty()
-                                            || t.type_name().to_lowercase().contains(&filter_string)
-                                    })
-                                    .collect();
-
-                                // Sort components alphabetically
-                                component_types
-                                    .sort_by(|(_, t1), (_, t2)| t1.type_name().cmp(t2.type_name()));
-
-                                let mut can_add_to_some_entity =
-                                    Vec::with_capacity(component_types.len());
-                                can_add_to_some_entity.resize(component_types.len(), false);
-
-                                //TODO: Determine what components can be added - Consider the type that this would become when spawned
-                                for (index, (_, component_type)) in
-                                    component_types.iter().enumerate()
-                                {
-                                    for entity in tx.world().iter_entities() {
-                                        if !tx.world().has_component_by_id(
-                                            entity,
-                                            component_type.component_type_id(),
-                                        ) {
-                                            can_add_to_some_entity[index] = true;
-                                            break;
-                                        }
+                                let component_types =
+                                    filtered_component_types(ui, &mut *editor_ui_state);
+
+                                // A component can be added if at least one selected entity doesn't
+                                // already have it (or something that would spawn into the same type)
+                                for component_type in component_types {
+                                    let can_add_to_some_entity =
+                                        all_entities.iter().any(|entity| {
+                                            editor_ui_state.can_add_component(
+                                                tx.world(),
+                                                *entity,
+                                                &component_type,
+                                            )
+                                        });
+
+                                    //TODO: Consider drawing by hierarchy of component type.. i.e. PhysicsComponent -> PhysicsComponentBoxPrototype
+                                    if imgui::Selectable::new(&im_str!(
+                                        "{}",
+                                        component_type.type_name()
+                                    ))
+                                    .disabled(!can_add_to_some_entity)
+                                    .build(ui)
+                                    {
+                                        component_type_to_add = Some(component_type);
                                     }
                                 }
+                            });
+
+                            // Render the remove component pop-up. It lists component types that are
+                            // on at least one of the selected entities
+                            ui.popup(im_str!("Remove Component"), || {
+                                let component_types =
+                                    filtered_component_types(ui, &mut *editor_ui_state);
+
+                                for component_type in component_types {
+                                    let on_some_entity = all_entities.iter().any(|entity| {
+                                        tx.world().has_component_by_id(
+                                            *entity,
+                                            component_type.component_type_id(),
+                                        )
+                                    });
 
-                                // Draw all the menu items, if one of them is clicked store it in component_type_to_add
-                                //TODO: Consider drawing by hierarchy of component type.. i.e. PhysicsComponent -> PhysicsComponentBoxPrototype
-                                for (index, (_, v)) in component_types.iter().enumerate() {
-                                    let disabled = !can_add_to_some_entity[index];
-                                    if imgui::Selectable::new(&im_str!("{}", v.type_name()))
-                                        .disabled(disabled)
+                                    if on_some_entity
+                                        && imgui::Selectable::new(&im_str!(
+                                            "{}",
+                                            component_type.type_name()
+                                        ))
                                         .build(ui)
                                     {
-                                        component_type_to_add = Some(v.clone());
+                                        component_type_to_remove = Some(component_type);
                                     }
                                 }
                             });
 
                             let mut commit_required = false;
 
-                            // Make a list of all entities, this is necessary because we can't take an &-borrow for uuid_to_entities at the
-                            // same time as an &mut-borrow for world_mut()
-                            let all_entities: Vec<Entity> = tx.world().iter_entities().collect();
-
                             //
-                            // If a component needs to be added, do that now
+                            // If a component needs to be added, add it to every selected entity that can accept it
                             //
                             if let Some(component_type_to_add) = component_type_to_add {
-                                //TODO: Add this component to all selected entities
+                                // This commits its own transaction, the same as the command palette
+                                editor_ui_state.add_component_to_selected(
+                                    &*selection_world,
+                                    &*universe_resource,
+                                    &component_type_to_add,
+                                );
+                            }
+
+                            //
+                            // If a component needs to be removed, remove it from every selected entity
+                            //
+                            if let Some(component_type_to_remove) = component_type_to_remove {
+                                tx.set_description(format!(
+                                    "Remove {}",
+                                    component_type_to_remove.type_name()
+                                ));
                                 for e in &all_entities {
-                                    // e guaranteed to be Some, this is a new transaction and we aren't deleting entities
-                                    component_type_to_add
-                                        .add_default_to_entity(tx.world_mut(), *e)
-                                        .unwrap();
+                                    if tx.world().has_component_by_id(
+                                        *e,
+                                        component_type_to_remove.component_type_id(),
+                                    ) {
+                                        component_type_to_remove
+                                            .remove_from_entity(tx.world_mut(), *e)
+                                            .unwrap();
+                                    }
                                 }
 
                                 commit_required |= true;
@@ -173,13 +239,18 @@ pub fn editor_inspector_window(
                             // Draw inspect widgets
                             //
                             let registry = crate::create_editor_inspector_registry();
-                            commit_required |= registry.render_mut(
+                            let edited = registry.render_mut(
                                 tx.world_mut(),
                                 &all_entities,
                                 ui,
                                 &Default::default(),
                             );
 
+                            if edited {
+                                tx.set_description(format!("Edit {} entities", all_entities.len()));
+                                commit_required = true;
+                            }
+
                             if commit_required {
                                 tx.commit(
                                     &mut editor_ui_state,
@@ -0,0 +1,75 @@
+use legion::prelude::*;
+
+use crate::resources::EditorStateResource;
+use crate::resources::ImguiResource;
+use crate::resources::{EditorAction, EditorKeybindingsResource};
+
+use imgui;
+use imgui::im_str;
+
+pub fn editor_keybindings_window() -> Box<dyn Schedulable> {
+    SystemBuilder::new("editor_keybindings_window")
+        .write_resource::<ImguiResource>()
+        .read_resource::<EditorStateResource>()
+        .write_resource::<EditorKeybindingsResource>()
+        .build(|_, _, (imgui_manager, editor_state, keybindings), _| {
+            imgui_manager.with_ui(|ui: &mut imgui::Ui| {
+                if !editor_state.window_options().show_keybindings {
+                    // Don't leave a rebind waiting for a key if the window was closed
+                    if keybindings.rebinding_action().is_some() {
+                        keybindings.cancel_rebinding();
+                    }
+
+                    return;
+                }
+
+                imgui::Window::new(im_str!("Keybindings"))
+                    .position([550.0, 100.0], imgui::Condition::FirstUseEver)
+                    .size([450.0, 500.0], imgui::Condition::FirstUseEver)
+                    .build(ui, || {
+                        if ui.button(im_str!("Reset to Defaults"), [160.0, 0.0]) {
+                            keybindings.reset_to_defaults();
+                        }
+
+                        ui.separator();
+                        ui.columns(3, im_str!("keybindings"), true);
+
+                        for (index, action) in EditorAction::ALL.iter().enumerate() {
+                            let id_token = ui.push_id(index as i32);
+
+                            ui.text(im_str!("{}", action.name()));
+                            ui.next_column();
+
+                            if keybindings.rebinding_action() == Some(*action) {
+                                ui.text(im_str!("Press a key (Escape cancels)..."));
+                            } else if let Some(key_chord) = keybindings.binding(*action) {
+                                ui.text(im_str!("{}", key_chord));
+                            } else {
+                                ui.text_disabled(im_str!("Unbound"));
+                            }
+                            ui.next_column();
+
+                            if keybindings.rebinding_action() == Some(*action) {
+                                if ui.small_button(im_str!("Cancel")) {
+                                    keybindings.cancel_rebinding();
+                                }
+                            } else {
+                                if ui.small_button(im_str!("Rebind")) {
+                                    keybindings.begin_rebinding(*action);
+                                }
+
+                                ui.same_line(0.0);
+                                if ui.small_button(im_str!("Clear")) {
+                                    keybindings.set_binding(*action, None);
+                                }
+                            }
+                            ui.next_column();
+
+                            id_token.pop(ui);
+                        }
+
+                        ui.columns(1, im_str!(""), false);
+                    });
+            });
+        })
+}
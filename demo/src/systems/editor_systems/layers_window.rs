@@ -0,0 +1,57 @@
+use legion::prelude::*;
+
+use crate::components::DrawLayer;
+use crate::inspect::InspectEnum;
+use crate::resources::EditorStateResource;
+use crate::resources::ImguiResource;
+
+use imgui;
+use imgui::im_str;
+
+pub fn editor_layers_window() -> Box<dyn Schedulable> {
+    SystemBuilder::new("editor_layers_window")
+        .write_resource::<ImguiResource>()
+        .write_resource::<EditorStateResource>()
+        .build(|_, _, (imgui_manager, editor_state), _| {
+            imgui_manager.with_ui(|ui: &mut imgui::Ui| {
+                if !editor_state.window_options().show_layers {
+                    return;
+                }
+
+                imgui::Window::new(im_str!("Layers"))
+                    .position([350.0, 450.0], imgui::Condition::FirstUseEver)
+                    .size([300.0, 130.0], imgui::Condition::FirstUseEver)
+                    .build(ui, || {
+                        ui.columns(3, im_str!("layers"), true);
+                        ui.text(im_str!("Layer"));
+                        ui.next_column();
+                        ui.text(im_str!("Visible"));
+                        ui.next_column();
+                        ui.text(im_str!("Locked"));
+                        ui.next_column();
+                        ui.separator();
+
+                        // Layers are listed front to back, the same way they appear on screen
+                        let draw_layer_settings = editor_state.draw_layer_settings_mut();
+                        for (layer, name) in DrawLayer::variants().iter().rev() {
+                            ui.text(&im_str!("{}", name));
+                            ui.next_column();
+
+                            let mut visible = draw_layer_settings.is_visible(*layer);
+                            if ui.checkbox(&im_str!("###visible_{}", name), &mut visible) {
+                                draw_layer_settings.set_visible(*layer, visible);
+                            }
+                            ui.next_column();
+
+                            let mut locked = draw_layer_settings.is_locked(*layer);
+                            if ui.checkbox(&im_str!("###locked_{}", name), &mut locked) {
+                                draw_layer_settings.set_locked(*layer, locked);
+                            }
+                            ui.next_column();
+                        }
+
+                        ui.columns(1, im_str!(""), false);
+                    });
+            });
+        })
+}
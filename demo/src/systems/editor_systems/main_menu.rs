@@ -6,6 +6,8 @@ use crate::resources::{
 };
 use crate::resources::ImguiResource;
 use crate::resources::EditorTool;
+use crate::resources::{GizmoPivotMode, GizmoSpace, DiscardChangesAction};
+use crate::resources::find_prefab_source_files;
 use legion_transaction::{TransactionBuilder, Transaction};
 
 use imgui;
@@ -76,6 +78,101 @@ pub fn editor_imgui_menu() -> Box<dyn Schedulable> {
                     }
                 }
 
+                // If unsaved changes from a previous session were found, ask whether to recover them
+                if let Some(step_count) = editor_state.recoverable_journal_step_count() {
+                    ui.open_popup(im_str!("Recover Unsaved Changes"));
+                    ui.popup_modal(im_str!("Recover Unsaved Changes"))
+                        .always_auto_resize(true)
+                        .build(|| {
+                            ui.text(im_str!(
+                                "Found {} unsaved changes from a previous session.",
+                                step_count
+                            ));
+                            ui.text(im_str!("Recover them?"));
+
+                            if ui.button(im_str!("Recover"), [120.0, 0.0]) {
+                                editor_state.enqueue_recover_journal();
+                                ui.close_current_popup();
+                            }
+
+                            ui.same_line(0.0);
+                            if ui.button(im_str!("Discard"), [120.0, 0.0]) {
+                                editor_state.enqueue_discard_journal();
+                                ui.close_current_popup();
+                            }
+                        });
+                }
+
+                // If an action would discard unsaved changes, ask before doing it
+                if let Some(action) = editor_state.pending_discard_changes_action() {
+                    ui.open_popup(im_str!("Unsaved Changes"));
+                    ui.popup_modal(im_str!("Unsaved Changes"))
+                        .always_auto_resize(true)
+                        .build(|| match action {
+                            DiscardChangesAction::HotReload(_) => {
+                                ui.text(im_str!(
+                                    "The prefab was changed on disk, but there are unsaved changes."
+                                ));
+                                ui.text(im_str!("Reloading will discard them."));
+
+                                if ui.button(im_str!("Reload"), [120.0, 0.0]) {
+                                    editor_state.enqueue_confirm_discard_changes();
+                                    ui.close_current_popup();
+                                }
+
+                                ui.same_line(0.0);
+                                if ui.button(im_str!("Keep Changes"), [120.0, 0.0]) {
+                                    editor_state.enqueue_cancel_discard_changes();
+                                    ui.close_current_popup();
+                                }
+                            }
+                            DiscardChangesAction::Quit => {
+                                ui.text(im_str!("These documents have unsaved changes:"));
+                                for name in editor_state.documents_with_unsaved_changes() {
+                                    ui.bullet_text(&im_str!("{}", name));
+                                }
+
+                                if ui.button(im_str!("Quit Without Saving"), [160.0, 0.0]) {
+                                    editor_state.enqueue_confirm_discard_changes();
+                                    ui.close_current_popup();
+                                }
+
+                                ui.same_line(0.0);
+                                if ui.button(im_str!("Cancel"), [120.0, 0.0]) {
+                                    editor_state.enqueue_cancel_discard_changes();
+                                    ui.close_current_popup();
+                                }
+                            }
+                            DiscardChangesAction::CloseDocument(_) => {
+                                // Documents with unsaved changes are made active before closing,
+                                // so saving here saves the document being closed
+                                ui.text(im_str!("There are unsaved changes. Save them first?"));
+
+                                // A failed save keeps the document open, so say why
+                                if let Some(error) = editor_state.save_error() {
+                                    ui.text_colored([1.0, 0.3, 0.3, 1.0], &im_str!("{}", error));
+                                }
+
+                                if ui.button(im_str!("Save"), [120.0, 0.0]) {
+                                    editor_state.enqueue_save_and_confirm_discard_changes();
+                                    ui.close_current_popup();
+                                }
+
+                                ui.same_line(0.0);
+                                if ui.button(im_str!("Discard"), [120.0, 0.0]) {
+                                    editor_state.enqueue_confirm_discard_changes();
+                                    ui.close_current_popup();
+                                }
+
+                                ui.same_line(0.0);
+                                if ui.button(im_str!("Cancel"), [120.0, 0.0]) {
+                                    editor_state.enqueue_cancel_discard_changes();
+                                    ui.close_current_popup();
+                                }
+                            }
+                        });
+                }
+
                 ui.main_menu_bar(|| {
                     //axis-arrow
                     imgui_menu_tool_button(
@@ -90,15 +187,25 @@ pub fn editor_imgui_menu() -> Box<dyn Schedulable> {
                     imgui_menu_tool_button(ui, &mut *editor_state, EditorTool::Rotate, "\u{fd74}");
 
                     ui.menu(imgui::im_str!("File"), true, || {
-                        if imgui::MenuItem::new(imgui::im_str!("Open")).build(ui) {
-                            editor_state.enqueue_open_prefab(asset_uuid!(
-                                "3991506e-ed7e-4bcb-8cfd-3366b31a6439"
-                            ));
-                        }
+                        ui.menu(im_str!("Open"), true, || {
+                            for prefab_source_file in find_prefab_source_files() {
+                                if imgui::MenuItem::new(&im_str!("{}", prefab_source_file.name()))
+                                    .build(ui)
+                                {
+                                    editor_state.enqueue_open_prefab(prefab_source_file.uuid);
+                                }
+                            }
+                        });
 
                         if imgui::MenuItem::new(im_str!("Save")).build(ui) {
                             editor_state.enqueue_save_prefab();
                         }
+
+                        if let Some(active_document) = editor_state.active_document() {
+                            if imgui::MenuItem::new(im_str!("Close")).build(ui) {
+                                editor_state.enqueue_close_document(active_document);
+                            }
+                        }
                     });
 
                     ui.menu(imgui::im_str!("Edit"), true, || {
@@ -111,6 +218,66 @@ pub fn editor_imgui_menu() -> Box<dyn Schedulable> {
                         }
                     });
 
+                    ui.menu(im_str!("Gizmo"), true, || {
+                        ui.text(im_str!("Pivot"));
+                        let pivot_modes = [
+                            (
+                                GizmoPivotMode::IndividualOrigins,
+                                im_str!("Individual Origins"),
+                            ),
+                            (GizmoPivotMode::SelectionCenter, im_str!("Selection Center")),
+                            (GizmoPivotMode::ActiveEntity, im_str!("Active Entity")),
+                        ];
+
+                        for (pivot_mode, label) in &pivot_modes {
+                            if imgui::MenuItem::new(label)
+                                .selected(editor_state.gizmo_pivot_mode() == *pivot_mode)
+                                .build(ui)
+                            {
+                                editor_state.set_gizmo_pivot_mode(*pivot_mode);
+                            }
+                        }
+
+                        ui.separator();
+                        ui.text(im_str!("Translate Space"));
+                        let spaces = [
+                            (GizmoSpace::World, im_str!("World")),
+                            (GizmoSpace::Local, im_str!("Local")),
+                        ];
+
+                        for (space, label) in &spaces {
+                            if imgui::MenuItem::new(label)
+                                .selected(editor_state.gizmo_space() == *space)
+                                .build(ui)
+                            {
+                                editor_state.set_gizmo_space(*space);
+                            }
+                        }
+                    });
+
+                    let snap_settings = editor_state.snap_settings_mut();
+                    ui.menu(im_str!("Snap"), true, || {
+                        ui.checkbox(im_str!("Snap Enabled"), &mut snap_settings.snap_enabled);
+                        ui.checkbox(im_str!("Show Grid"), &mut snap_settings.show_grid);
+                        ui.input_float(im_str!("Grid Step"), &mut snap_settings.grid_step)
+                            .build();
+                        ui.input_float(
+                            im_str!("Rotate Step (Degrees)"),
+                            &mut snap_settings.rotate_step_degrees,
+                        )
+                        .build();
+                        ui.input_float(im_str!("Scale Step"), &mut snap_settings.scale_step)
+                            .build();
+                        ui.input_float(
+                            im_str!("Fine Adjustment Multiplier"),
+                            &mut snap_settings.fine_adjustment_multiplier,
+                        )
+                        .build();
+                        ui.text(im_str!(
+                            "Hold Ctrl to toggle snapping, Shift for fine adjustment"
+                        ));
+                    });
+
                     let window_settings = editor_state.window_options_mut();
                     ui.menu(im_str!("Windows"), true, || {
                         ui.checkbox(
@@ -127,6 +294,17 @@ pub fn editor_imgui_menu() -> Box<dyn Schedulable> {
                             &mut window_settings.show_entity_list,
                         );
                         ui.checkbox(im_str!("Inspector"), &mut window_settings.show_inspector);
+                        ui.checkbox(im_str!("Documents"), &mut window_settings.show_documents);
+                        ui.checkbox(
+                            im_str!("Undo History"),
+                            &mut window_settings.show_undo_history,
+                        );
+                        ui.checkbox(
+                            im_str!("Keybindings"),
+                            &mut window_settings.show_keybindings,
+                        );
+                        ui.checkbox(im_str!("Assets"), &mut window_settings.show_assets);
+                        ui.checkbox(im_str!("Layers"), &mut window_settings.show_layers);
                     });
 
                     ui.separator();
@@ -149,11 +327,48 @@ pub fn editor_imgui_menu() -> Box<dyn Schedulable> {
                         }
                     }
 
+                    // Runtime values are thrown away when play is reset unless they are kept
+                    if editor_state.is_playing() {
+                        if imgui::MenuItem::new(im_str!("Keep Changes")).build(ui) {
+                            editor_state.enqueue_keep_changes_from_play();
+                        }
+
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text(im_str!(
+                                "Copies the selected entities' current values into the prefab \
+                                 as an undoable change. Changes that aren't kept are lost on \
+                                 reset."
+                            ));
+                        }
+                    }
+
                     ui.text(im_str!(
                         "FPS: {:.1}",
                         time_state.system_time().updates_per_second_smoothed()
                     ));
 
+                    if editor_state.has_unsaved_changes() {
+                        ui.text(im_str!("* UNSAVED CHANGES"));
+                    }
+
+                    if editor_state.is_playing() {
+                        ui.text(im_str!("PLAYING"));
+                    }
+
+                    // Edits are rejected while playing. Say so, rather than silently dropping them
+                    if let Some(message) = editor_state.rejected_edit_message() {
+                        ui.text(im_str!("{} (reset to edit)", message));
+                    }
+
+                    if let Some(error) = editor_state.save_error() {
+                        ui.text_colored([1.0, 0.3, 0.3, 1.0], &im_str!("{}", error));
+                    }
+
+                    // A prefab that fails to load leaves the previous one open, so say why
+                    if let Some(error) = editor_state.load_error() {
+                        ui.text_colored([1.0, 0.3, 0.3, 1.0], &im_str!("{}", error));
+                    }
+
                     if time_state.is_simulation_paused() {
                         ui.text(im_str!("SIMULATION PAUSED"));
                     }
@@ -0,0 +1,97 @@
+use legion::prelude::*;
+
+use crate::resources::EditorStateResource;
+use crate::resources::ImguiResource;
+use crate::resources::MergeResolution;
+
+use imgui;
+use imgui::im_str;
+
+pub fn editor_merge_conflicts_window() -> Box<dyn Schedulable> {
+    SystemBuilder::new("editor_merge_conflicts_window")
+        .write_resource::<ImguiResource>()
+        .write_resource::<EditorStateResource>()
+        .build(|_, _, (imgui_manager, editor_state), _| {
+            imgui_manager.with_ui(|ui: &mut imgui::Ui| {
+                let mut apply_merge = false;
+                let mut abort_merge = false;
+
+                if let Some(pending_merge) = editor_state.pending_merge_mut() {
+                    ui.open_popup(im_str!("Merge Conflicts"));
+                    ui.popup_modal(im_str!("Merge Conflicts"))
+                        .always_auto_resize(true)
+                        .build(|| {
+                            ui.text(im_str!(
+                                "The prefab changed on disk. These components were also changed in the editor."
+                            ));
+                            ui.text(im_str!(
+                                "Taking theirs discards all of the editor's changes to that component."
+                            ));
+                            ui.separator();
+
+                            for (index, conflict) in pending_merge.conflicts.iter_mut().enumerate() {
+                                let id_token = ui.push_id(index as i32);
+
+                                ui.text(im_str!(
+                                    "{} on entity {}",
+                                    conflict.component_name,
+                                    uuid::Uuid::from_bytes(conflict.entity_uuid).to_string()
+                                ));
+
+                                ui.indent();
+                                for field in &conflict.fields {
+                                    let field_path = if field.field_path.is_empty() {
+                                        "<component>"
+                                    } else {
+                                        &field.field_path
+                                    };
+
+                                    ui.text(im_str!(
+                                        "{}: base {}, mine {}, theirs {}",
+                                        field_path,
+                                        field.base,
+                                        field.mine,
+                                        field.theirs
+                                    ));
+                                }
+
+                                ui.radio_button(
+                                    im_str!("Keep Mine"),
+                                    &mut conflict.resolution,
+                                    MergeResolution::KeepMine,
+                                );
+                                ui.same_line(0.0);
+                                ui.radio_button(
+                                    im_str!("Take Theirs"),
+                                    &mut conflict.resolution,
+                                    MergeResolution::TakeTheirs,
+                                );
+                                ui.unindent();
+
+                                id_token.pop(ui);
+                                ui.separator();
+                            }
+
+                            if ui.button(im_str!("Merge"), [160.0, 0.0]) {
+                                apply_merge = true;
+                                ui.close_current_popup();
+                            }
+
+                            ui.same_line(0.0);
+                            if ui.button(im_str!("Ignore File Changes"), [160.0, 0.0]) {
+                                abort_merge = true;
+                                ui.close_current_popup();
+                            }
+                        });
+                }
+
+                if apply_merge {
+                    editor_state.enqueue_apply_merge();
+                }
+
+                if abort_merge {
+                    editor_state.enqueue_abort_merge();
+                }
+            });
+        })
+}
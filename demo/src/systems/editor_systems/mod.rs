@@ -6,6 +6,7 @@ use crate::resources::{
 };
 use crate::resources::ImguiResource;
 use crate::resources::EditorTool;
+use crate::resources::EditorKeybindingsResource;
 use legion_transaction::{TransactionBuilder, Transaction};
 
 use imgui;
@@ -41,6 +42,27 @@ pub use entity_list_window::editor_entity_list_window;
 mod inspector_window;
 pub use inspector_window::editor_inspector_window;
 
+mod undo_history_window;
+pub use undo_history_window::editor_undo_history_window;
+
+mod merge_conflicts_window;
+pub use merge_conflicts_window::editor_merge_conflicts_window;
+
+mod documents_window;
+pub use documents_window::editor_documents_window;
+
+mod keybindings_window;
+pub use keybindings_window::editor_keybindings_window;
+
+mod assets_window;
+pub use assets_window::editor_assets_window;
+
+mod layers_window;
+pub use layers_window::editor_layers_window;
+
+mod command_palette_window;
+pub use command_palette_window::editor_command_palette_window;
+
 mod selection;
 pub use selection::draw_selection_shapes;
 pub use selection::editor_handle_selection;
@@ -48,6 +70,9 @@ pub use selection::editor_handle_selection;
 mod gizmos;
 pub use gizmos::editor_gizmos;
 
+mod grid;
+pub use grid::editor_draw_grid;
+
 pub fn editor_refresh_selection_world(
     world: &mut World,
     resources: &mut Resources,
@@ -93,43 +118,34 @@ pub fn editor_process_editor_ops(
     EditorStateResource::process_editor_ops(world, resources);
 }
 
+pub fn editor_write_edit_journal() -> Box<dyn Schedulable> {
+    SystemBuilder::new("editor_write_edit_journal")
+        .write_resource::<EditorStateResource>()
+        .build(|_, _, editor_state, _| {
+            editor_state.write_edit_journal_if_due();
+        })
+}
+
 pub fn editor_keybinds() -> Box<dyn Schedulable> {
-    SystemBuilder::new("editor_input")
+    SystemBuilder::new("editor_keybinds")
         .write_resource::<EditorStateResource>()
+        .write_resource::<EditorKeybindingsResource>()
         .read_resource::<InputResource>()
-        .read_resource::<ViewportResource>()
-        .write_resource::<EditorSelectionResource>()
-        .write_resource::<DebugDrawResource>()
-        .write_resource::<EditorDrawResource>()
-        .read_resource::<UniverseResource>()
-        .with_query(<(Read<Position2DComponent>)>::query())
+        .read_resource::<ImguiResource>()
         .build(
-            |command_buffer,
-             subworld,
-             (
-                editor_state,
-                input_state,
-                viewport,
-                editor_selection,
-                debug_draw,
-                editor_draw,
-                universe_resource,
-            ),
-             (position_query)| {
-                if input_state.is_key_just_down(VirtualKeyCode::Key1) {
-                    editor_state.enqueue_set_active_editor_tool(EditorTool::Translate);
-                }
-
-                if input_state.is_key_just_down(VirtualKeyCode::Key2) {
-                    editor_state.enqueue_set_active_editor_tool(EditorTool::Scale);
+            |_, _, (editor_state, keybindings, input_state, imgui_manager), _| {
+                // Keys typed into imgui (i.e. a text field) shouldn't trigger actions
+                if imgui_manager.want_capture_keyboard() {
+                    return;
                 }
 
-                if input_state.is_key_just_down(VirtualKeyCode::Key3) {
-                    editor_state.enqueue_set_active_editor_tool(EditorTool::Rotate);
+                // If the user is picking a new key for an action, the key shouldn't do anything else
+                if keybindings.update_rebinding(&*input_state) {
+                    return;
                 }
 
-                if input_state.is_key_just_down(VirtualKeyCode::Space) {
-                    editor_state.enqueue_toggle_pause();
+                for action in keybindings.just_pressed_actions(&*input_state) {
+                    action.enqueue(&mut *editor_state);
                 }
             },
         )
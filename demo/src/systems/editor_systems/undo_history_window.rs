@@ -0,0 +1,83 @@
+use legion::prelude::*;
+
+use crate::resources::EditorStateResource;
+use crate::resources::ImguiResource;
+
+use imgui;
+use imgui::im_str;
+
+pub fn editor_undo_history_window() -> Box<dyn Schedulable> {
+    SystemBuilder::new("editor_undo_history_window")
+        .write_resource::<ImguiResource>()
+        .write_resource::<EditorStateResource>()
+        .build(|_, _, (imgui_manager, editor_state), _| {
+            imgui_manager.with_ui(|ui: &mut imgui::Ui| {
+                if !editor_state.window_options().show_undo_history {
+                    return;
+                }
+
+                imgui::Window::new(im_str!("Undo History"))
+                    .position([0.0, 600.0], imgui::Condition::FirstUseEver)
+                    .size([350.0, 200.0], imgui::Condition::FirstUseEver)
+                    .build(ui, || {
+                        let mut max_undo_steps = editor_state.max_undo_steps() as i32;
+                        if ui
+                            .input_int(im_str!("Max Steps"), &mut max_undo_steps)
+                            .build()
+                        {
+                            editor_state.set_max_undo_steps(max_undo_steps.max(1) as usize);
+                        }
+
+                        ui.separator();
+
+                        // Position 0 means nothing is applied, so list it as the initial state.
+                        // Every following entry corresponds to the state after that step
+                        let undo_chain_position = editor_state.undo_chain_position();
+                        let mut jump_to_position = None;
+
+                        if imgui::Selectable::new(im_str!("<Opened Prefab>"))
+                            .selected(undo_chain_position == 0)
+                            .build(ui)
+                        {
+                            jump_to_position = Some(0);
+                        }
+
+                        for (index, undo_step) in editor_state.undo_chain().iter().enumerate() {
+                            let position = index + 1;
+
+                            // Steps that have been undone (and can be redone) are greyed out
+                            let color_stack_token = if position > undo_chain_position {
+                                Some(ui.push_style_color(
+                                    imgui::StyleColor::Text,
+                                    [0.5, 0.5, 0.5, 1.0],
+                                ))
+                            } else {
+                                None
+                            };
+
+                            if imgui::Selectable::new(&im_str!(
+                                "{}: {}##undo_step_{}",
+                                position,
+                                undo_step.description(),
+                                position
+                            ))
+                            .selected(position == undo_chain_position)
+                            .build(ui)
+                            {
+                                jump_to_position = Some(position);
+                            }
+
+                            if let Some(color_stack_token) = color_stack_token {
+                                color_stack_token.pop(ui);
+                            }
+                        }
+
+                        if let Some(jump_to_position) = jump_to_position {
+                            if jump_to_position != undo_chain_position {
+                                editor_state.enqueue_jump_to_undo_position(jump_to_position);
+                            }
+                        }
+                    });
+            });
+        })
+}
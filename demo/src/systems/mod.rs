@@ -8,11 +8,10 @@ pub use physics_systems::read_from_physics;
 mod asset_manager_systems;
 pub use asset_manager_systems::update_asset_manager;
 
-mod app_control_systems;
-pub use app_control_systems::quit_if_escape_pressed;
-
 mod draw_systems;
 pub use draw_systems::draw;
+pub use draw_systems::update_sprite_images;
+pub use draw_systems::update_vector_shapes;
 
 mod time_systems;
 pub use time_systems::advance_time;
@@ -27,12 +26,21 @@ pub use editor_systems::editor_keybinds;
 pub use editor_systems::editor_mouse_input;
 pub use editor_systems::editor_update_editor_draw;
 pub use editor_systems::editor_gizmos;
+pub use editor_systems::editor_draw_grid;
 pub use editor_systems::editor_handle_selection;
 pub use editor_systems::draw_selection_shapes;
 pub use editor_systems::editor_refresh_selection_world;
 pub use editor_systems::editor_entity_list_window;
 pub use editor_systems::editor_process_selection_ops;
 pub use editor_systems::editor_inspector_window;
+pub use editor_systems::editor_undo_history_window;
+pub use editor_systems::editor_merge_conflicts_window;
+pub use editor_systems::editor_documents_window;
+pub use editor_systems::editor_keybindings_window;
+pub use editor_systems::editor_assets_window;
+pub use editor_systems::editor_layers_window;
+pub use editor_systems::editor_command_palette_window;
+pub use editor_systems::editor_write_edit_journal;
 pub use editor_systems::reload_editor_state_if_file_changed;
 pub use editor_systems::editor_process_edit_diffs;
 
@@ -134,8 +142,9 @@ pub fn create_update_schedule(criteria: &ScheduleCriteria) -> Schedule {
     ScheduleBuilder::new(criteria)
         .always(update_input_resource)
         .always(advance_time)
-        .always(quit_if_escape_pressed)
         .always(update_asset_manager)
+        .always(update_sprite_images)
+        .always(update_vector_shapes)
         .always(update_fps_text)
         .always(update_physics)
         .simulation_unpaused_only(read_from_physics)
@@ -147,15 +156,24 @@ pub fn create_update_schedule(criteria: &ScheduleCriteria) -> Schedule {
         .always(editor_keybinds)
         .always(editor_mouse_input)
         .always(editor_update_editor_draw)
+        .always(editor_draw_grid)
         .always(editor_gizmos)
         .always(editor_handle_selection)
         .always(editor_imgui_menu)
         .always(editor_entity_list_window)
         .always_thread_local(editor_inspector_window)
+        .always(editor_undo_history_window)
+        .always(editor_merge_conflicts_window)
+        .always(editor_documents_window)
+        .always(editor_keybindings_window)
+        .always(editor_assets_window)
+        .always(editor_layers_window)
+        .always_thread_local(editor_command_palette_window)
         // Editor processing
         .always_thread_local(editor_process_edit_diffs)
         .always_thread_local(editor_process_selection_ops)
         .always_thread_local(editor_process_editor_ops)
+        .always(editor_write_edit_journal)
         // Editor output
         .always(draw_selection_shapes)
         // --- End editor stuff ---